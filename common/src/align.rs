@@ -0,0 +1,30 @@
+use skia_safe::{Canvas, Font, Paint, Point, Rect};
+
+/// Where to put the baseline origin of `text` so that it ends up centered in
+/// `rect`, both ways.
+///
+/// Horizontally the text's advance width is centered, not counting trailing
+/// whitespace. Vertically it's the line box, from the font's ascent to its
+/// descent, rather than the ink of these particular glyphs: labels like
+/// "Save" and "yes" then share a baseline when their boxes line up, instead
+/// of each one jumping by the height of its descenders. Caps and x-height
+/// letters come out very slightly above true center, because the ascent
+/// leaves room for accents.
+pub fn centered_origin(text: &str, font: &Font, rect: Rect) -> Point {
+    // Trailing spaces take up no room at the end of a line, so they'd only
+    // push the visible text off center (see `shaping::TrailingWhitespace`).
+    let (width, _) = font.measure_str(text.trim_end(), None);
+    let (_, metrics) = font.metrics();
+    // Skia's ascent is negative (above the baseline), descent positive.
+    let line_center = (metrics.ascent + metrics.descent) / 2.0;
+    Point::new(rect.center_x() - width / 2.0, rect.center_y() - line_center)
+}
+
+/// Draws `text` centered in `rect` (see `centered_origin`), e.g. a button
+/// label. Text that doesn't fit is clipped to the rect.
+pub fn draw_text_centered(canvas: &Canvas, text: &str, font: &Font, paint: &Paint, rect: Rect) {
+    canvas.save();
+    canvas.clip_rect(rect, None, true);
+    canvas.draw_str(text, centered_origin(text, font, rect), font, paint);
+    canvas.restore();
+}
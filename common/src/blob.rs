@@ -0,0 +1,37 @@
+use crate::shaping::GlyphRun;
+use skia_safe::{Font, Point, TextBlob, TextBlobBuilder};
+
+/// Shaped glyphs and the font they were shaped with, as one piece of a line.
+#[derive(Clone)]
+pub struct FontRun {
+    pub font: Font,
+    pub glyphs: GlyphRun,
+}
+
+/// Packs `runs` into one blob, laid out left to right on a baseline at y = 0.
+///
+/// Each run becomes its own blob run (a blob run has exactly one font), and
+/// starts where the previous run's advances ended. Within a run every glyph
+/// gets its own point from `alloc_run_pos`: the pen position plus the
+/// shaper's x/y offset, which is already what `GlyphRun::positions` holds.
+/// So offsets (mark positioning, superscripts) come out right, instead of
+/// every glyph sitting at the pen position.
+///
+/// Returns `None` if there are no glyphs at all, like `TextBlobBuilder`.
+pub fn build_blob(runs: &[FontRun]) -> Option<TextBlob> {
+    let mut builder = TextBlobBuilder::new();
+    let mut pen_x = 0.0;
+    for run in runs {
+        let glyph_run = &run.glyphs;
+        let count = glyph_run.glyphs.len();
+        if count > 0 {
+            let (glyphs, positions) = builder.alloc_run_pos(&run.font, count, None);
+            glyphs.copy_from_slice(&glyph_run.glyphs);
+            for (position, glyph_position) in positions.iter_mut().zip(&glyph_run.positions) {
+                *position = Point::new(pen_x, 0.0) + *glyph_position;
+            }
+        }
+        pen_x += glyph_run.width;
+    }
+    builder.make()
+}
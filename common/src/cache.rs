@@ -0,0 +1,97 @@
+use crate::font::LoadedFont;
+use crate::shaping::{shape_text, GlyphRun};
+use std::collections::HashMap;
+
+/// Everything that affects the shaped result of a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    /// Skia's unique id for the typeface.
+    font_id: u32,
+    /// `f32` isn't `Hash`, so sizes and axis values are keyed by their bits.
+    size_bits: u32,
+    variations: Vec<([u8; 4], u32)>,
+    text: String,
+}
+
+impl CacheKey {
+    fn new(font: &LoadedFont, text: &str) -> Self {
+        CacheKey {
+            font_id: font.typeface.unique_id(),
+            size_bits: font.px_size.to_bits(),
+            variations: font
+                .variations
+                .iter()
+                .map(|&(tag, value)| (tag, value.to_bits()))
+                .collect(),
+            text: text.to_string(),
+        }
+    }
+}
+
+/// A least-recently-used cache of shaped text.
+///
+/// Laying out a UI often measures the same labels over and over, and each
+/// measurement means a full HarfBuzz shaping pass. The cache remembers the
+/// `GlyphRun` (and with it the width) per font, size, variation settings and
+/// text, so changing any of those naturally misses the cache instead of
+/// returning a stale result.
+///
+/// Eviction scans all entries for the oldest one, which is fine for the few
+/// hundred entries a label cache typically holds.
+pub struct ShapeCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, (GlyphRun, u64)>,
+    /// Incremented on every lookup; an entry's value is its last use.
+    clock: u64,
+}
+
+impl ShapeCache {
+    pub fn new(capacity: usize) -> Self {
+        ShapeCache {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Shapes `text` with `font`, or returns the cached result.
+    pub fn measure_shaped(&mut self, font: &LoadedFont, text: &str) -> &GlyphRun {
+        self.clock += 1;
+        let key = CacheKey::new(font, text);
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_oldest();
+        }
+        let clock = self.clock;
+        let entry = self
+            .entries
+            .entry(key)
+            .or_insert_with(|| (shape_text(&font.hb_font, text, &[]), clock));
+        entry.1 = clock;
+        &entry.0
+    }
+
+    /// Whether `text` with `font` is cached, without counting as a use.
+    pub fn contains(&self, font: &LoadedFont, text: &str) -> bool {
+        self.entries.contains_key(&CacheKey::new(font, text))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use harfbuzz_rs::{GlyphBuffer, GlyphInfo};
+
+/// Maps each shaped glyph back to the text it came from.
+///
+/// Returns `(glyph_index, byte_start, byte_end)` for every glyph, in glyph
+/// order, where `text[byte_start..byte_end]` is the glyph's cluster. This is
+/// what selection, caret movement and screen readers need.
+///
+/// HarfBuzz only tells us where each cluster *starts* (`GlyphInfo::cluster`,
+/// a byte offset into the shaped text). A cluster ends where the next larger
+/// cluster value starts, or at `text_len`. That covers both directions of
+/// many-to-many mapping:
+///
+/// - a ligature ("ffi") is one glyph whose range spans several characters;
+/// - a decomposition (a base letter plus a separately drawn combining mark)
+///   is several glyphs that all report the same range.
+///
+/// It also works for RTL runs, where cluster values decrease along the glyphs.
+pub fn cluster_map(infos: &[GlyphInfo], text_len: usize) -> Vec<(usize, usize, usize)> {
+    let mut starts: Vec<usize> = infos.iter().map(|info| info.cluster as usize).collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    infos
+        .iter()
+        .enumerate()
+        .map(|(glyph_index, info)| {
+            let start = info.cluster as usize;
+            // `starts` is sorted and contains `start`, so the next entry (if
+            // any) is where the following cluster begins.
+            let next = starts.partition_point(|&s| s <= start);
+            let end = starts.get(next).copied().unwrap_or(text_len);
+            (glyph_index, start, end)
+        })
+        .collect()
+}
+
+/// Where a caret before the `caret`th character goes, as an x offset in
+/// pixels from the start of `shaped`, a left-to-right line shaped from
+/// `text` with the font's scale in 26.6 fixed point.
+///
+/// The caret goes at the pen position of the first glyph whose cluster
+/// starts at or after the character. The cluster map tells us which text
+/// each glyph came from, which is what makes this work when glyphs and
+/// characters don't line up one to one. A ligature like "ffi" is one glyph
+/// for three characters, so a caret inside it gets a share of the ligature's
+/// advance per character, the way browsers place it.
+pub fn caret_offset(text: &str, shaped: &GlyphBuffer, caret: usize) -> f32 {
+    // Character index => byte offset, since clusters are byte offsets.
+    let index = text.char_indices().nth(caret).map_or(text.len(), |(i, _)| i);
+
+    let clusters = cluster_map(shaped.get_glyph_infos(), text.len());
+    let mut pen = 0.0;
+    for ((_, start, end), pos) in clusters.into_iter().zip(shaped.get_glyph_positions()) {
+        let advance = pos.x_advance as f32 / 64.0;
+        if index <= start {
+            return pen;
+        }
+        if index < end {
+            let before = text[start..index].chars().count();
+            let total = text[start..end].chars().count();
+            return pen + advance * before as f32 / total as f32;
+        }
+        pen += advance;
+    }
+    pen
+}
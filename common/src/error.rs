@@ -0,0 +1,30 @@
+use thiserror::Error;
+
+/// Errors from loading fonts, shaping, and rendering text.
+///
+/// The examples started out returning `Box<dyn Error>` built from string
+/// literals, which is fine for printing but leaves a caller nothing to match
+/// on. These variants carry the details (which file, what size) instead.
+#[derive(Debug, Error)]
+pub enum TextError {
+    #[error("failed to load font from {path}")]
+    FontLoad { path: String },
+
+    #[error("failed to create a {width}x{height} surface")]
+    SurfaceCreation { width: i32, height: i32 },
+
+    #[error("failed to shape text: {0}")]
+    Shaping(String),
+
+    #[error("failed to encode image as {format}")]
+    Encode { format: &'static str },
+
+    #[error("failed to load image from {path}")]
+    ImageLoad { path: String },
+
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
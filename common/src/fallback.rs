@@ -0,0 +1,60 @@
+use crate::style::TextStyle;
+use skia_safe::{Font, GlyphId, Typeface};
+use std::collections::BTreeSet;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Splits `text` into runs that `style` can draw, switching to `fallback`
+/// for grapheme clusters the style's typeface has no glyphs for.
+///
+/// Each run is returned with the style to draw it in: either `style` itself,
+/// or `style` with the fallback typeface swapped in. Whole grapheme clusters
+/// go into a run, so a base letter and its combining marks (or a
+/// multi-codepoint emoji) are always drawn together.
+pub fn fallback_runs(text: &str, style: &TextStyle, fallback: &Typeface) -> Vec<(String, TextStyle)> {
+    let primary_font = style.font();
+    let fallback_style = TextStyle {
+        typeface: fallback.clone(),
+        ..style.clone()
+    };
+
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for grapheme in text.graphemes(true) {
+        let use_primary = has_glyphs(&primary_font, grapheme);
+        match runs.last_mut() {
+            Some((run, primary)) if *primary == use_primary => run.push_str(grapheme),
+            _ => runs.push((grapheme.to_string(), use_primary)),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(run, use_primary)| {
+            let run_style = if use_primary { style } else { &fallback_style };
+            (run, run_style.clone())
+        })
+        .collect()
+}
+
+/// Checks if a font has glyphs for every character of a grapheme cluster
+/// (e.g. both the base letter and its combining mark).
+pub fn has_glyphs(font: &Font, grapheme: &str) -> bool {
+    let num_chars = grapheme.chars().count();
+    let mut glyphs = vec![0 as GlyphId; num_chars];
+    let count = font.text_to_glyphs(grapheme, glyphs.as_mut_slice());
+    count > 0 && glyphs.iter().all(|&glyph| glyph != 0)
+}
+
+/// Returns the characters in `text` that `font` has no glyph for (glyph id
+/// 0, which would draw as a box or nothing), each once and sorted.
+///
+/// This is `has_glyphs` for a whole string at once: a single
+/// `str_to_glyphs_vec` call maps every character, which is much cheaper than
+/// one call per grapheme for long texts. Control characters (`\n`, `\t`)
+/// are left out, since they aren't drawn with glyphs anyway.
+pub fn missing_chars(font: &Font, text: &str) -> BTreeSet<char> {
+    let glyphs = font.str_to_glyphs_vec(text);
+    text.chars()
+        .zip(glyphs)
+        .filter(|&(c, glyph)| glyph == 0 && !c.is_control())
+        .map(|(c, _)| c)
+        .collect()
+}
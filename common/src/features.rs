@@ -0,0 +1,57 @@
+use crate::info::tag_name;
+use std::collections::{BTreeMap, BTreeSet};
+use ttf_parser::{Face, Tag};
+
+/// The HarfBuzz tag for a four-byte OpenType tag, e.g. `*b"frac"` or
+/// `*b"wght"`.
+pub fn tag_from_bytes(tag: [u8; 4]) -> harfbuzz_rs::Tag {
+    harfbuzz_rs::Tag::new(tag[0] as char, tag[1] as char, tag[2] as char, tag[3] as char)
+}
+
+/// Returns true if the font's GSUB or GPOS table defines the feature `tag`
+/// (e.g. `*b"frac"`) for any script.
+///
+/// HarfBuzz silently ignores features a font doesn't have, so this is only
+/// needed to tell the user why a requested feature made no difference.
+pub fn supports_feature(font_data: &[u8], tag: [u8; 4]) -> bool {
+    let Ok(face) = Face::parse(font_data, 0) else {
+        return false;
+    };
+    let tag = Tag::from_bytes(&tag);
+    let tables = face.tables();
+    [tables.gsub, tables.gpos]
+        .into_iter()
+        .flatten()
+        .any(|table| table.features.find(tag).is_some())
+}
+
+/// Lists the OpenType features the font defines, grouped by script: for each
+/// script tag in GSUB or GPOS (e.g. "DFLT", "latn", "cyrl"), the sorted tags
+/// of the features its default language system or any of its specific
+/// languages use, including required features.
+///
+/// These are the tags worth passing to the shaper for text in that script;
+/// anything else is ignored. Returns an empty map if the data isn't a font
+/// ttf-parser can read.
+pub fn features_by_script(font_data: &[u8]) -> BTreeMap<String, BTreeSet<String>> {
+    let mut scripts: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let Ok(face) = Face::parse(font_data, 0) else {
+        return scripts;
+    };
+    let tables = face.tables();
+    for table in [tables.gsub, tables.gpos].into_iter().flatten() {
+        for script in table.scripts {
+            let features = scripts.entry(tag_name(script.tag)).or_default();
+            let languages = script.default_language.into_iter().chain(script.languages);
+            for language in languages {
+                let indices = language.required_feature.into_iter().chain(language.feature_indices);
+                for index in indices {
+                    if let Some(feature) = table.features.get(index) {
+                        features.insert(tag_name(feature.tag));
+                    }
+                }
+            }
+        }
+    }
+    scripts
+}
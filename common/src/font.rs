@@ -0,0 +1,138 @@
+use crate::features::tag_from_bytes;
+use harfbuzz_rs::{Face as HbFace, Font as HbFont, Owned, Variation};
+use skia_safe::{font::Edging, Data, Font, FontMgr, Typeface};
+use std::error::Error;
+
+/// One font file, loaded once for every library that needs it.
+///
+/// The examples used to `fs::read` a font and then hand copies of the bytes
+/// to Skia, HarfBuzz and FreeType separately. This builds all of them from a
+/// single byte slice, which also works for fonts baked into the binary with
+/// `include_bytes!`.
+pub struct LoadedFont<'a> {
+    /// The Skia typeface, used for drawing.
+    pub typeface: Typeface,
+    /// A HarfBuzz font scaled to `px_size` (in 26.6 fixed point), used for shaping.
+    pub hb_font: Owned<HbFont<'a>>,
+    /// A FreeType face sized to `px_size`, used for glyph outlines.
+    #[cfg(feature = "freetype")]
+    pub ft_face: freetype::Face,
+    /// The pixel size everything above was set up for.
+    pub px_size: f32,
+    /// Variable font axis settings, e.g. `(*b"wght", 600.0)`. Use
+    /// `set_variations` to change them.
+    pub variations: Vec<([u8; 4], f32)>,
+}
+
+impl<'a> LoadedFont<'a> {
+    /// Loads the first face in `bytes` at `px_size` pixels.
+    pub fn from_bytes(bytes: &'a [u8], px_size: f32) -> Result<Self, Box<dyn Error>> {
+        // Skia keeps its own reference-counted copy of the data.
+        let typeface = FontMgr::new()
+            .new_from_data(&Data::new_copy(bytes), None)
+            .ok_or("Failed to load typeface")?;
+
+        // HarfBuzz borrows the bytes, hence the lifetime on `LoadedFont`.
+        let mut hb_font = HbFont::new(HbFace::from_bytes(bytes, 0));
+        let hb_scale = (px_size * 64.0).round() as i32;
+        hb_font.set_scale(hb_scale, hb_scale);
+
+        // The face keeps the FreeType library alive, so it can go out of scope here.
+        #[cfg(feature = "freetype")]
+        let ft_face = {
+            let library = freetype::Library::init()?;
+            let ft_face = library.new_memory_face(bytes.to_vec(), 0)?;
+            // Sized in 26.6 points at 72 dpi (i.e. pixels), since
+            // `set_pixel_sizes` would round fractional sizes down.
+            ft_face.set_char_size(0, hb_scale as isize, 72, 72)?;
+            ft_face
+        };
+
+        Ok(LoadedFont {
+            typeface,
+            hb_font,
+            #[cfg(feature = "freetype")]
+            ft_face,
+            px_size,
+            variations: Vec::new(),
+        })
+    }
+
+    /// Changes the pixel size everything is set up for.
+    pub fn set_size(&mut self, px_size: f32) -> Result<(), Box<dyn Error>> {
+        let hb_scale = (px_size * 64.0).round() as i32;
+        self.hb_font.set_scale(hb_scale, hb_scale);
+        #[cfg(feature = "freetype")]
+        self.ft_face.set_char_size(0, hb_scale as isize, 72, 72)?;
+        self.px_size = px_size;
+        Ok(())
+    }
+
+    /// Sets variable font axes (e.g. weight) for shaping.
+    pub fn set_variations(&mut self, variations: &[([u8; 4], f32)]) {
+        self.hb_font.set_variations(&hb_variations(variations));
+        self.variations = variations.to_vec();
+    }
+
+    /// Creates a Skia `Font` matching the size HarfBuzz shapes at.
+    pub fn skia_font(&self) -> Font {
+        let mut font = Font::from_typeface(self.typeface.clone(), self.px_size);
+        font.set_edging(Edging::SubpixelAntiAlias);
+        font
+    }
+}
+
+/// `variations` (as from `parse_variations`) as HarfBuzz takes them.
+pub fn hb_variations(variations: &[([u8; 4], f32)]) -> Vec<Variation> {
+    variations
+        .iter()
+        .map(|&(tag, value)| Variation::new(tag_from_bytes(tag), value))
+        .collect()
+}
+
+/// Matches `variations` to a variable font's `axes`, given as each axis's
+/// tag and default value in the font's own order. Returns a value for every
+/// axis (the requested one, or else the default), as FreeType wants them,
+/// and the requested tags the font has no axis for.
+pub fn match_axes(
+    axes: &[([u8; 4], f32)],
+    variations: &[([u8; 4], f32)],
+) -> (Vec<f32>, Vec<[u8; 4]>) {
+    let values = axes
+        .iter()
+        .map(|&(tag, default)| {
+            variations
+                .iter()
+                .find(|(wanted, _)| *wanted == tag)
+                .map_or(default, |&(_, value)| value)
+        })
+        .collect();
+    let unknown = variations
+        .iter()
+        .map(|&(tag, _)| tag)
+        .filter(|tag| !axes.iter().any(|(axis, _)| axis == tag))
+        .collect();
+    (values, unknown)
+}
+
+/// Parses variation settings like `"wght=600,wdth=75"` into `(tag, value)`
+/// pairs, as taken by `LoadedFont::set_variations`.
+///
+/// This is the same syntax as CSS `font-variation-settings` and HarfBuzz's
+/// `hb-view --variations`, minus the quotes.
+pub fn parse_variations(spec: &str) -> Result<Vec<([u8; 4], f32)>, Box<dyn Error>> {
+    spec.split(',')
+        .map(|setting| {
+            let (tag, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("Expected `axis=value`, got `{setting}`"))?;
+            let tag: [u8; 4] = tag
+                .trim()
+                .as_bytes()
+                .try_into()
+                .map_err(|_| format!("Axis tags are 4 characters, got `{tag}`"))?;
+            Ok((tag, value.trim().parse()?))
+        })
+        .collect()
+}
+
@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use ttf_parser::{name_id, Face, Tag};
+
+/// The container format of a font file, from its first four bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontFormat {
+    /// An sfnt with TrueType (quadratic) outlines, usually `.ttf`.
+    TrueType,
+    /// An sfnt with CFF (cubic) outlines, usually `.otf`.
+    OpenTypeCff,
+    /// Several sfnt fonts in one file (`.ttc`).
+    Collection,
+    /// A zlib-compressed web font.
+    Woff,
+    /// A Brotli-compressed web font.
+    Woff2,
+}
+
+impl fmt::Display for FontFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FontFormat::TrueType => "TrueType (TTF)",
+            FontFormat::OpenTypeCff => "OpenType/CFF (OTF)",
+            FontFormat::Collection => "font collection (TTC)",
+            FontFormat::Woff => "WOFF",
+            FontFormat::Woff2 => "WOFF2",
+        })
+    }
+}
+
+/// Detects the font format from the file's magic number, or `None` if the
+/// data doesn't look like a font at all.
+///
+/// The file extension is only a hint (plenty of `.ttf` files have CFF
+/// outlines), so this looks at the data itself.
+pub fn detect_format(data: &[u8]) -> Option<FontFormat> {
+    match data.get(..4)? {
+        [0x00, 0x01, 0x00, 0x00] | b"true" => Some(FontFormat::TrueType),
+        b"OTTO" => Some(FontFormat::OpenTypeCff),
+        b"ttcf" => Some(FontFormat::Collection),
+        b"wOFF" => Some(FontFormat::Woff),
+        b"wOF2" => Some(FontFormat::Woff2),
+        _ => None,
+    }
+}
+
+/// What's in a font file, for telling the user what they loaded.
+#[derive(Debug, Clone)]
+pub struct FontInfo {
+    pub format: FontFormat,
+    /// Number of faces in the file (more than one only for collections).
+    /// Everything below describes the first one.
+    pub face_count: u32,
+    pub family: Option<String>,
+    /// The style within the family, e.g. "Light Italic".
+    pub style: Option<String>,
+    pub units_per_em: u16,
+    pub glyph_count: u16,
+    /// OpenType feature tags from GSUB and GPOS, sorted, e.g. "kern", "liga".
+    pub features: Vec<String>,
+    /// OpenType script tags from GSUB and GPOS, sorted, e.g. "arab", "latn".
+    pub scripts: Vec<String>,
+}
+
+/// Reports the format, names, metrics and OpenType layout features of a font.
+///
+/// Returns `None` if the data isn't a font ttf-parser can read. That includes
+/// WOFF and WOFF2: they wrap the same tables in compression, and have to be
+/// decompressed first (`detect_format` still recognizes them).
+pub fn font_info(data: &[u8]) -> Option<FontInfo> {
+    let format = detect_format(data)?;
+    let face = Face::parse(data, 0).ok()?;
+
+    // Prefer the typographic names (IDs 16 and 17), which group all weights
+    // into one family. The legacy ones (IDs 1 and 2) only allow the four
+    // styles regular, italic, bold and bold italic, so fonts that have more
+    // put the weight in the family name instead, e.g. "Roboto Light".
+    let name = |preferred: u16, legacy: u16| {
+        [preferred, legacy].into_iter().find_map(|id| {
+            face.names()
+                .into_iter()
+                .filter(|name| name.name_id == id && name.is_unicode())
+                .find_map(|name| name.to_string())
+        })
+    };
+
+    let tables = face.tables();
+    let layout_tables = || [tables.gsub, tables.gpos].into_iter().flatten();
+    let features: BTreeSet<String> = layout_tables()
+        .flat_map(|table| table.features)
+        .map(|feature| tag_name(feature.tag))
+        .collect();
+    let scripts: BTreeSet<String> = layout_tables()
+        .flat_map(|table| table.scripts)
+        .map(|script| tag_name(script.tag))
+        .collect();
+
+    Some(FontInfo {
+        format,
+        face_count: ttf_parser::fonts_in_collection(data).unwrap_or(1),
+        family: name(name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY),
+        style: name(name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY),
+        units_per_em: face.units_per_em(),
+        glyph_count: face.number_of_glyphs(),
+        features: features.into_iter().collect(),
+        scripts: scripts.into_iter().collect(),
+    })
+}
+
+/// A tag as text. Tags are 4 ASCII characters, padded with spaces.
+pub(crate) fn tag_name(tag: Tag) -> String {
+    String::from_utf8_lossy(&tag.to_bytes()).trim_end().to_string()
+}
+
+impl fmt::Display for FontInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unknown = "(unknown)";
+        writeln!(f, "Format:       {}", self.format)?;
+        if self.face_count > 1 {
+            writeln!(f, "Faces:        {} (showing the first)", self.face_count)?;
+        }
+        writeln!(f, "Family:       {}", self.family.as_deref().unwrap_or(unknown))?;
+        writeln!(f, "Style:        {}", self.style.as_deref().unwrap_or(unknown))?;
+        writeln!(f, "Units per em: {}", self.units_per_em)?;
+        writeln!(f, "Glyphs:       {}", self.glyph_count)?;
+        writeln!(f, "Scripts:      {}", self.scripts.join(" "))?;
+        writeln!(f, "Features:     {}", self.features.join(" "))
+    }
+}
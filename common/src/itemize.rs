@@ -0,0 +1,71 @@
+use crate::script_fonts::script_runs;
+use crate::style::TextStyle;
+use std::ops::Range;
+use unicode_bidi::BidiInfo;
+use unicode_script::Script;
+
+/// A piece of text that can be shaped in one go: one style, one script and
+/// one direction throughout.
+#[derive(Clone)]
+pub struct Run {
+    /// Byte range of the run in the itemized text.
+    pub range: Range<usize>,
+    pub style: TextStyle,
+    /// See `script_runs` for how spaces, digits and punctuation are
+    /// assigned.
+    pub script: Script,
+    /// The bidi embedding level: even is left-to-right, odd is
+    /// right-to-left, and a higher level is nested in a lower one (digits in
+    /// Arabic text are a level above it).
+    pub level: u8,
+}
+
+impl Run {
+    pub fn is_rtl(&self) -> bool {
+        self.level % 2 == 1
+    }
+}
+
+/// Splits `text` into the runs a shaper needs, in logical order: a new run
+/// starts wherever the style, the script or the bidi level changes, and
+/// nowhere else.
+///
+/// `styles` are byte ranges of `text` with the style for each, in order and
+/// not overlapping. Text they don't cover is left out of the runs. Two
+/// neighbouring ranges stay separate runs even if their styles are alike.
+///
+/// The bidi levels are resolved over the whole text, with each paragraph
+/// (`\n` starts a new one) taking its direction from its first strong
+/// character. Reordering the runs for display is left to the caller, line
+/// by line, once the text is wrapped.
+pub fn itemize(text: &str, styles: &[(Range<usize>, TextStyle)]) -> Vec<Run> {
+    let bidi_info = BidiInfo::new(text, None);
+    // Script runs cover the text in order, and the spans are in order too,
+    // so each character's script run is at or after the previous one's.
+    let scripts = script_runs(text);
+    let mut script_index = 0;
+    let mut runs: Vec<Run> = Vec::new();
+    for (span, style) in styles {
+        let span = span.start.min(text.len())..span.end.min(text.len());
+        let span_start = runs.len();
+        for (offset, c) in text[span.clone()].char_indices() {
+            let start = span.start + offset;
+            let end = start + c.len_utf8();
+            while scripts[script_index].0.end <= start {
+                script_index += 1;
+            }
+            let script = scripts[script_index].1;
+            let level = bidi_info.levels[start].number();
+            match runs[span_start..].last_mut() {
+                Some(run) if run.script == script && run.level == level => run.range.end = end,
+                _ => runs.push(Run {
+                    range: start..end,
+                    style: style.clone(),
+                    script,
+                    level,
+                }),
+            }
+        }
+    }
+    runs
+}
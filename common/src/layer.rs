@@ -0,0 +1,29 @@
+use skia_safe::{Canvas, Paint, Point, TextBlob};
+
+/// A piece of text drawn on its own, at its own position, with its own paint.
+///
+/// Skia draws in call order, so whatever is drawn last ends up on top. When
+/// pieces of text overlap on purpose (stacked headline words, a drop shadow
+/// word behind another), that order has to be explicit rather than an
+/// accident of how the text was split up. That's what `z_index` is for.
+#[derive(Clone)]
+pub struct TextLayer {
+    pub blob: TextBlob,
+    /// Where the blob's origin (the start of its baseline) goes.
+    pub origin: Point,
+    pub paint: Paint,
+    /// Layers with a higher z-index are drawn on top of lower ones.
+    pub z_index: i32,
+}
+
+/// Draws `layers` back to front: lowest `z_index` first, so the highest ends
+/// up on top where they overlap. Layers with the same z-index are drawn in
+/// the order they're given.
+pub fn draw_layers(canvas: &Canvas, layers: &[TextLayer]) {
+    let mut order: Vec<&TextLayer> = layers.iter().collect();
+    // A stable sort, which is what keeps ties in order.
+    order.sort_by_key(|layer| layer.z_index);
+    for layer in order {
+        canvas.draw_text_blob(&layer.blob, layer.origin, &layer.paint);
+    }
+}
@@ -0,0 +1,32 @@
+//! Small helpers shared between the examples.
+//!
+//! Each example is still meant to be readable on its own, so only code that
+//! would otherwise be copy-pasted between examples ends up in here.
+
+pub mod align;
+pub mod blob;
+pub mod cache;
+pub mod cluster;
+pub mod error;
+pub mod fallback;
+pub mod features;
+pub mod font;
+pub mod info;
+pub mod itemize;
+pub mod layer;
+pub mod markup;
+pub mod mesh;
+pub mod metrics;
+pub mod monospace;
+pub mod output;
+pub mod paragraph;
+pub mod picture;
+pub mod png;
+pub mod raster;
+pub mod registry;
+pub mod script_fonts;
+pub mod shaping;
+pub mod style;
+pub mod svg_glyph;
+pub mod truncate;
+pub mod wrap;
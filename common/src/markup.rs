@@ -0,0 +1,123 @@
+use crate::style::TextStyle;
+use skia_safe::Color;
+
+/// An open markup tag.
+#[derive(Clone, Copy, PartialEq)]
+enum Tag {
+    Bold,
+    Color(Color),
+}
+
+/// A tag found in the markup: opening a new tag, or closing the most recently
+/// opened tag of a kind.
+enum Markup {
+    Open(Tag),
+    Close(fn(&Tag) -> bool),
+}
+
+/// Splits lightweight markup into runs of text, each with the style to draw
+/// it in.
+///
+/// Two tags are understood: `[b]...[/b]` for bold, and
+/// `[color=#f00]...[/color]` (or `#ff0000`) for a text color. Everything else
+/// is `base`. Tags nest, so `[b]bold [color=#00f]and blue[/color][/b]` works.
+///
+/// Malformed markup never fails, it just degrades:
+/// - a tag that isn't closed lasts until the end of the text;
+/// - a closing tag with nothing to close is dropped;
+/// - closing tags out of order (`[b][color=#f00]x[/b]y[/color]`) close the
+///   tag they name and leave the others open, so `y` is red but not bold;
+/// - anything in brackets that isn't one of the tags above, including a
+///   color we can't parse, is kept as literal text.
+///
+/// Bold is faux bold (`TextStyle::embolden`), since the examples only ship a
+/// single weight of each font.
+pub fn parse_markup(markup: &str, base: &TextStyle) -> Vec<(String, TextStyle)> {
+    let mut open: Vec<Tag> = Vec::new();
+    let mut runs: Vec<(String, Vec<Tag>)> = Vec::new();
+    let mut text = String::new();
+
+    let mut rest = markup;
+    while let Some(start) = rest.find('[') {
+        let Some(len) = rest[start..].find(']') else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+        let found = match &rest[start + 1..start + len] {
+            "b" => Some(Markup::Open(Tag::Bold)),
+            "/b" => Some(Markup::Close(|tag| matches!(tag, Tag::Bold))),
+            "/color" => Some(Markup::Close(|tag| matches!(tag, Tag::Color(_)))),
+            other => other
+                .strip_prefix("color=")
+                .and_then(parse_hex_color)
+                .map(|color| Markup::Open(Tag::Color(color))),
+        };
+
+        match found {
+            Some(markup) => {
+                // The style changes here, so finish the run so far.
+                flush(&mut runs, &mut text, &open);
+                match markup {
+                    Markup::Open(tag) => open.push(tag),
+                    Markup::Close(closes) => {
+                        if let Some(index) = open.iter().rposition(closes) {
+                            open.remove(index);
+                        }
+                    }
+                }
+            }
+            // Not markup after all: keep the brackets as text.
+            None => text.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    text.push_str(rest);
+    flush(&mut runs, &mut text, &open);
+
+    runs.into_iter()
+        .map(|(text, tags)| {
+            let mut style = base.clone();
+            for tag in tags {
+                match tag {
+                    Tag::Bold => style.embolden = true,
+                    Tag::Color(color) => style.color = color,
+                }
+            }
+            (text, style)
+        })
+        .collect()
+}
+
+/// Ends the current run of text, styled by the tags that are open.
+/// Consecutive runs with the same tags are merged.
+fn flush(runs: &mut Vec<(String, Vec<Tag>)>, text: &mut String, open: &[Tag]) {
+    if text.is_empty() {
+        return;
+    }
+    match runs.last_mut() {
+        Some((run, tags)) if tags == open => run.push_str(text),
+        _ => runs.push((text.clone(), open.to_vec())),
+    }
+    text.clear();
+}
+
+/// Parses a CSS-style hex color, `#rgb` or `#rrggbb`.
+pub fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => {
+            // Each digit is doubled: #f80 is #ff8800.
+            let [r, g, b] = [0, 1, 2].map(|i| channel(&hex[i..i + 1]).map(|v| v * 17));
+            Some(Color::from_rgb(r?, g?, b?))
+        }
+        6 => {
+            let [r, g, b] = [0, 2, 4].map(|i| channel(&hex[i..i + 2]));
+            Some(Color::from_rgb(r?, g?, b?))
+        }
+        _ => None,
+    }
+}
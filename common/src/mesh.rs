@@ -0,0 +1,115 @@
+use lyon_tessellation::math::point;
+use lyon_tessellation::path::Path as LyonPath;
+use lyon_tessellation::{
+    BuffersBuilder, FillOptions, FillRule, FillTessellator, FillVertex, VertexBuffers,
+};
+use skia_safe::{path::Iter, path::Verb, Path, PathFillType, Point};
+use std::fmt::Write;
+
+/// A filled outline as triangles, ready to upload to a GPU: every three
+/// indices are one triangle's corners in `vertices`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// The mesh as a Wavefront .obj file (z = 0), which most 3D tools and
+    /// mesh viewers open.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+        for [x, y] in &self.vertices {
+            // .obj's y axis points up, the path's down.
+            writeln!(obj, "v {x} {} 0", -y).unwrap();
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            // .obj indices start at 1.
+            writeln!(obj, "f {} {} {}", triangle[0] + 1, triangle[1] + 1, triangle[2] + 1)
+                .unwrap();
+        }
+        obj
+    }
+}
+
+/// Triangulates the inside of `path`, e.g. a glyph outline.
+///
+/// Curves are flattened into line segments first, none of them further than
+/// `tolerance` (in the path's units, so pixels for a glyph at its size) from
+/// the true curve; smaller tolerances give smoother edges and more
+/// triangles. The path's fill type decides what counts as inside, so the
+/// counter of an "o" stays empty. Returns `None` if lyon can't tessellate the
+/// path.
+pub fn triangulate(path: &Path, tolerance: f32) -> Option<Mesh> {
+    let fill_rule = match path.fill_type() {
+        PathFillType::EvenOdd | PathFillType::InverseEvenOdd => FillRule::EvenOdd,
+        PathFillType::Winding | PathFillType::InverseWinding => FillRule::NonZero,
+    };
+
+    let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+    FillTessellator::new()
+        .tessellate_path(
+            &to_lyon(path),
+            &FillOptions::tolerance(tolerance).with_fill_rule(fill_rule),
+            &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| {
+                vertex.position().to_array()
+            }),
+        )
+        .ok()?;
+    Some(Mesh {
+        vertices: buffers.vertices,
+        indices: buffers.indices,
+    })
+}
+
+/// Converts a Skia path to lyon's, curve for curve.
+fn to_lyon(path: &Path) -> LyonPath {
+    let to = |p: Point| point(p.x, p.y);
+    let mut builder = LyonPath::builder();
+    let mut open = false;
+    let mut iter = Iter::new(path, true);
+    while let Some((verb, points)) = iter.next() {
+        match verb {
+            Verb::Move => {
+                if open {
+                    builder.end(false);
+                }
+                builder.begin(to(points[0]));
+                open = true;
+            }
+            Verb::Line => {
+                builder.line_to(to(points[1]));
+            }
+            Verb::Quad => {
+                builder.quadratic_bezier_to(to(points[1]), to(points[2]));
+            }
+            Verb::Cubic => {
+                builder.cubic_bezier_to(to(points[1]), to(points[2]), to(points[3]));
+            }
+            Verb::Conic => {
+                // lyon has no conics (font outlines don't either), so split
+                // it into quads: 2^2 of them are plenty.
+                let weight = iter.conic_weight().unwrap_or(1.0);
+                let mut quads = [Point::default(); 9];
+                let [start, control, end] = [points[0], points[1], points[2]];
+                let count =
+                    Path::convert_conic_to_quads(start, control, end, weight, &mut quads, 2)
+                        .unwrap_or(0);
+                for quad in quads[1..=2 * count].chunks_exact(2) {
+                    builder.quadratic_bezier_to(to(quad[0]), to(quad[1]));
+                }
+            }
+            Verb::Close => {
+                if open {
+                    builder.end(true);
+                    open = false;
+                }
+            }
+            Verb::Done => break,
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+    builder.build()
+}
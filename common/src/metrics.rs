@@ -0,0 +1,42 @@
+use skia_safe::FontMetrics;
+
+/// The vertical extent of a line drawn in several fonts on one baseline.
+///
+/// Same conventions as Skia's `FontMetrics`: the ascent is negative (above
+/// the baseline) and the descent positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub leading: f32,
+}
+
+impl LineMetrics {
+    /// Distance from one baseline to the next.
+    pub fn height(&self) -> f32 {
+        self.descent - self.ascent + self.leading
+    }
+}
+
+/// Combines the metrics of every font on a line into one line box: the
+/// highest ascent, the lowest descent and the largest leading.
+///
+/// Sizing the line from the primary font alone clips whatever reaches
+/// further, typically emoji, whose fallback font has a much taller ascent
+/// than Latin text at the same size. Runs moved off the baseline (like
+/// example-2's centered emoji) should have their offset added to their
+/// ascent and descent first.
+pub fn line_metrics(metrics: impl IntoIterator<Item = FontMetrics>) -> LineMetrics {
+    metrics.into_iter().fold(
+        LineMetrics {
+            ascent: 0.0,
+            descent: 0.0,
+            leading: 0.0,
+        },
+        |line, metrics| LineMetrics {
+            ascent: line.ascent.min(metrics.ascent),
+            descent: line.descent.max(metrics.descent),
+            leading: line.leading.max(metrics.leading),
+        },
+    )
+}
@@ -0,0 +1,10 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Returns how many terminal-style cells a grapheme cluster occupies.
+///
+/// Wide characters (CJK, most emoji, emoji presentation sequences) take two
+/// cells, everything else takes one. Zero-width clusters (e.g. a lone control
+/// character) report zero.
+pub fn cell_width(grapheme: &str) -> u8 {
+    grapheme.width().min(2) as u8
+}
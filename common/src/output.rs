@@ -0,0 +1,22 @@
+use std::fs;
+use std::io::{self, Write};
+
+/// Writes `bytes` to the file at `path`, or to standard output if `path` is
+/// `-`, like most command line tools. That lets an example's image be piped
+/// straight into another program:
+///
+/// ```text
+/// cargo run -- --out - | convert - -resize 50% small.png
+/// ```
+///
+/// Anything else the program prints then has to go to stderr, or it would
+/// end up in the middle of the image.
+pub fn write_output(path: &str, bytes: &[u8]) -> io::Result<()> {
+    if path == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(bytes)?;
+        stdout.flush()
+    } else {
+        fs::write(path, bytes)
+    }
+}
@@ -0,0 +1,301 @@
+use crate::blob::{build_blob, FontRun};
+use crate::fallback::has_glyphs;
+use crate::itemize::itemize;
+use crate::script_fonts::ScriptFontMap;
+use crate::shaping::{shape_text, GlyphRun};
+use crate::style::TextStyle;
+use crate::wrap::wrap_lines;
+use harfbuzz_rs::{Face as HbFace, Font as HbFont};
+use skia_safe::{Canvas, Paint, Point, TextBlob, Typeface};
+use std::collections::HashMap;
+use unicode_script::{Script, UnicodeScript};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A paragraph of text in one style, wrapped and shaped once and then drawn
+/// as often as needed.
+///
+/// Laying text out (picking fonts for each run, measuring candidate lines,
+/// shaping the runs into glyphs) is by far the expensive part of drawing
+/// it. An app that re-renders every frame keeps the `Paragraph` around
+/// instead: `draw` only replays the blobs, and the layout is only redone
+/// when something that affects it changes, like the width.
+///
+/// Lines are split into runs at script and direction changes (see
+/// `Paragraph::runs`) and each run is shaped with HarfBuzz, so kerning,
+/// ligatures and Arabic joining all apply. The runs of a line are then laid
+/// out in visual order by their bidi levels, but every line starts at the
+/// left edge: it's meant for left-to-right paragraphs with the odd
+/// right-to-left word, not for right-aligned Arabic text.
+pub struct Paragraph {
+    text: String,
+    style: TextStyle,
+    fonts: ScriptFontMap,
+    fallback: Typeface,
+    files: FontFiles,
+    width: f32,
+    lines: Vec<ParagraphLine>,
+    height: f32,
+    layouts: usize,
+}
+
+/// One wrapped line of a `Paragraph`.
+struct ParagraphLine {
+    /// Each run's glyphs, with the run's start on the baseline at (0, 0),
+    /// the paint to draw it with and how far into the line it starts.
+    runs: Vec<(TextBlob, Paint, f32)>,
+    /// Distance from the top of the paragraph to the line's baseline.
+    baseline: f32,
+}
+
+impl Paragraph {
+    /// Lays out `text` in `style` at `width`, each script in its typeface
+    /// from `fonts`, and with `fallback` for the emoji and symbols those
+    /// can't draw (see `Paragraph::runs`).
+    pub fn new(
+        text: &str,
+        style: &TextStyle,
+        fonts: &ScriptFontMap,
+        fallback: &Typeface,
+        width: f32,
+    ) -> Self {
+        let mut paragraph = Paragraph {
+            text: text.to_string(),
+            style: style.clone(),
+            fonts: fonts.clone(),
+            fallback: fallback.clone(),
+            files: FontFiles::new(fonts, fallback),
+            width,
+            lines: Vec::new(),
+            height: 0.0,
+            layouts: 0,
+        };
+        paragraph.layout();
+        paragraph
+    }
+
+    /// Wraps the paragraph to a new width. Changing the width is what moves
+    /// words between lines, so the layout is redone, unless the width is
+    /// the same as before.
+    pub fn set_width(&mut self, width: f32) {
+        if width != self.width {
+            self.width = width;
+            self.layout();
+        }
+    }
+
+    /// The width the paragraph is wrapped to.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// The total height of the lines, each as tall as its font's line
+    /// spacing (the largest of its runs').
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// How many times the paragraph has been laid out: once when it's
+    /// created, then once per change of width.
+    pub fn layouts(&self) -> usize {
+        self.layouts
+    }
+
+    /// Draws the paragraph with its top-left corner at `origin`.
+    pub fn draw(&self, canvas: &Canvas, origin: impl Into<Point>) {
+        let origin = origin.into();
+        for line in &self.lines {
+            for (blob, paint, x) in &line.runs {
+                canvas.draw_text_blob(blob, origin + Point::new(*x, line.baseline), paint);
+            }
+        }
+    }
+
+    /// Wraps the text, splits every line into runs (see `Paragraph::runs`)
+    /// and shapes them into blobs.
+    ///
+    /// The emoji font has different metrics from the text font, so a line's
+    /// ascent and spacing are the largest of its runs'.
+    fn layout(&mut self) {
+        self.layouts += 1;
+        self.lines.clear();
+        self.height = 0.0;
+
+        let measure = |text: &str| -> f32 {
+            Paragraph::runs(text, &self.style, &self.fonts, &self.fallback)
+                .iter()
+                .map(|(run, run_style)| self.files.shape(run, run_style).width)
+                .sum()
+        };
+        for line in wrap_lines(&self.text, self.width, measure) {
+            let line_runs =
+                Paragraph::leveled_runs(&line, &self.style, &self.fonts, &self.fallback);
+            let levels: Vec<u8> = line_runs.iter().map(|&(_, _, level)| level).collect();
+            let mut runs = Vec::new();
+            let (mut x, mut ascent, mut height) = (0.0, 0.0_f32, 0.0_f32);
+            for index in visual_order(&levels) {
+                let (run, run_style, _) = &line_runs[index];
+                let font = run_style.font();
+                let (spacing, metrics) = font.metrics();
+                ascent = ascent.max(-metrics.ascent);
+                height = height.max(spacing);
+                // Each run is shaped on its own, so a right-to-left run's
+                // glyphs already come out in visual order.
+                let glyphs = self.files.shape(run, run_style);
+                let advance = glyphs.width;
+                if let Some(blob) = build_blob(&[FontRun { font, glyphs }]) {
+                    runs.push((blob, run_style.paint(), x));
+                }
+                x += advance;
+            }
+            // An empty line still takes up one line of the paragraph's style.
+            if line_runs.is_empty() {
+                let (spacing, metrics) = self.style.font().metrics();
+                ascent = -metrics.ascent;
+                height = spacing;
+            }
+            self.lines.push(ParagraphLine {
+                runs,
+                baseline: self.height + ascent,
+            });
+            self.height += height;
+        }
+    }
+
+    /// Splits `text` into the runs a paragraph draws it in, in logical
+    /// order: runs of one script and direction (see `itemize`), each in the
+    /// typeface `fonts` has for its script (`style`'s own typeface is
+    /// replaced).
+    ///
+    /// The script decides the typeface without asking it what it covers.
+    /// Only emoji, symbols and punctuation, which have no script of their
+    /// own (they're `Common`) and just join the run next to them, are
+    /// checked against the run's typeface, and drawn in `fallback` if it has
+    /// no glyph for them. Whole grapheme clusters are checked, as in
+    /// `fallback_runs`, so a multi-codepoint emoji stays together.
+    ///
+    /// This is also how to measure text the way a paragraph will draw it:
+    /// shape each run with `FontFiles::shape` and add up the widths.
+    pub fn runs(
+        text: &str,
+        style: &TextStyle,
+        fonts: &ScriptFontMap,
+        fallback: &Typeface,
+    ) -> Vec<(String, TextStyle)> {
+        Paragraph::leveled_runs(text, style, fonts, fallback)
+            .into_iter()
+            .map(|(run, run_style, _)| (run, run_style))
+            .collect()
+    }
+
+    /// `Paragraph::runs`, with each run's bidi level.
+    fn leveled_runs(
+        text: &str,
+        style: &TextStyle,
+        fonts: &ScriptFontMap,
+        fallback: &Typeface,
+    ) -> Vec<(String, TextStyle, u8)> {
+        let mut runs = Vec::new();
+        for item in itemize(text, &[(0..text.len(), style.clone())]) {
+            let script_style = TextStyle {
+                typeface: fonts.typeface_for(item.script).clone(),
+                ..item.style
+            };
+            let fallback_style = TextStyle {
+                typeface: fallback.clone(),
+                ..script_style.clone()
+            };
+            let script_font = script_style.font();
+
+            let mut item_runs: Vec<(String, bool)> = Vec::new();
+            for grapheme in text[item.range].graphemes(true) {
+                let common = grapheme.chars().next().is_some_and(|c| c.script() == Script::Common);
+                let use_fallback = common && !has_glyphs(&script_font, grapheme);
+                match item_runs.last_mut() {
+                    Some((run, in_fallback)) if *in_fallback == use_fallback => {
+                        run.push_str(grapheme)
+                    }
+                    _ => item_runs.push((grapheme.to_string(), use_fallback)),
+                }
+            }
+            runs.extend(item_runs.into_iter().map(|(run, use_fallback)| {
+                let run_style = if use_fallback { &fallback_style } else { &script_style };
+                (run, run_style.clone(), item.level)
+            }));
+        }
+        runs
+    }
+}
+
+/// The order to lay out runs with the bidi embedding `levels` in, from left
+/// to right (rule L2 of the Unicode Bidirectional Algorithm): from the
+/// highest level down to the lowest odd one, every stretch of runs at that
+/// level or higher is reversed.
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let highest = levels.iter().copied().max().unwrap_or(0);
+    let lowest_odd = levels.iter().copied().filter(|level| level % 2 == 1).min();
+    let Some(lowest_odd) = lowest_odd else {
+        return order;
+    };
+    for level in (lowest_odd..=highest).rev() {
+        let mut start = 0;
+        while start < order.len() {
+            if levels[order[start]] < level {
+                start += 1;
+                continue;
+            }
+            let mut end = start;
+            while end < order.len() && levels[order[end]] >= level {
+                end += 1;
+            }
+            order[start..end].reverse();
+            start = end;
+        }
+    }
+    order
+}
+
+/// The font files behind the typefaces a paragraph draws with, for shaping.
+///
+/// Skia draws from a `Typeface`, but HarfBuzz needs the font file itself,
+/// and `Typeface::to_font_data` copies the whole file every time it's
+/// called. This reads each one once, up front.
+#[derive(Clone, Default)]
+pub struct FontFiles {
+    /// The file's bytes and the face's index in it, by the typeface's
+    /// `unique_id`.
+    files: HashMap<u32, (Vec<u8>, u32)>,
+}
+
+impl FontFiles {
+    /// Reads the files of every typeface in `fonts`, and of `fallback`.
+    pub fn new(fonts: &ScriptFontMap, fallback: &Typeface) -> Self {
+        let files = fonts
+            .typefaces()
+            .chain([fallback])
+            .filter_map(|typeface| {
+                let (data, index) = typeface.to_font_data()?;
+                Some((typeface.unique_id(), (data, index as u32)))
+            })
+            .collect();
+        FontFiles { files }
+    }
+
+    /// Shapes `text` in `style`'s typeface and size, with its OpenType
+    /// features. Text in a typeface that isn't one of these, or whose file
+    /// Skia couldn't hand back, shapes to no glyphs at all.
+    pub fn shape(&self, text: &str, style: &TextStyle) -> GlyphRun {
+        let Some((data, index)) = self.files.get(&style.typeface.unique_id()) else {
+            return GlyphRun::default();
+        };
+        let mut hb_font = HbFont::new(HbFace::from_bytes(data, *index));
+        // In 26.6 fixed point, as `shape_text` expects.
+        let hb_scale = (style.size * 64.0).round() as i32;
+        hb_font.set_scale(hb_scale, hb_scale);
+        shape_text(&hb_font, text, &style.hb_features())
+    }
+}
@@ -0,0 +1,29 @@
+use skia_safe::{Canvas, ISize, Image, Picture, PictureRecorder, Rect, Surface};
+
+/// Records `draw` into a picture of `size`, instead of rasterizing it.
+///
+/// A picture is the list of draw calls themselves, so it captures exactly
+/// what was drawn, independent of the surface it ends up on. Serialized with
+/// `Picture::serialize` it becomes an .skp file, which embeds the typefaces
+/// it uses: attach one to a bug report, and it can be replayed (with
+/// `replay_picture`, or in Skia's debugger) on a machine that doesn't have
+/// the fonts.
+pub fn record_picture(size: impl Into<ISize>, draw: impl FnOnce(&Canvas)) -> Option<Picture> {
+    let size = size.into();
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_isize(size), None);
+    draw(canvas);
+    recorder.finish_recording_as_picture(None)
+}
+
+/// Reads a serialized picture (.skp) back and rasterizes it onto a surface
+/// of `size`.
+///
+/// Nothing is cleared first, so a picture that doesn't paint its own
+/// background comes out on transparent.
+pub fn replay_picture(skp: &[u8], size: impl Into<ISize>) -> Option<Image> {
+    let picture = Picture::from_bytes(skp)?;
+    let mut surface = Surface::new_raster_n32_premul(size.into())?;
+    surface.canvas().draw_picture(&picture, None, None);
+    Some(surface.image_snapshot())
+}
@@ -0,0 +1,157 @@
+//! Small post-processing steps on encoded PNG files.
+//!
+//! Skia's PNG encoder doesn't expose every chunk, so these work directly on
+//! the encoded bytes. A PNG is an 8-byte signature followed by chunks, each
+//! laid out as: length (u32 BE), type (4 bytes), data, CRC-32 of type + data.
+
+use skia_safe::{images, AlphaType, ColorType, Data, EncodedImageFormat, ISize, ImageInfo};
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Sets the physical pixel density (the `pHYs` chunk) of an encoded PNG.
+///
+/// Image viewers and print tools use it to report e.g. "300 DPI". PNG stores
+/// the density in pixels per meter, so `dpi` is converted (1 inch = 0.0254 m).
+/// An existing `pHYs` chunk is replaced; otherwise the chunk is inserted right
+/// after `IHDR`, which satisfies the rule that it must come before `IDAT`.
+///
+/// Returns `None` if `png` doesn't look like a PNG.
+pub fn set_dpi(png: &[u8], dpi: f32) -> Option<Vec<u8>> {
+    let pixels_per_meter = (dpi / 0.0254).round() as u32;
+    let mut data = Vec::with_capacity(9);
+    data.extend_from_slice(&pixels_per_meter.to_be_bytes()); // x
+    data.extend_from_slice(&pixels_per_meter.to_be_bytes()); // y
+    data.push(1); // unit: meter
+
+    let mut out = Vec::with_capacity(png.len() + 21);
+    out.extend_from_slice(png.get(..SIGNATURE.len()).filter(|s| *s == SIGNATURE)?);
+    for (chunk_type, chunk) in chunks(png)? {
+        match &chunk_type {
+            b"pHYs" => continue,
+            b"IHDR" => {
+                out.extend_from_slice(chunk);
+                write_chunk(&mut out, b"pHYs", &data);
+            }
+            _ => out.extend_from_slice(chunk),
+        }
+    }
+    Some(out)
+}
+
+/// Assembles RGBA8888 frames (straight alpha, row after row, all `size`) into
+/// one animated PNG that shows each frame for `frame_delay_ms` and loops
+/// forever.
+///
+/// An APNG is a regular PNG whose image data is the first frame, so viewers
+/// without APNG support still show that. It adds an `acTL` chunk (frame and
+/// loop count), an `fcTL` chunk before each frame (its size, position and
+/// delay), and stores the later frames in `fdAT` chunks, which are `IDAT`
+/// chunks with a sequence number in front. Rather than compressing the
+/// pixels ourselves, each frame is encoded as a PNG with Skia and its `IDAT`
+/// data moved over.
+///
+/// Returns `None` if there are no frames, or one has the wrong length.
+pub fn encode_apng(
+    frames: &[Vec<u8>],
+    size: impl Into<ISize>,
+    frame_delay_ms: u16,
+) -> Option<Vec<u8>> {
+    let size = size.into();
+    let info = ImageInfo::new(size, ColorType::RGBA8888, AlphaType::Unpremul, None);
+    let row_bytes = info.min_row_bytes();
+    let frame_len = info.compute_min_byte_size();
+    if frames.is_empty() || frames.iter().any(|frame| frame.len() != frame_len) {
+        return None;
+    }
+
+    let mut actl = Vec::with_capacity(8);
+    actl.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+    actl.extend_from_slice(&0u32.to_be_bytes()); // plays: 0 is forever
+
+    // fcTL and fdAT chunks share one sequence number, counting from 0.
+    let mut sequence = 0u32;
+    let mut out = SIGNATURE.to_vec();
+    for (index, frame) in frames.iter().enumerate() {
+        let image = images::raster_from_data(&info, Data::new_copy(frame), row_bytes)?;
+        let png = image.encode_to_data(EncodedImageFormat::PNG)?;
+
+        let mut fctl = Vec::with_capacity(26);
+        fctl.extend_from_slice(&sequence.to_be_bytes());
+        fctl.extend_from_slice(&(size.width as u32).to_be_bytes());
+        fctl.extend_from_slice(&(size.height as u32).to_be_bytes());
+        fctl.extend_from_slice(&[0; 8]); // x and y offset
+        fctl.extend_from_slice(&frame_delay_ms.to_be_bytes()); // delay numerator
+        fctl.extend_from_slice(&1000u16.to_be_bytes()); // ...and denominator: ms
+        fctl.push(0); // dispose: leave the frame as is, the next one covers it
+        fctl.push(0); // blend: replace the pixels rather than draw over them
+        sequence += 1;
+
+        let mut wrote_fctl = false;
+        for (chunk_type, chunk) in chunks(png.as_bytes())? {
+            let data = &chunk[8..chunk.len() - 4];
+            match &chunk_type {
+                b"IDAT" => {
+                    if !wrote_fctl {
+                        write_chunk(&mut out, b"fcTL", &fctl);
+                        wrote_fctl = true;
+                    }
+                    if index == 0 {
+                        out.extend_from_slice(chunk);
+                    } else {
+                        let mut fdat = sequence.to_be_bytes().to_vec();
+                        fdat.extend_from_slice(data);
+                        write_chunk(&mut out, b"fdAT", &fdat);
+                        sequence += 1;
+                    }
+                }
+                b"IEND" => {}
+                // Everything else (the header, color space, ...) comes from
+                // the first frame; the frames are all encoded alike.
+                b"IHDR" if index == 0 => {
+                    out.extend_from_slice(chunk);
+                    write_chunk(&mut out, b"acTL", &actl);
+                }
+                _ if index == 0 => out.extend_from_slice(chunk),
+                _ => {}
+            }
+        }
+    }
+    write_chunk(&mut out, b"IEND", &[]);
+    Some(out)
+}
+
+/// Splits an encoded PNG into `(type, raw chunk bytes)` pairs.
+fn chunks(png: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    let mut chunks = Vec::new();
+    let mut rest = png.get(SIGNATURE.len()..)?;
+    while !rest.is_empty() {
+        let length = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let total = 12 + length;
+        let chunk_type = rest.get(4..8)?.try_into().ok()?;
+        chunks.push((chunk_type, rest.get(..total)?));
+        rest = &rest[total..];
+    }
+    Some(chunks)
+}
+
+/// Appends a complete chunk (length, type, data, CRC) to `out`.
+pub(crate) fn write_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let crc_start = out.len();
+    out.extend_from_slice(chunk_type);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[crc_start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// The CRC-32 used by PNG (ISO 3309, same as zlib's).
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
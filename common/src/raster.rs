@@ -0,0 +1,177 @@
+use skia_safe::{
+    image::CachingHint, images, surfaces, AlphaType, Canvas, Color, ColorSpace, ColorType,
+    CubicResampler, Data, ISize, Image, ImageInfo, Paint, Rect, Surface,
+};
+
+/// Renders at `factor` times the target size, then downsamples to `size`.
+///
+/// Skia's glyph rasterizer is already anti-aliased, but at very small sizes
+/// the coverage gets coarse and text looks muddy. Drawing big and shrinking
+/// with a cubic (Mitchell) filter averages many samples per output pixel,
+/// which gives crisper small text. `draw` sees a canvas that is already
+/// scaled, so it can draw in target-size coordinates.
+pub fn render_supersampled(
+    size: impl Into<ISize>,
+    factor: u32,
+    background: Color,
+    draw: impl FnOnce(&Canvas),
+) -> Option<Image> {
+    let size = size.into();
+    let factor = factor.max(1);
+
+    let mut big_surface =
+        Surface::new_raster_n32_premul((size.width * factor as i32, size.height * factor as i32))?;
+    let big_canvas = big_surface.canvas();
+    big_canvas.clear(background);
+    big_canvas.scale((factor as f32, factor as f32));
+    draw(big_canvas);
+    downsample(&big_surface.image_snapshot(), size, background)
+}
+
+/// Shrinks `image` to `size` with a cubic (Mitchell) filter, over
+/// `background`: the second half of `render_supersampled`, for code that
+/// draws onto the big surface itself.
+pub fn downsample(image: &Image, size: impl Into<ISize>, background: Color) -> Option<Image> {
+    let size = size.into();
+    let mut surface = Surface::new_raster_n32_premul(size)?;
+    let canvas = surface.canvas();
+    canvas.clear(background);
+    canvas.draw_image_rect_with_sampling_options(
+        image,
+        None,
+        Rect::from_isize(size),
+        CubicResampler::mitchell(),
+        &Paint::default(),
+    );
+    Some(surface.image_snapshot())
+}
+
+/// Renders `draw` onto a transparent surface and keeps only its alpha channel.
+///
+/// The result is a single-channel 8-bit image: 255 where the drawing is fully
+/// opaque, 0 where nothing was drawn. That's handy as a mask texture. Draw in
+/// white so the color channels don't matter if you later need them, e.g. for
+/// compositing the same surface.
+pub fn render_alpha_mask(size: impl Into<ISize>, draw: impl FnOnce(&Canvas)) -> Option<Image> {
+    let size = size.into();
+    let mut surface = Surface::new_raster_n32_premul(size)?;
+    let canvas = surface.canvas();
+    canvas.clear(Color::TRANSPARENT);
+    draw(canvas);
+
+    // Let Skia convert the RGBA pixels down to just their alpha.
+    let row_bytes = size.width as usize;
+    let mut alpha = vec![0u8; row_bytes * size.height as usize];
+    if !surface.read_pixels(&ImageInfo::new_a8(size), &mut alpha, row_bytes, (0, 0)) {
+        return None;
+    }
+
+    // An alpha-only image would encode as a transparent PNG; relabel the same
+    // bytes as gray so it encodes as a grayscale one instead.
+    let gray_info = ImageInfo::new(size, ColorType::Gray8, AlphaType::Opaque, None);
+    images::raster_from_data(&gray_info, Data::new_copy(&alpha), row_bytes)
+}
+
+/// Reads `image` back as raw RGBA8888 pixels, row after row with no padding.
+///
+/// Skia keeps pixels premultiplied: a pixel's color channels are already
+/// multiplied by its alpha, so 50% transparent red is stored as
+/// (128, 0, 0, 128). Tools that expect straight alpha would read that as a
+/// dark red. Passing `AlphaType::Unpremul` makes Skia divide the alpha back
+/// out while reading, giving (255, 0, 0, 128) instead. For opaque pixels the
+/// two are the same.
+pub fn read_rgba(image: &Image, alpha_type: AlphaType) -> Option<Vec<u8>> {
+    let info = ImageInfo::new(image.dimensions(), ColorType::RGBA8888, alpha_type, None);
+    let row_bytes = info.min_row_bytes();
+    let mut pixels = vec![0u8; info.compute_min_byte_size()];
+    image
+        .read_pixels(&info, &mut pixels, row_bytes, (0, 0), CachingHint::Allow)
+        .then_some(pixels)
+}
+
+/// Converts `image` to 8-bit grayscale, one byte per pixel.
+///
+/// Skia weighs the channels by how bright they look (green counts most, blue
+/// least), so colored text keeps its contrast. The alpha channel is dropped:
+/// draw on an opaque background first. Encoded as PNG this gives a
+/// single-channel grayscale file: a third of the raw data of RGB, and a
+/// smaller file for monochrome text.
+pub fn to_grayscale(image: &Image) -> Option<Image> {
+    let info = ImageInfo::new(image.dimensions(), ColorType::Gray8, AlphaType::Opaque, None);
+    let row_bytes = info.min_row_bytes();
+    let mut gray = vec![0u8; info.compute_min_byte_size()];
+    if !image.read_pixels(&info, &mut gray, row_bytes, (0, 0), CachingHint::Allow) {
+        return None;
+    }
+    images::raster_from_data(&info, Data::new_copy(&gray), row_bytes)
+}
+
+/// The color space a surface's pixels are stored in, which is also the space
+/// Skia blends in when it draws onto it.
+///
+/// Blending is what happens at every anti-aliased edge: a pixel half covered
+/// by a glyph becomes the average of the text and background colors. Averaged
+/// as sRGB values (what an untagged surface does), that average comes out too
+/// dark, because sRGB values are gamma-encoded rather than proportional to
+/// light. On colored text over a colored background this shows as a dark
+/// fringe: the edge between red text and a green background goes a muddy
+/// brown instead of the yellow the two lights actually mix to. Dark text on a
+/// light background looks a little bolder, light text on a dark background a
+/// little thinner. Averaged in linear light, edges have the brightness you'd
+/// expect and light-on-dark and dark-on-light text look equally heavy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendSpace {
+    /// sRGB, 8 bits per channel. Blends like an untagged surface, but the
+    /// pixels are marked as sRGB, so they are converted correctly when drawn
+    /// into another color space.
+    Srgb,
+    /// Linear sRGB (sRGB's colors without its gamma curve), as 16-bit floats;
+    /// 8 bits aren't enough for linear values without visible banding in the
+    /// dark tones.
+    Linear,
+}
+
+impl BlendSpace {
+    /// The Skia color space for this choice.
+    pub fn color_space(self) -> ColorSpace {
+        match self {
+            BlendSpace::Srgb => ColorSpace::new_srgb(),
+            BlendSpace::Linear => ColorSpace::new_srgb_linear(),
+        }
+    }
+}
+
+/// Creates a raster surface whose `ImageInfo` carries an explicit color
+/// space, so drawing onto it blends in `space` (see `BlendSpace`).
+///
+/// Colors given to Skia (paints, `clear`) are sRGB, and Skia converts them
+/// into the surface's space.
+pub fn color_managed_surface(size: impl Into<ISize>, space: BlendSpace) -> Option<Surface> {
+    let color_type = match space {
+        BlendSpace::Srgb => ColorType::RGBA8888,
+        BlendSpace::Linear => ColorType::RGBAF16,
+    };
+    let info = ImageInfo::new(size, color_type, AlphaType::Premul, space.color_space());
+    surfaces::raster(&info, None, None)
+}
+
+/// Renders `draw` on a surface that blends in `space`, and returns the
+/// result as an 8-bit sRGB image, ready to encode like any other.
+pub fn render_color_managed(
+    size: impl Into<ISize>,
+    space: BlendSpace,
+    background: Color,
+    draw: impl FnOnce(&Canvas),
+) -> Option<Image> {
+    let size = size.into();
+    let mut surface = color_managed_surface(size, space)?;
+    let canvas = surface.canvas();
+    canvas.clear(background);
+    draw(canvas);
+    let image = surface.image_snapshot();
+
+    // Both images are tagged, so drawing one onto the other converts it.
+    let mut srgb_surface = color_managed_surface(size, BlendSpace::Srgb)?;
+    srgb_surface.canvas().draw_image(&image, (0, 0), None);
+    Some(srgb_surface.image_snapshot())
+}
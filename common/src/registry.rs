@@ -0,0 +1,66 @@
+use crate::error::TextError;
+use skia_safe::{Data, FontMgr, Typeface};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Loads each font file once and hands out the same typeface after that.
+///
+/// Parsing a font file isn't free, and every loaded copy keeps its own data
+/// and glyph caches in memory. When many things are drawn with the same few
+/// fonts (a batch of images, every label in a UI), keep one registry around
+/// and load through it. A `Typeface` is a reference-counted handle, so the
+/// clones it returns all share the one loaded font.
+///
+/// Fonts are keyed by path as given plus the face index within the file
+/// (only collections, `.ttc`, have more than one), so two different
+/// spellings of the same path load it twice.
+pub struct TypefaceRegistry {
+    font_mgr: FontMgr,
+    typefaces: HashMap<(PathBuf, usize), Typeface>,
+}
+
+impl TypefaceRegistry {
+    pub fn new() -> Self {
+        TypefaceRegistry {
+            font_mgr: FontMgr::new(),
+            typefaces: HashMap::new(),
+        }
+    }
+
+    /// Returns face `index` of the font file at `path`, loading it if this
+    /// registry hasn't yet.
+    pub fn load(&mut self, path: impl AsRef<Path>, index: usize) -> Result<Typeface, TextError> {
+        let path = path.as_ref();
+        let key = (path.to_path_buf(), index);
+        if let Some(typeface) = self.typefaces.get(&key) {
+            return Ok(typeface.clone());
+        }
+
+        let font_load_error = || TextError::FontLoad {
+            path: path.display().to_string(),
+        };
+        let bytes = fs::read(path).map_err(|_| font_load_error())?;
+        let typeface = self
+            .font_mgr
+            .new_from_data(&Data::new_copy(&bytes), index)
+            .ok_or_else(font_load_error)?;
+        self.typefaces.insert(key, typeface.clone());
+        Ok(typeface)
+    }
+
+    /// How many typefaces have been loaded.
+    pub fn len(&self) -> usize {
+        self.typefaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.typefaces.is_empty()
+    }
+}
+
+impl Default for TypefaceRegistry {
+    fn default() -> Self {
+        TypefaceRegistry::new()
+    }
+}
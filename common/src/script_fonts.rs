@@ -0,0 +1,105 @@
+use crate::style::TextStyle;
+use skia_safe::Typeface;
+use std::collections::HashMap;
+use std::ops::Range;
+use unicode_script::{Script, UnicodeScript};
+
+/// Which typeface draws which script, e.g. Arabic in one font and Latin in
+/// another, with a default for every script that isn't mapped.
+///
+/// This is the configured alternative to `fallback_runs`, which asks the
+/// primary font about every grapheme and falls back where it has no glyph.
+/// Splitting by script needs no font lookups at all, and the result doesn't
+/// depend on what a font happens to cover: Latin text always goes to the
+/// Latin font, even if the Arabic font has Latin letters too (most do, and
+/// they rarely match). Coverage probing is still the way to go for emoji,
+/// which have no script of their own (they're `Common`, like punctuation);
+/// `Paragraph::runs` does both.
+#[derive(Clone)]
+pub struct ScriptFontMap {
+    default: Typeface,
+    fonts: HashMap<Script, Typeface>,
+}
+
+impl ScriptFontMap {
+    /// A map that sends every script to `default`, until told otherwise.
+    pub fn new(default: Typeface) -> Self {
+        ScriptFontMap {
+            default,
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// Draws `script` in `typeface` from now on.
+    pub fn insert(&mut self, script: Script, typeface: Typeface) {
+        self.fonts.insert(script, typeface);
+    }
+
+    /// The typeface for `script`, or the default if it isn't mapped.
+    pub fn typeface_for(&self, script: Script) -> &Typeface {
+        self.fonts.get(&script).unwrap_or(&self.default)
+    }
+
+    /// The default typeface and every mapped one, in no particular order.
+    pub fn typefaces(&self) -> impl Iterator<Item = &Typeface> {
+        std::iter::once(&self.default).chain(self.fonts.values())
+    }
+
+    /// Splits `text` into runs of one script each (see `script_runs`) and
+    /// returns each with `style`, its typeface swapped for the script's.
+    /// Neighbouring runs that end up with the same typeface are merged.
+    pub fn runs(&self, text: &str, style: &TextStyle) -> Vec<(String, TextStyle)> {
+        let mut runs: Vec<(String, TextStyle)> = Vec::new();
+        for (range, script) in script_runs(text) {
+            let typeface = self.typeface_for(script);
+            // Typefaces are handles, so compare which font they are.
+            let same_typeface = |run_style: &TextStyle| {
+                run_style.typeface.unique_id() == typeface.unique_id()
+            };
+            match runs.last_mut() {
+                Some((run, run_style)) if same_typeface(run_style) => run.push_str(&text[range]),
+                _ => runs.push((
+                    text[range].to_string(),
+                    TextStyle {
+                        typeface: typeface.clone(),
+                        ..style.clone()
+                    },
+                )),
+            }
+        }
+        runs
+    }
+}
+
+/// Splits `text` into byte ranges of a single script each, in order.
+///
+/// Characters shared between scripts (`Common`: spaces, digits,
+/// punctuation) and combining marks (`Inherited`) take the script of the
+/// text before them, so "Hello, مرحبا!" is one Latin run "Hello, " and one
+/// Arabic run "مرحبا!". Text that starts with them takes the script of the
+/// first character that has one. Text with no script at all (only digits,
+/// say) comes back as a single `Common` run.
+pub fn script_runs(text: &str) -> Vec<(Range<usize>, Script)> {
+    let mut runs: Vec<(Range<usize>, Script)> = Vec::new();
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        let script = match c.script() {
+            Script::Common | Script::Inherited | Script::Unknown => None,
+            script => Some(script),
+        };
+        match (runs.last_mut(), script) {
+            // No script of its own: the run goes on.
+            (Some((range, _)), None) => range.end = end,
+            // The same script goes on too, and if the text so far had no
+            // script, it takes this one.
+            (Some((range, run_script)), Some(script))
+                if *run_script == script || *run_script == Script::Common =>
+            {
+                range.end = end;
+                *run_script = script;
+            }
+            (_, script) => runs.push((index..end, script.unwrap_or(Script::Common))),
+        }
+    }
+    runs
+}
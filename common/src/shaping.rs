@@ -0,0 +1,214 @@
+use harfbuzz_rs::{shape, Feature, Font as HbFont, GlyphBuffer, Tag, UnicodeBuffer};
+use skia_safe::{Font, GlyphId, Point, Rect};
+
+/// The result of shaping one run of text with HarfBuzz, in pixels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlyphRun {
+    pub glyphs: Vec<GlyphId>,
+    /// Where to draw each glyph, relative to the start of the run's baseline.
+    pub positions: Vec<Point>,
+    /// Byte offset of each glyph's cluster in the shaped text.
+    pub clusters: Vec<u32>,
+    /// Total advance width of the run.
+    pub width: f32,
+}
+
+impl GlyphRun {
+    /// The run's logical and ink boxes when drawn with `font` (see
+    /// `text_bounds`).
+    pub fn bounds(&self, font: &Font) -> TextBounds {
+        text_bounds(font, &self.glyphs, &self.positions, self.width)
+    }
+}
+
+/// Two boxes around a piece of text, both relative to the start of its
+/// baseline (so the top is negative, above the baseline).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextBounds {
+    /// The space the text takes up in layout: its advance width, and the
+    /// font's ascent to descent. It's the same height whatever the glyphs.
+    pub logical: Rect,
+    /// Tight around the glyph outlines, i.e. the pixels that get drawn.
+    /// Empty if there are no outlines at all (only spaces, say).
+    pub ink: Rect,
+}
+
+/// Measures glyphs at `positions` (relative to the baseline origin) whose
+/// advances add up to `advance`.
+///
+/// Use the logical box for layout (line height, alignment, stacking lines)
+/// and the ink box for tight fits (cropping, backgrounds hugging the
+/// letters). The ink box is usually inside the logical one, but not always:
+/// italics can lean past their advance, and tall accents or swashes can
+/// reach above the ascent.
+pub fn text_bounds(
+    font: &Font,
+    glyphs: &[GlyphId],
+    positions: &[Point],
+    advance: f32,
+) -> TextBounds {
+    let (_, metrics) = font.metrics();
+    let logical = Rect::new(0.0, metrics.ascent, advance, metrics.descent);
+
+    let mut ink = Rect::new_empty();
+    for (&glyph, &position) in glyphs.iter().zip(positions) {
+        if let Some(path) = font.get_path(glyph) {
+            ink.join(path.bounds().with_offset(position));
+        }
+    }
+    TextBounds { logical, ink }
+}
+
+/// Shapes `text` with `hb_font`, letting HarfBuzz guess direction and script.
+///
+/// Like the examples, this assumes the font's scale was set in 26.6 fixed
+/// point (pixels * 64). HarfBuzz's y axis points up while Skia's points down,
+/// so y offsets are negated.
+pub fn shape_text(hb_font: &HbFont, text: &str, features: &[Feature]) -> GlyphRun {
+    let buffer = UnicodeBuffer::new().add_str(text).guess_segment_properties();
+    let shaped = shape(hb_font, buffer, features);
+
+    let mut run = GlyphRun {
+        glyphs: Vec::new(),
+        positions: Vec::new(),
+        clusters: Vec::new(),
+        width: 0.0,
+    };
+    for (info, pos) in shaped.get_glyph_infos().iter().zip(shaped.get_glyph_positions()) {
+        let x_offset = pos.x_offset as f32 / 64.0;
+        let y_offset = pos.y_offset as f32 / 64.0;
+        run.glyphs.push(info.codepoint as GlyphId);
+        run.positions.push(Point::new(run.width + x_offset, -y_offset));
+        run.clusters.push(info.cluster);
+        run.width += pos.x_advance as f32 / 64.0;
+    }
+    run
+}
+
+/// Shapes `runs` one after another on a single baseline and yields each
+/// glyph as `(glyph, position, advance)`, in pixels, without collecting them.
+///
+/// It's `shape_text` turned inside out: `shape_text` fills a `GlyphRun` with
+/// every glyph of the text up front, which for a whole book is a lot of
+/// memory for glyphs that are drawn once and thrown away. This only shapes a
+/// run when the glyphs before it have been used up, and only keeps that one
+/// run's HarfBuzz buffer around. Stop iterating and the rest of the text is
+/// never shaped at all.
+///
+/// Positions are relative to the start of the first run's baseline, and each
+/// run starts where the previous one's advances ended (like `build_blob`).
+/// Runs are shaped separately, so split the text where shaping can't reach
+/// across anyway: at spaces or line breaks, not in the middle of a word.
+pub fn stream_glyphs<'a, I>(
+    hb_font: &'a HbFont<'a>,
+    runs: I,
+    features: &'a [Feature],
+) -> GlyphStream<'a, I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    GlyphStream {
+        hb_font,
+        features,
+        runs: runs.into_iter(),
+        shaped: None,
+        index: 0,
+        pen_x: 0.0,
+    }
+}
+
+/// The iterator returned by `stream_glyphs`.
+pub struct GlyphStream<'a, I> {
+    hb_font: &'a HbFont<'a>,
+    features: &'a [Feature],
+    runs: I,
+    /// The run being walked, and the index of its next glyph.
+    shaped: Option<GlyphBuffer>,
+    index: usize,
+    /// Where the next glyph's advance starts.
+    pen_x: f32,
+}
+
+impl<I> Iterator for GlyphStream<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = (GlyphId, Point, f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(shaped) = &self.shaped {
+                let info = shaped.get_glyph_infos().get(self.index);
+                if let Some((info, pos)) = info.zip(shaped.get_glyph_positions().get(self.index)) {
+                    // The same conversion as `shape_text`.
+                    let position = Point::new(
+                        self.pen_x + pos.x_offset as f32 / 64.0,
+                        -pos.y_offset as f32 / 64.0,
+                    );
+                    let advance = pos.x_advance as f32 / 64.0;
+                    self.index += 1;
+                    self.pen_x += advance;
+                    return Some((info.codepoint as GlyphId, position, advance));
+                }
+            }
+
+            // This run is used up (or there wasn't one yet): shape the next.
+            // A run can shape to no glyphs at all (an empty string), hence
+            // the loop.
+            let run = self.runs.next()?;
+            let buffer = UnicodeBuffer::new().add_str(run.as_ref()).guess_segment_properties();
+            self.shaped = Some(shape(self.hb_font, buffer, self.features));
+            self.index = 0;
+        }
+    }
+}
+
+/// Whether a measured width counts spaces at the end of the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailingWhitespace {
+    /// Count them, like CSS `white-space: pre`, where spaces are content.
+    /// Use it when more text will follow, e.g. measuring a span that the next
+    /// span continues on the same line.
+    Include,
+    /// Leave them out, like CSS `white-space: normal` does at the end of a
+    /// line: the spaces are there, but take up no room. Use it for aligning,
+    /// so "OK " is centered like "OK" rather than shifted left by a space.
+    Exclude,
+}
+
+/// Measures the advance width of `text` shaped with `hb_font`, with or
+/// without its trailing whitespace.
+///
+/// Either way the whole text is shaped, so the result is the width the
+/// visible part has in context; the trailing whitespace is left out by
+/// dropping the glyphs that came from it.
+pub fn measure_width(hb_font: &HbFont, text: &str, trailing: TrailingWhitespace) -> f32 {
+    let visible_end = match trailing {
+        TrailingWhitespace::Include => text.len(),
+        TrailingWhitespace::Exclude => text.trim_end().len(),
+    };
+    let buffer = UnicodeBuffer::new().add_str(text).guess_segment_properties();
+    let shaped = shape(hb_font, buffer, &[]);
+    let advance: i32 = shaped
+        .get_glyph_infos()
+        .iter()
+        .zip(shaped.get_glyph_positions())
+        .filter(|(info, _)| (info.cluster as usize) < visible_end)
+        .map(|(_, pos)| pos.x_advance)
+        .sum();
+    advance as f32 / 64.0
+}
+
+/// Returns how much kerning changes the width of `pair` (e.g. "AV"), in
+/// pixels. Negative means the two characters are pulled closer together.
+///
+/// The pair is shaped twice, with the `kern` feature on (HarfBuzz's default)
+/// and off; the difference in advance is the font's adjustment for the pair.
+/// Fonts that only kern through GPOS (most modern ones) are covered too, since
+/// HarfBuzz applies the GPOS `kern` feature under the same tag.
+pub fn kerning_adjustment(hb_font: &HbFont, pair: &str) -> f32 {
+    let no_kern = Feature::new(Tag::new('k', 'e', 'r', 'n'), 0, ..);
+    shape_text(hb_font, pair, &[]).width - shape_text(hb_font, pair, &[no_kern]).width
+}
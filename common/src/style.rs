@@ -0,0 +1,68 @@
+use crate::features::tag_from_bytes;
+use harfbuzz_rs::Feature;
+use skia_safe::{font::Edging, Color, Font, Paint, Typeface};
+
+/// Everything needed to draw a piece of text in a particular look.
+///
+/// The examples used to set up a `Font` and a `Paint` by hand every time;
+/// a `TextStyle` bundles those settings so they can be passed around (and
+/// compared, or tweaked with struct update syntax) as one value.
+#[derive(Clone)]
+pub struct TextStyle {
+    pub typeface: Typeface,
+    /// Font size in pixels.
+    pub size: f32,
+    pub color: Color,
+    /// Multiplies into the color's alpha: 1 draws the color as is, 0 draws
+    /// nothing. Separate from `color` so text can be faded (for a crossfade,
+    /// say) without losing its own transparency.
+    pub opacity: f32,
+    pub edging: Edging,
+    /// Faux bold: Skia thickens the outlines of a regular-weight typeface.
+    pub embolden: bool,
+    /// OpenType features as `(tag, value)`, e.g. `(*b"smcp", 1)`. These are
+    /// for the shaping step (see `hb_features`); Skia's `draw_str` doesn't
+    /// apply OpenType features on its own.
+    pub features: Vec<([u8; 4], u32)>,
+}
+
+impl TextStyle {
+    /// Black, sub-pixel anti-aliased text with no extra features.
+    pub fn new(typeface: Typeface, size: f32) -> Self {
+        TextStyle {
+            typeface,
+            size,
+            color: Color::BLACK,
+            opacity: 1.0,
+            edging: Edging::SubpixelAntiAlias,
+            embolden: false,
+            features: Vec::new(),
+        }
+    }
+
+    /// Creates a Skia `Font` configured for this style.
+    pub fn font(&self) -> Font {
+        let mut font = Font::from_typeface(self.typeface.clone(), self.size);
+        font.set_edging(self.edging);
+        font.set_embolden(self.embolden);
+        font
+    }
+
+    /// `features` as HarfBuzz takes them, each applied to the whole text.
+    pub fn hb_features(&self) -> Vec<Feature> {
+        self.features
+            .iter()
+            .map(|&(tag, value)| Feature::new(tag_from_bytes(tag), value, ..))
+            .collect()
+    }
+
+    /// Creates an anti-aliased fill `Paint` in this style's color, faded by
+    /// its opacity.
+    pub fn paint(&self) -> Paint {
+        let mut paint = Paint::default();
+        paint.set_color(self.color);
+        paint.set_alpha_f(paint.alpha_f() * self.opacity.clamp(0.0, 1.0));
+        paint.set_anti_alias(true);
+        paint
+    }
+}
@@ -0,0 +1,37 @@
+use ttf_parser::{Face, GlyphId};
+
+/// A font's SVG glyph documents (its `SVG ` table), parsed once for looking
+/// up many glyphs.
+pub struct SvgGlyphs<'a> {
+    /// `None` if ttf-parser can't read the font, which then has no SVG
+    /// glyphs.
+    face: Option<Face<'a>>,
+}
+
+impl<'a> SvgGlyphs<'a> {
+    pub fn new(font_data: &'a [u8]) -> Self {
+        SvgGlyphs {
+            face: Face::parse(font_data, 0).ok(),
+        }
+    }
+
+    /// Returns the SVG document for `glyph_id`.
+    ///
+    /// Only documents that describe exactly this one glyph are returned: a
+    /// document may cover a whole range of glyphs (each one an element with
+    /// `id="glyphN"`), and rendering it as a whole would draw all of them on
+    /// top of each other. Gzip-compressed documents (SVGZ) are skipped as
+    /// well. In both cases the caller should fall back to the glyph's other
+    /// representation (COLR, bitmap or plain outline).
+    pub fn document(&self, glyph_id: u16) -> Option<&'a [u8]> {
+        let document = self.face.as_ref()?.glyph_svg_image(GlyphId(glyph_id))?;
+        let is_gzip = document.data.starts_with(&[0x1f, 0x8b]);
+        let single_glyph = document.start_glyph_id == document.end_glyph_id;
+        (single_glyph && !is_gzip).then_some(document.data)
+    }
+
+    /// The font's units per em, which SVG glyph documents are drawn in.
+    pub fn units_per_em(&self) -> Option<u16> {
+        self.face.as_ref().map(|face| face.units_per_em())
+    }
+}
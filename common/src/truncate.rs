@@ -0,0 +1,46 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The character appended to truncated text.
+pub const ELLIPSIS: &str = "…";
+
+/// Truncates `text` so that it fits in `max_width`, appending an ellipsis.
+///
+/// `measure` returns the advance width of a string. It is called on every
+/// candidate prefix (including the ellipsis), so the prefix is re-measured as
+/// a whole rather than by summing per-character widths, which keeps kerning
+/// and shaping correct.
+///
+/// Text is only cut at grapheme cluster boundaries so we never split an emoji
+/// or a base letter from its combining marks. If `text` already fits it is
+/// returned unchanged, and if not even the ellipsis on its own fits an empty
+/// string is returned. So is one for empty text or a NaN `max_width`.
+pub fn truncate_with_ellipsis(text: &str, max_width: f32, measure: impl Fn(&str) -> f32) -> String {
+    // Nothing compares as fitting in NaN, which would leave the search below
+    // with no boundaries at all for empty text.
+    if text.is_empty() || max_width.is_nan() {
+        return String::new();
+    }
+    if measure(text) <= max_width {
+        return text.to_string();
+    }
+    if measure(ELLIPSIS) > max_width {
+        return String::new();
+    }
+
+    // Byte offsets where we're allowed to cut, shortest prefix first.
+    let boundaries: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+    let candidate = |end: usize| format!("{}{}", text[..end].trim_end(), ELLIPSIS);
+
+    // Binary search for the longest prefix that still fits. The empty prefix
+    // (boundaries[0] == 0) is known to fit because the ellipsis alone does.
+    let (mut lo, mut hi) = (0, boundaries.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if measure(&candidate(boundaries[mid])) <= max_width {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    candidate(boundaries[lo])
+}
@@ -0,0 +1,190 @@
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Breaks `text` into lines that fit in `max_width`.
+///
+/// Lines break between words, and `\n` always starts a new line. Like
+/// `truncate_with_ellipsis`, `measure` is called on whole candidate lines so
+/// kerning and shaping are taken into account. Spaces at the end of a line
+/// don't count towards its width and are dropped. A word that is too wide
+/// for a line of its own is broken between grapheme clusters instead.
+pub fn wrap_lines(text: &str, max_width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    wrap_line_ranges(text, max_width, measure)
+        .into_iter()
+        .map(|range| text[range].to_string())
+        .collect()
+}
+
+/// Like `wrap_lines`, but returns each line as a byte range into `text`.
+///
+/// This is what you need when something else was computed for the whole
+/// text, e.g. bidi levels, and has to be looked up per line.
+pub fn wrap_line_ranges(
+    text: &str,
+    max_width: f32,
+    measure: impl Fn(&str) -> f32,
+) -> Vec<Range<usize>> {
+    wrap_hyphenated(text, max_width, measure, |_| Vec::new())
+        .into_iter()
+        .map(|line| line.range)
+        .collect()
+}
+
+/// A line from `wrap_hyphenated`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrappedLine {
+    /// The line, as a byte range into the text.
+    pub range: Range<usize>,
+    /// The line ends in the middle of a word, and needs a hyphen drawn after
+    /// it (the hyphen isn't part of the text).
+    pub hyphenated: bool,
+}
+
+/// Like `wrap_line_ranges`, but a word that doesn't fit on the end of a line
+/// can be broken with a hyphen, at one of the points `hyphenate(word)`
+/// allows (byte offsets into the word, e.g. from a hyphenation dictionary).
+///
+/// The latest break point that still fits, hyphen included, is used; if
+/// none does, the word moves to the next line as usual. So a long word can
+/// be hyphenated more than once, and only a word without a usable break
+/// point falls back to breaking between grapheme clusters.
+pub fn wrap_hyphenated(
+    text: &str,
+    max_width: f32,
+    measure: impl Fn(&str) -> f32,
+    hyphenate: impl Fn(&str) -> Vec<usize>,
+) -> Vec<WrappedLine> {
+    let mut lines = Vec::new();
+    let mut push = |range: Range<usize>, hyphenated| lines.push(WrappedLine { range, hyphenated });
+    let mut paragraph_start = 0;
+    for paragraph in text.split('\n') {
+        // The current line, as a range into `text`.
+        let mut line = paragraph_start..paragraph_start;
+        for (offset, word) in paragraph.split_word_bound_indices() {
+            let word_start = paragraph_start + offset;
+            let mut word_range = word_start..word_start + word.len();
+            if measure(text[line.start..word_range.end].trim_end()) <= max_width {
+                line.end = word_range.end;
+                continue;
+            }
+
+            // Hyphenate the word for as long as the rest of it doesn't fit.
+            let breaks = if word.trim_start().is_empty() {
+                Vec::new()
+            } else {
+                hyphenate(word)
+            };
+            if !breaks.is_empty() {
+                loop {
+                    let split = breaks
+                        .iter()
+                        .rev()
+                        .map(|point| word_start + point)
+                        .filter(|&split| split > word_range.start && split < word_range.end)
+                        .find(|&split| {
+                            measure(&format!("{}-", &text[line.start..split])) <= max_width
+                        });
+                    if let Some(split) = split {
+                        push(line.start..split, true);
+                        line = split..split;
+                        word_range.start = split;
+                    } else if !text[line.clone()].trim_end().is_empty() {
+                        // Nothing fits after the words already on the line, so
+                        // try again at the start of a line of its own.
+                        push(trim_end(text, line), false);
+                        line = word_range.start..word_range.start;
+                    } else {
+                        break;
+                    }
+                    if measure(&text[word_range.clone()]) <= max_width {
+                        break;
+                    }
+                }
+                if measure(text[line.start..word_range.end].trim_end()) <= max_width {
+                    line.end = word_range.end;
+                    continue;
+                }
+            }
+            let word = &text[word_range.clone()];
+
+            // The word doesn't fit: finish the current line and start the next
+            // one with it (a space that caused the break is simply dropped).
+            if !text[line.clone()].trim_end().is_empty() {
+                push(trim_end(text, line), false);
+            }
+            line = if word.trim_start().is_empty() {
+                word_range.end..word_range.end
+            } else {
+                word_range.clone()
+            };
+
+            // Still too wide on its own, so split the word itself.
+            if measure(&text[line.clone()]) > max_width {
+                line = word_range.start..word_range.start;
+                for (offset, grapheme) in word.grapheme_indices(true) {
+                    let end = word_range.start + offset + grapheme.len();
+                    if line.is_empty() || measure(&text[line.start..end]) <= max_width {
+                        line.end = end;
+                    } else {
+                        push(line.clone(), false);
+                        line = end - grapheme.len()..end;
+                    }
+                }
+            }
+        }
+        push(trim_end(text, line), false);
+        paragraph_start += paragraph.len() + 1;
+    }
+    lines
+}
+
+/// Shrinks `range` so that `text[range]` has no trailing whitespace.
+fn trim_end(text: &str, range: Range<usize>) -> Range<usize> {
+    range.start..range.start + text[range].trim_end().len()
+}
+
+/// Text wrapped (and possibly shrunk) to fit a box; see `fit_to_box`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedText {
+    pub font_size: f32,
+    /// The lines, as byte ranges into the text.
+    pub lines: Vec<Range<usize>>,
+    /// False if even `min_size` was too big and the text overflows the box.
+    pub fits: bool,
+}
+
+/// Wraps `text` to `box_width`, shrinking the font size until the wrapped
+/// lines also fit in `box_height`.
+///
+/// Starts at `max_size` and goes down one pixel at a time, re-wrapping at
+/// every step: a smaller size fits more words per line, so the line breaks
+/// (not just the line heights) change. Stops at `min_size` even if the text
+/// still doesn't fit, since tiny text is rarely better than overflowing.
+///
+/// `measure(text, size)` returns the width of `text` at `size`, and
+/// `line_height(size)` the distance between baselines at `size`.
+pub fn fit_to_box(
+    text: &str,
+    box_width: f32,
+    box_height: f32,
+    max_size: f32,
+    min_size: f32,
+    measure: impl Fn(&str, f32) -> f32,
+    line_height: impl Fn(f32) -> f32,
+) -> FittedText {
+    let mut font_size = max_size;
+    loop {
+        let lines = wrap_line_ranges(text, box_width, |line| measure(line, font_size));
+        let height = lines.len() as f32 * line_height(font_size);
+        let fits = height <= box_height
+            && lines.iter().all(|line| measure(&text[line.clone()], font_size) <= box_width);
+        if fits || font_size <= min_size {
+            return FittedText {
+                font_size,
+                lines,
+                fits,
+            };
+        }
+        font_size = (font_size - 1.0).max(min_size);
+    }
+}
@@ -0,0 +1,55 @@
+use common::align::{centered_origin, draw_text_centered};
+use common::raster::{read_rgba, render_alpha_mask};
+use skia_safe::{AlphaType, Font, FontMgr, Paint, Rect};
+
+/// Draws `text` centered in `rect` on a 200x100 mask and returns the bounds
+/// of the ink, as (left, top, right, bottom) pixel edges.
+fn ink_bounds(text: &str, rect: Rect) -> (usize, usize, usize, usize) {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let font = Font::from_typeface(typeface, 24.0);
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    let (width, height) = (200, 100);
+    let mask = render_alpha_mask((width, height), |canvas| {
+        draw_text_centered(canvas, text, &font, &paint, rect)
+    })
+    .unwrap();
+    let pixels = read_rgba(&mask, AlphaType::Premul).unwrap();
+
+    let inked = |x: usize, y: usize| pixels[(y * width + x) * 4] > 127;
+    let columns: Vec<usize> = (0..width).filter(|&x| (0..height).any(|y| inked(x, y))).collect();
+    let rows: Vec<usize> = (0..height).filter(|&y| (0..width).any(|x| inked(x, y))).collect();
+    (columns[0], rows[0], columns[columns.len() - 1] + 1, rows[rows.len() - 1] + 1)
+}
+
+#[test]
+fn text_is_centered_in_the_rect() {
+    // "H" has the same side bearings on both sides and no descender, so its
+    // ink is centered exactly when the text is.
+    let rect = Rect::from_xywh(30.0, 20.0, 120.0, 60.0);
+    let (left, top, right, bottom) = ink_bounds("HH", rect);
+    let center_x = (left + right) as f32 / 2.0;
+    let center_y = (top + bottom) as f32 / 2.0;
+    assert!((center_x - rect.center_x()).abs() <= 1.0, "x center {center_x}");
+    assert!((center_y - rect.center_y()).abs() <= 1.0, "y center {center_y}");
+}
+
+#[test]
+fn overflowing_text_is_clipped_to_the_rect() {
+    let rect = Rect::from_xywh(80.0, 40.0, 40.0, 10.0);
+    let (left, top, right, bottom) = ink_bounds("Far too long a label", rect);
+    assert!(left >= 80 && right <= 120, "ink spills out sideways");
+    assert!(top >= 40 && bottom <= 50, "ink spills out vertically");
+}
+
+#[test]
+fn trailing_spaces_dont_shift_the_text() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let font = Font::from_typeface(typeface, 24.0);
+    let rect = Rect::from_xywh(0.0, 0.0, 200.0, 50.0);
+    assert_eq!(centered_origin("OK   ", &font, rect), centered_origin("OK", &font, rect));
+}
@@ -0,0 +1,74 @@
+use common::blob::{build_blob, FontRun};
+use common::raster::{read_rgba, render_alpha_mask};
+use common::shaping::GlyphRun;
+use skia_safe::{AlphaType, Font, FontMgr, Paint, Point, TextBlob};
+
+/// `text` laid out with `font` by Skia, as a run for `build_blob`.
+fn font_run(font: &Font, text: &str) -> FontRun {
+    let glyphs = font.str_to_glyphs_vec(text);
+    let mut positions = vec![Point::default(); glyphs.len()];
+    font.get_pos(&glyphs, &mut positions, None);
+    let (width, _) = font.measure_str(text, None);
+    FontRun {
+        font: font.clone(),
+        glyphs: GlyphRun {
+            clusters: (0..glyphs.len() as u32).collect(),
+            glyphs,
+            positions,
+            width,
+        },
+    }
+}
+
+/// Draws each blob at its origin, and returns the coverage.
+fn draw(blobs: &[(TextBlob, Point)]) -> Vec<u8> {
+    let mask = render_alpha_mask((200, 80), |canvas| {
+        for (blob, origin) in blobs {
+            canvas.draw_text_blob(blob, *origin, &Paint::default());
+        }
+    })
+    .unwrap();
+    read_rgba(&mask, AlphaType::Premul).unwrap()
+}
+
+#[test]
+fn second_run_starts_where_the_first_ends() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let small = Font::from_typeface(typeface.clone(), 16.0);
+    let big = Font::from_typeface(typeface, 32.0);
+    let first = font_run(&small, "ab");
+    let second = font_run(&big, "cd");
+
+    let blob = build_blob(&[first.clone(), second.clone()]).unwrap();
+    // The big font made it in: the blob is taller than the small font's
+    // glyphs could make it.
+    let (_, small_metrics) = small.metrics();
+    assert!(blob.bounds().height() > small_metrics.descent - small_metrics.ascent);
+
+    // One blob with both runs draws the same as each run on its own, the
+    // second moved along by the first one's width.
+    let origin = Point::new(10.0, 50.0);
+    let together = draw(&[(blob, origin)]);
+    let apart = draw(&[
+        (build_blob(&[first.clone()]).unwrap(), origin),
+        (build_blob(&[second]).unwrap(), origin + Point::new(first.glyphs.width, 0.0)),
+    ]);
+    assert_eq!(together, apart);
+}
+
+#[test]
+fn glyph_offsets_are_kept() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let font = Font::from_typeface(typeface, 24.0);
+    let plain = font_run(&font, "x");
+    let mut raised = plain.clone();
+    raised.glyphs.positions[0].y -= 10.0;
+
+    let plain = build_blob(&[plain]).unwrap().bounds();
+    let raised = build_blob(&[raised]).unwrap().bounds();
+    assert!((raised.top - (plain.top - 10.0)).abs() < 0.01);
+}
@@ -0,0 +1,33 @@
+use common::shaping::text_bounds;
+use skia_safe::{Contains, Font, FontMgr, Point};
+
+/// Measures `text` as Skia lays it out, in the default typeface at 32px.
+fn measure(text: &str) -> common::shaping::TextBounds {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let font = Font::from_typeface(typeface, 32.0);
+    let glyphs = font.str_to_glyphs_vec(text);
+    let mut positions = vec![Point::default(); glyphs.len()];
+    font.get_pos(&glyphs, &mut positions, None);
+    let (advance, _) = font.measure_str(text, None);
+    text_bounds(&font, &glyphs, &positions, advance)
+}
+
+#[test]
+fn ink_box_is_inside_the_logical_box() {
+    for text in ["hello, world", "Quick brown fox", "1234567890"] {
+        let bounds = measure(text);
+        assert!(!bounds.ink.is_empty(), "{text:?} has no ink");
+        assert!(bounds.logical.contains(bounds.ink), "{text:?}: {bounds:?}");
+        // The ink is tight, so it's strictly smaller.
+        assert!(bounds.ink.height() < bounds.logical.height());
+    }
+}
+
+#[test]
+fn spaces_have_a_logical_box_but_no_ink() {
+    let bounds = measure("   ");
+    assert!(bounds.logical.width() > 0.0);
+    assert!(bounds.ink.is_empty());
+}
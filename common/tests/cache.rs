@@ -0,0 +1,61 @@
+use common::cache::ShapeCache;
+use common::font::LoadedFont;
+use common::shaping::shape_text;
+use std::fs;
+
+fn rubik() -> Vec<u8> {
+    fs::read("../example-3/Rubik-VariableFont_wght.ttf").unwrap()
+}
+
+#[test]
+fn a_repeated_lookup_is_served_from_the_cache() {
+    let data = rubik();
+    let font = LoadedFont::from_bytes(&data, 20.0).unwrap();
+    let mut cache = ShapeCache::new(8);
+
+    let first = cache.measure_shaped(&font, "Hello").clone();
+    assert_eq!(first, shape_text(&font.hb_font, "Hello", &[]));
+    assert!(cache.contains(&font, "Hello"));
+    assert_eq!(cache.measure_shaped(&font, "Hello"), &first);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn a_new_size_or_new_variations_miss_the_cache() {
+    let data = rubik();
+    let mut font = LoadedFont::from_bytes(&data, 20.0).unwrap();
+    let mut cache = ShapeCache::new(8);
+    let regular_width = cache.measure_shaped(&font, "Hello").width;
+
+    // The same font at another size is a different entry...
+    font.set_size(40.0).unwrap();
+    assert!(!cache.contains(&font, "Hello"));
+    let bigger_width = cache.measure_shaped(&font, "Hello").width;
+    assert!(bigger_width > regular_width);
+
+    // ...and so is the same font at another weight.
+    font.set_size(20.0).unwrap();
+    assert!(cache.contains(&font, "Hello"));
+    font.set_variations(&[(*b"wght", 700.0)]);
+    assert!(!cache.contains(&font, "Hello"));
+    let bold_width = cache.measure_shaped(&font, "Hello").width;
+    assert!(bold_width > regular_width);
+    assert_eq!(cache.len(), 3);
+}
+
+#[test]
+fn the_least_recently_used_entry_is_evicted() {
+    let data = rubik();
+    let font = LoadedFont::from_bytes(&data, 20.0).unwrap();
+    let mut cache = ShapeCache::new(2);
+    cache.measure_shaped(&font, "one");
+    cache.measure_shaped(&font, "two");
+    // Using "one" again makes "two" the oldest.
+    cache.measure_shaped(&font, "one");
+    cache.measure_shaped(&font, "three");
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.contains(&font, "one"));
+    assert!(!cache.contains(&font, "two"));
+    assert!(cache.contains(&font, "three"));
+}
@@ -0,0 +1,58 @@
+use common::cluster::cluster_map;
+use common::font::LoadedFont;
+use harfbuzz_rs::{shape, UnicodeBuffer};
+use std::fs;
+
+/// Shapes `text` in Roboto and returns its cluster map.
+fn clusters(text: &str) -> Vec<(usize, usize, usize)> {
+    let font_data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let font = LoadedFont::from_bytes(&font_data, 20.0).unwrap();
+    let buffer = UnicodeBuffer::new().add_str(text).guess_segment_properties();
+    let shaped = shape(&font.hb_font, buffer, &[]);
+    cluster_map(shaped.get_glyph_infos(), text.len())
+}
+
+/// Checks that the map has one entry per glyph, in glyph order, and that its
+/// clusters cover every byte of the text exactly once.
+fn assert_complete(text: &str, map: &[(usize, usize, usize)]) {
+    let indices: Vec<usize> = map.iter().map(|&(glyph_index, _, _)| glyph_index).collect();
+    assert_eq!(indices, (0..map.len()).collect::<Vec<_>>());
+
+    // Glyphs of the same cluster report the same range, so count each once.
+    let mut ranges: Vec<(usize, usize)> = map.iter().map(|&(_, start, end)| (start, end)).collect();
+    ranges.dedup();
+    let mut covered = vec![0; text.len()];
+    for (start, end) in ranges {
+        for count in &mut covered[start..end] {
+            *count += 1;
+        }
+    }
+    assert!(covered.iter().all(|&count| count == 1), "{text:?}: {map:?}");
+}
+
+#[test]
+fn ligature_covers_all_of_its_characters() {
+    let text = "office";
+    let map = clusters(text);
+    assert_complete(text, &map);
+    // Roboto draws "ffi" as one glyph.
+    assert_eq!(map.len(), 4, "{map:?}");
+    assert!(map.iter().any(|&(_, start, end)| &text[start..end] == "ffi"), "{map:?}");
+}
+
+#[test]
+fn combining_sequences_stay_one_cluster() {
+    // "é" typed as e + U+0301 is drawn as the precomposed glyph: one glyph
+    // for two characters.
+    let text = "e\u{301}";
+    let map = clusters(text);
+    assert_complete(text, &map);
+    assert_eq!(map, [(0, 0, text.len())]);
+
+    // There's no precomposed "q́", so the accent is a glyph of its own, in
+    // the same cluster as its base.
+    let text = "q\u{301}";
+    let map = clusters(text);
+    assert_complete(text, &map);
+    assert_eq!(map, [(0, 0, text.len()), (1, 0, text.len())]);
+}
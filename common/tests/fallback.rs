@@ -0,0 +1,14 @@
+use common::fallback::missing_chars;
+use skia_safe::{Data, Font, FontMgr};
+use std::fs;
+
+#[test]
+fn latin_font_is_missing_the_emoji() {
+    let data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let typeface = FontMgr::new().new_from_data(&Data::new_copy(&data), None).unwrap();
+    let font = Font::from_typeface(typeface, 20.0);
+
+    let missing = missing_chars(&font, "hello, world 🌎\nhe\u{301}llo 🌎!");
+    assert_eq!(missing.into_iter().collect::<Vec<_>>(), ['🌎']);
+    assert!(missing_chars(&font, "plain ASCII text").is_empty());
+}
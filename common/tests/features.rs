@@ -0,0 +1,28 @@
+use common::features::{features_by_script, supports_feature};
+use std::fs;
+
+#[test]
+fn small_caps_font_lists_smcp() {
+    let data = fs::read("../example-4/Roboto-LightItalic.ttf").unwrap();
+    let scripts = features_by_script(&data);
+    for script in ["DFLT", "latn", "cyrl", "grek"] {
+        assert!(scripts[script].contains("smcp"), "no smcp for {script}");
+    }
+    // Ligatures are only set up for Latin.
+    assert!(scripts["latn"].contains("liga"));
+    assert!(!scripts["grek"].contains("liga"));
+    assert!(supports_feature(&data, *b"smcp"));
+}
+
+#[test]
+fn emoji_font_has_no_small_caps() {
+    let data = fs::read("../example-4/NotoColorEmoji-Regular.ttf").unwrap();
+    let scripts = features_by_script(&data);
+    assert!(!scripts.is_empty());
+    assert!(scripts.values().all(|features| !features.contains("smcp")));
+}
+
+#[test]
+fn not_a_font_has_no_features() {
+    assert!(features_by_script(b"not a font").is_empty());
+}
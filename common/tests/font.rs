@@ -0,0 +1,11 @@
+use common::font::{match_axes, parse_variations};
+
+#[test]
+fn variations_are_matched_to_the_font_axes() {
+    let axes = [(*b"wght", 400.0), (*b"wdth", 100.0)];
+    let variations = parse_variations("wdth=75, slnt=-10").unwrap();
+    let (values, unknown) = match_axes(&axes, &variations);
+    // One value per axis, in the font's order; wght keeps its default.
+    assert_eq!(values, [400.0, 75.0]);
+    assert_eq!(unknown, [*b"slnt"]);
+}
@@ -0,0 +1,53 @@
+use common::itemize::itemize;
+use common::style::TextStyle;
+use skia_safe::FontMgr;
+use unicode_script::Script;
+
+#[test]
+fn runs_split_at_style_script_and_direction_changes() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let body = TextStyle::new(typeface, 16.0);
+    let big = TextStyle {
+        size: 24.0,
+        ..body.clone()
+    };
+
+    // "Hello" in the big style, the rest in the body style.
+    let text = "Hello, مرحبا 2024 world!";
+    let runs: Vec<(&str, f32, Script, u8)> =
+        itemize(text, &[(0..5, big), (5..text.len(), body)])
+            .into_iter()
+            .map(|run| (&text[run.range], run.style.size, run.script, run.level))
+            .collect();
+    assert_eq!(
+        runs,
+        [
+            ("Hello", 24.0, Script::Latin, 0),
+            (", ", 16.0, Script::Latin, 0),
+            // The space after the Arabic word is between two right-to-left
+            // pieces (the digits follow Arabic, so they're Arabic numbers),
+            // so it's right-to-left too.
+            ("مرحبا ", 16.0, Script::Arabic, 1),
+            ("2024", 16.0, Script::Arabic, 2),
+            // Spaces and digits keep the script before them; this space is
+            // back in the left-to-right paragraph.
+            (" ", 16.0, Script::Arabic, 0),
+            ("world!", 16.0, Script::Latin, 0),
+        ],
+    );
+}
+
+#[test]
+fn text_outside_the_styles_is_left_out() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let style = TextStyle::new(typeface, 16.0);
+    let text = "one two three";
+    let runs = itemize(text, &[(4..7, style)]);
+    assert_eq!(runs.len(), 1);
+    assert_eq!(&text[runs[0].range.clone()], "two");
+    assert!(!runs[0].is_rtl());
+}
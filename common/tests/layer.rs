@@ -0,0 +1,61 @@
+use common::layer::{draw_layers, TextLayer};
+use common::raster::read_rgba;
+use skia_safe::{AlphaType, Color, Font, FontMgr, Paint, Surface, TextBlob};
+
+/// The same word twice in the same spot, red at `red_z` and blue at `blue_z`,
+/// with the red one listed first. Returns the (red, blue) channels of each
+/// pixel.
+fn draw_red_and_blue(red_z: i32, blue_z: i32) -> Vec<(u8, u8)> {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let font = Font::from_typeface(typeface, 48.0);
+    let blob = TextBlob::from_str("MW", &font).unwrap();
+    let layer = |color: Color, z_index| {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true).set_color(color);
+        TextLayer {
+            blob: blob.clone(),
+            origin: (10, 60).into(),
+            paint,
+            z_index,
+        }
+    };
+
+    let mut surface = Surface::new_raster_n32_premul((120, 80)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    draw_layers(
+        surface.canvas(),
+        &[layer(Color::RED, red_z), layer(Color::BLUE, blue_z)],
+    );
+    let pixels = read_rgba(&surface.image_snapshot(), AlphaType::Premul).unwrap();
+    pixels.chunks(4).map(|p| (p[0], p[2])).collect()
+}
+
+/// Whether `top` is never noticeably weaker than `bottom`. Where one layer
+/// covers the other this holds even at the anti-aliased edges, give or take
+/// rounding.
+fn dominates(top: u8, bottom: u8) -> bool {
+    top as i32 >= bottom as i32 - 2
+}
+
+#[test]
+fn higher_z_index_draws_on_top() {
+    // Red is listed first but has the higher z-index, so it covers the blue.
+    let pixels = draw_red_and_blue(1, 0);
+    assert!(pixels.iter().any(|&(red, blue)| red == 255 && blue == 0));
+    assert!(pixels.iter().all(|&(red, blue)| dominates(red, blue)));
+
+    // The other way round, blue covers the red.
+    let pixels = draw_red_and_blue(0, 1);
+    assert!(pixels.iter().any(|&(red, blue)| red == 0 && blue == 255));
+    assert!(pixels.iter().all(|&(red, blue)| dominates(blue, red)));
+}
+
+#[test]
+fn equal_z_indices_keep_their_order() {
+    // Blue is listed last, so it ends up on top.
+    let pixels = draw_red_and_blue(0, 0);
+    assert!(pixels.iter().any(|&(red, blue)| red == 0 && blue == 255));
+    assert!(pixels.iter().all(|&(red, blue)| dominates(blue, red)));
+}
@@ -0,0 +1,29 @@
+use common::markup::parse_markup;
+use common::style::TextStyle;
+use skia_safe::{Color, FontMgr};
+
+fn base_style() -> TextStyle {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    TextStyle::new(typeface, 20.0)
+}
+
+#[test]
+fn bold_tag_only_covers_its_text() {
+    let runs = parse_markup("[b]Hi[/b] there", &base_style());
+    let runs: Vec<(&str, bool)> = runs
+        .iter()
+        .map(|(text, style)| (text.as_str(), style.embolden))
+        .collect();
+    assert_eq!(runs, [("Hi", true), (" there", false)]);
+}
+
+#[test]
+fn unclosed_and_unknown_tags_degrade_to_text() {
+    let runs = parse_markup("[i]x[/i] [color=#f00]red", &base_style());
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].0, "[i]x[/i] ");
+    assert_eq!(runs[1].0, "red");
+    assert_eq!(runs[1].1.color, Color::from_rgb(255, 0, 0));
+}
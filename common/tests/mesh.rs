@@ -0,0 +1,66 @@
+use common::mesh::triangulate;
+use common::raster::{read_rgba, render_alpha_mask};
+use skia_safe::{AlphaType, Data, Font, FontMgr, Paint, Path};
+use std::fs;
+
+/// The outline of "o" at 100px, with its baseline at (20, 100).
+fn glyph_o() -> Path {
+    let data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let typeface = FontMgr::new().new_from_data(&Data::new_copy(&data), None).unwrap();
+    let font = Font::from_typeface(typeface, 100.0);
+    let glyph = font.str_to_glyphs_vec("o")[0];
+    font.get_path(glyph).unwrap().with_offset((20.0, 100.0))
+}
+
+/// Twice the signed area of a triangle.
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+#[test]
+fn mesh_covers_the_glyph() {
+    let path = glyph_o();
+    let mesh = triangulate(&path, 0.1).unwrap();
+    assert!(!mesh.vertices.is_empty());
+    assert!(!mesh.indices.is_empty() && mesh.indices.len() % 3 == 0);
+    assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+
+    // No degenerate (zero-area) triangles, and together they cover as much
+    // as the rasterized glyph does.
+    let triangles: Vec<[[f32; 2]; 3]> = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|t| [0, 1, 2].map(|i| mesh.vertices[t[i] as usize]))
+        .collect();
+    let mut mesh_area = 0.0;
+    for &[a, b, c] in &triangles {
+        let area = cross(a, b, c).abs() / 2.0;
+        assert!(area > 1e-4, "degenerate triangle {a:?} {b:?} {c:?}");
+        mesh_area += area;
+    }
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    let mask = render_alpha_mask((150, 150), |canvas| canvas.draw_path(&path, &paint)).unwrap();
+    let pixels = read_rgba(&mask, AlphaType::Premul).unwrap();
+    // The mask is gray, coverage in every channel.
+    let raster_area: f32 = pixels.chunks(4).map(|pixel| pixel[0] as f32 / 255.0).sum();
+    let ratio = mesh_area / raster_area;
+    assert!((0.98..1.02).contains(&ratio), "mesh {mesh_area}, raster {raster_area}");
+
+    // The counter (the hole in the middle) is left out.
+    let center = path.bounds().center();
+    let center = [center.x, center.y];
+    let inside = |&[a, b, c]: &[[f32; 2]; 3]| {
+        let sides = [cross(a, b, center), cross(b, c, center), cross(c, a, center)];
+        sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+    };
+    assert!(!triangles.iter().any(inside), "the counter is filled");
+}
+
+#[test]
+fn obj_export_lists_every_vertex_and_triangle() {
+    let mesh = triangulate(&glyph_o(), 0.5).unwrap();
+    let obj = mesh.to_obj();
+    assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), mesh.vertices.len());
+    assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), mesh.indices.len() / 3);
+}
@@ -0,0 +1,34 @@
+use common::metrics::line_metrics;
+use skia_safe::{Data, Font, FontMgr};
+use std::fs;
+
+fn font(path: &str, size: f32) -> Font {
+    let data = fs::read(path).unwrap();
+    let typeface = FontMgr::new().new_from_data(&Data::new_copy(&data), None).unwrap();
+    Font::from_typeface(typeface, size)
+}
+
+#[test]
+fn line_height_is_the_tallest_fonts() {
+    // The same font at twice the size is taller every way, so the line box
+    // is exactly the big font's.
+    let small = font("../example-2/Roboto-LightItalic.ttf", 20.0).metrics().1;
+    let big = font("../example-2/Roboto-LightItalic.ttf", 40.0).metrics().1;
+    let line = line_metrics([small, big]);
+    assert_eq!((line.ascent, line.descent, line.leading), (big.ascent, big.descent, big.leading));
+    assert_eq!(line.height(), big.descent - big.ascent + big.leading);
+    // The order of the fonts doesn't matter.
+    assert_eq!(line_metrics([big, small]), line);
+}
+
+#[test]
+fn mixed_fonts_fit_in_the_line() {
+    let text = font("../example-2/Roboto-LightItalic.ttf", 20.0).metrics().1;
+    let emoji = font("../example-2/NotoColorEmoji-Regular.ttf", 20.0).metrics().1;
+    let line = line_metrics([text, emoji]);
+    for metrics in [text, emoji] {
+        assert!(line.ascent <= metrics.ascent && line.descent >= metrics.descent);
+    }
+    assert_eq!(line.ascent, text.ascent.min(emoji.ascent));
+    assert_eq!(line.descent, text.descent.max(emoji.descent));
+}
@@ -0,0 +1,125 @@
+use common::paragraph::{FontFiles, Paragraph};
+use common::raster::read_rgba;
+use common::script_fonts::ScriptFontMap;
+use common::style::TextStyle;
+use skia_safe::{AlphaType, Color, Data, FontMgr, Surface, Typeface};
+use std::fs;
+use unicode_script::Script;
+
+const TEXT: &str = "The quick brown fox jumps over the lazy dog, twice over.";
+
+fn paragraph(width: f32) -> Paragraph {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let style = TextStyle::new(typeface.clone(), 20.0);
+    let fonts = ScriptFontMap::new(typeface.clone());
+    Paragraph::new(TEXT, &style, &fonts, &typeface, width)
+}
+
+fn draw(paragraph: &Paragraph) -> Vec<u8> {
+    let mut surface = Surface::new_raster_n32_premul((320, 200)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    paragraph.draw(surface.canvas(), (10, 10));
+    read_rgba(&surface.image_snapshot(), AlphaType::Premul).unwrap()
+}
+
+#[test]
+fn drawing_twice_reuses_the_layout() {
+    let paragraph = paragraph(300.0);
+    let first = draw(&paragraph);
+    let second = draw(&paragraph);
+
+    assert!(first.iter().any(|&channel| channel < 128), "nothing drawn");
+    assert_eq!(first, second);
+    assert_eq!(paragraph.layouts(), 1);
+}
+
+#[test]
+fn only_a_new_width_relayouts() {
+    let mut paragraph = paragraph(300.0);
+    let (lines, height) = (paragraph.line_count(), paragraph.height());
+
+    paragraph.set_width(300.0);
+    assert_eq!(paragraph.layouts(), 1);
+
+    paragraph.set_width(150.0);
+    assert_eq!(paragraph.layouts(), 2);
+    assert_eq!(paragraph.width(), 150.0);
+    assert!(paragraph.line_count() > lines);
+    assert!(paragraph.height() > height);
+}
+
+fn load(path: &str) -> Typeface {
+    let data = fs::read(path).unwrap();
+    FontMgr::new().new_from_data(&Data::new_copy(&data), None).unwrap()
+}
+
+#[test]
+fn runs_pick_fonts_by_script_and_fall_back_for_emoji() {
+    let roboto = load("../example-1/Roboto-LightItalic.ttf");
+    let rubik = load("../example-3/Rubik-VariableFont_wght.ttf");
+    let emoji = load("../example-22/NotoColorEmoji-Regular.ttf");
+    let mut fonts = ScriptFontMap::new(roboto.clone());
+    fonts.insert(Script::Arabic, rubik.clone());
+
+    let style = TextStyle::new(roboto.clone(), 20.0);
+    let runs: Vec<(String, u32)> = Paragraph::runs("Hello مرحبا 🎉", &style, &fonts, &emoji)
+        .into_iter()
+        .map(|(run, run_style)| (run, run_style.typeface.unique_id()))
+        .collect();
+    // The space after the Arabic word goes back to left-to-right, so it's a
+    // run of its own. The emoji is `Common` and joins the Arabic run, and
+    // only it is checked against Rubik, which has no glyph for it.
+    assert_eq!(
+        runs,
+        [
+            ("Hello ".to_string(), roboto.unique_id()),
+            ("مرحبا".to_string(), rubik.unique_id()),
+            (" ".to_string(), rubik.unique_id()),
+            ("🎉".to_string(), emoji.unique_id()),
+        ],
+    );
+}
+
+/// The rightmost column with ink in it, in a 320 pixel wide rendering.
+fn ink_right(pixels: &[u8]) -> usize {
+    (0..320)
+        .rev()
+        .find(|&x| pixels.chunks(320 * 4).any(|row| row[x * 4] < 128))
+        .unwrap()
+}
+
+#[test]
+fn runs_are_shaped_with_kerning() {
+    let roboto = load("../example-1/Roboto-LightItalic.ttf");
+    let style = TextStyle::new(roboto.clone(), 40.0);
+    let fonts = ScriptFontMap::new(roboto.clone());
+    let text = "AVAVAVAVAV";
+    let shaped = Paragraph::new(text, &style, &fonts, &roboto, 1000.0);
+
+    // Skia on its own draws every glyph at its plain advance.
+    let mut surface = Surface::new_raster_n32_premul((320, 200)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    let (_, metrics) = style.font().metrics();
+    surface.canvas().draw_str(text, (10.0, 10.0 - metrics.ascent), &style.font(), &style.paint());
+    let unshaped = read_rgba(&surface.image_snapshot(), AlphaType::Premul).unwrap();
+
+    // Roboto kerns both "AV" and "VA", by 14px over the nine pairs.
+    let (shaped, unshaped) = (ink_right(&draw(&shaped)), ink_right(&unshaped));
+    assert!(shaped + 10 < unshaped, "shaped {shaped}, unshaped {unshaped}");
+}
+
+#[test]
+fn style_features_are_passed_to_the_shaper() {
+    let roboto = load("../example-1/Roboto-LightItalic.ttf");
+    let files = FontFiles::new(&ScriptFontMap::new(roboto.clone()), &roboto);
+    let style = TextStyle::new(roboto, 20.0);
+    let no_ligatures = TextStyle {
+        features: vec![(*b"liga", 0)],
+        ..style.clone()
+    };
+    // Roboto draws "ffi" as one glyph, unless ligatures are turned off.
+    assert_eq!(files.shape("office", &style).glyphs.len(), 4);
+    assert_eq!(files.shape("office", &no_ligatures).glyphs.len(), 6);
+}
@@ -0,0 +1,34 @@
+use common::raster::{color_managed_surface, read_rgba, render_color_managed, BlendSpace};
+use skia_safe::{AlphaType, Color, Paint, Rect};
+
+#[test]
+fn surface_carries_the_chosen_color_space() {
+    for space in [BlendSpace::Srgb, BlendSpace::Linear] {
+        let surface = color_managed_surface((10, 10), space).unwrap();
+        assert_eq!(surface.image_info().color_space(), Some(space.color_space()));
+    }
+    assert!(BlendSpace::Srgb.color_space().is_srgb());
+    assert!(!BlendSpace::Linear.color_space().is_srgb());
+}
+
+#[test]
+fn linear_blending_is_brighter_between_colors() {
+    // Half-transparent red over green, like an anti-aliased edge of red text.
+    let blend = |space| {
+        let image = render_color_managed((4, 4), space, Color::from_rgb(0, 255, 0), |canvas| {
+            let mut paint = Paint::default();
+            paint.set_color(Color::from_argb(128, 255, 0, 0));
+            canvas.draw_rect(Rect::from_wh(4.0, 4.0), &paint);
+        })
+        .unwrap();
+        let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+        (pixels[0], pixels[1])
+    };
+    let (srgb_red, srgb_green) = blend(BlendSpace::Srgb);
+    let (linear_red, linear_green) = blend(BlendSpace::Linear);
+
+    // Averaging sRGB values gives about (128, 127); averaging light and then
+    // encoding it as sRGB gives about (188, 187).
+    assert!(linear_red > srgb_red + 40, "{linear_red} vs {srgb_red}");
+    assert!(linear_green > srgb_green + 40, "{linear_green} vs {srgb_green}");
+}
@@ -0,0 +1,29 @@
+use common::error::TextError;
+use common::registry::TypefaceRegistry;
+
+const FONT: &str = "../example-1/Roboto-LightItalic.ttf";
+
+#[test]
+fn loading_a_path_twice_returns_the_same_typeface() {
+    let mut registry = TypefaceRegistry::new();
+    let first = registry.load(FONT, 0).unwrap();
+    let second = registry.load(FONT, 0).unwrap();
+    // Skia gives every loaded typeface its own id, so equal ids mean the
+    // file was only loaded once.
+    assert_eq!(first.unique_id(), second.unique_id());
+    assert_eq!(registry.len(), 1);
+
+    // A new registry loads its own copy.
+    let other = TypefaceRegistry::new().load(FONT, 0).unwrap();
+    assert_ne!(first.unique_id(), other.unique_id());
+}
+
+#[test]
+fn missing_file_is_a_font_load_error() {
+    let mut registry = TypefaceRegistry::new();
+    match registry.load("does-not-exist.ttf", 0) {
+        Err(TextError::FontLoad { path }) => assert_eq!(path, "does-not-exist.ttf"),
+        other => panic!("expected a FontLoad error, got {:?}", other.map(|_| ())),
+    }
+    assert!(registry.is_empty());
+}
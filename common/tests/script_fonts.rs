@@ -0,0 +1,53 @@
+use common::script_fonts::{script_runs, ScriptFontMap};
+use common::style::TextStyle;
+use skia_safe::{Data, FontMgr, Typeface};
+use std::fs;
+use unicode_script::Script;
+
+fn load(path: &str) -> Typeface {
+    let data = fs::read(path).unwrap();
+    FontMgr::new().new_from_data(&Data::new_copy(&data), None).unwrap()
+}
+
+#[test]
+fn arabic_and_latin_go_to_their_own_fonts() {
+    let roboto = load("../example-1/Roboto-LightItalic.ttf");
+    let rubik = load("../example-3/Rubik-VariableFont_wght.ttf");
+    let (roboto_id, rubik_id) = (roboto.unique_id(), rubik.unique_id());
+    let mut fonts = ScriptFontMap::new(roboto.clone());
+    fonts.insert(Script::Arabic, rubik);
+
+    // Spaces, digits and punctuation stay with the script before them.
+    let text = "Hello, مرحبا 2024 world!";
+    let runs: Vec<(&str, Script)> = script_runs(text)
+        .into_iter()
+        .map(|(range, script)| (&text[range], script))
+        .collect();
+    assert_eq!(
+        runs,
+        [("Hello, ", Script::Latin), ("مرحبا 2024 ", Script::Arabic), ("world!", Script::Latin)],
+    );
+
+    let style = TextStyle::new(roboto, 20.0);
+    let runs: Vec<(String, u32)> = fonts
+        .runs(text, &style)
+        .into_iter()
+        .map(|(run, run_style)| (run, run_style.typeface.unique_id()))
+        .collect();
+    assert_eq!(
+        runs,
+        [
+            ("Hello, ".to_string(), roboto_id),
+            ("مرحبا 2024 ".to_string(), rubik_id),
+            ("world!".to_string(), roboto_id),
+        ],
+    );
+}
+
+#[test]
+fn text_without_a_script_joins_the_first_script_after_it() {
+    let text = "«2024» Hello";
+    assert_eq!(script_runs(text), [(0..text.len(), Script::Latin)]);
+    assert_eq!(script_runs("12:30"), [(0..5, Script::Common)]);
+    assert_eq!(script_runs(""), []);
+}
@@ -0,0 +1,55 @@
+use common::font::LoadedFont;
+use common::shaping::{measure_width, shape_text, stream_glyphs, TrailingWhitespace};
+use skia_safe::Point;
+use std::fs;
+
+#[test]
+fn trailing_spaces_only_count_when_included() {
+    let font_data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let font = LoadedFont::from_bytes(&font_data, 20.0).unwrap();
+
+    let with_spaces = measure_width(&font.hb_font, "Save   ", TrailingWhitespace::Include);
+    let without_spaces = measure_width(&font.hb_font, "Save   ", TrailingWhitespace::Exclude);
+    let bare = measure_width(&font.hb_font, "Save", TrailingWhitespace::Include);
+
+    assert!(with_spaces > without_spaces + 10.0, "{with_spaces} vs {without_spaces}");
+    // Unless the font kerns the last letter against a space, that's just the
+    // visible text on its own.
+    assert!((without_spaces - bare).abs() < 0.5, "{without_spaces} vs {bare}");
+    // Spaces in the middle aren't trailing, so they always count.
+    assert_eq!(
+        measure_width(&font.hb_font, "a b", TrailingWhitespace::Exclude),
+        measure_width(&font.hb_font, "a b", TrailingWhitespace::Include),
+    );
+}
+
+#[test]
+fn streamed_glyphs_match_the_shaped_run() {
+    let font_data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let font = LoadedFont::from_bytes(&font_data, 20.0).unwrap();
+    let text = "Waffles, AVA and office fjords: the quick brown fox.";
+
+    // One run is exactly `shape_text`, glyph for glyph.
+    let run = shape_text(&font.hb_font, text, &[]);
+    let streamed: Vec<_> = stream_glyphs(&font.hb_font, [text], &[]).collect();
+    assert_eq!(streamed.iter().map(|g| g.0).collect::<Vec<_>>(), run.glyphs);
+    assert_eq!(streamed.iter().map(|g| g.1).collect::<Vec<_>>(), run.positions);
+    assert_eq!(streamed.iter().map(|g| g.2).sum::<f32>(), run.width);
+
+    // Word by word, it's each word's `shape_text`, moved along by the words
+    // before it.
+    let words: Vec<&str> = text.split_inclusive(' ').collect();
+    let mut expected = Vec::new();
+    let mut pen_x = 0.0;
+    for word in &words {
+        let run = shape_text(&font.hb_font, word, &[]);
+        for (&glyph, &position) in run.glyphs.iter().zip(&run.positions) {
+            expected.push((glyph, position + Point::new(pen_x, 0.0)));
+        }
+        pen_x += run.width;
+    }
+    let streamed: Vec<_> = stream_glyphs(&font.hb_font, &words, &[])
+        .map(|(glyph, position, _)| (glyph, position))
+        .collect();
+    assert_eq!(streamed, expected);
+}
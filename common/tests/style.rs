@@ -0,0 +1,44 @@
+use common::raster::read_rgba;
+use common::style::TextStyle;
+use skia_safe::{font::Edging, AlphaType, Color, FontMgr, Rect, Surface};
+
+#[test]
+fn half_opacity_blends_halfway_with_the_background() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let style = TextStyle {
+        opacity: 0.5,
+        ..TextStyle::new(typeface, 20.0)
+    };
+    assert_eq!(style.paint().alpha(), 128);
+
+    // Black at 50% over white is mid gray.
+    let mut surface = Surface::new_raster_n32_premul((4, 4)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    surface.canvas().draw_rect(Rect::from_wh(4.0, 4.0), &style.paint());
+    let pixels = read_rgba(&surface.image_snapshot(), AlphaType::Premul).unwrap();
+    assert!(pixels[0].abs_diff(128) <= 1, "got {}", pixels[0]);
+}
+
+#[test]
+fn each_style_configures_its_own_font() {
+    let typeface = FontMgr::new()
+        .legacy_make_typeface(None, Default::default())
+        .expect("no default typeface");
+    let body = TextStyle::new(typeface, 16.0);
+    let heading = TextStyle {
+        size: 32.0,
+        embolden: true,
+        edging: Edging::AntiAlias,
+        ..body.clone()
+    };
+
+    let (body_font, heading_font) = (body.font(), heading.font());
+    assert_eq!(body_font.size(), 16.0);
+    assert_eq!(heading_font.size(), 32.0);
+    assert!(!body_font.is_embolden());
+    assert!(heading_font.is_embolden());
+    assert_eq!(body_font.edging(), Edging::SubpixelAntiAlias);
+    assert_eq!(heading_font.edging(), Edging::AntiAlias);
+}
@@ -0,0 +1,28 @@
+use common::svg_glyph::SvgGlyphs;
+use std::fs;
+use ttf_parser::Face;
+
+#[test]
+fn only_single_glyph_documents_are_returned() {
+    let data = fs::read("../example-4/NotoColorEmoji-Regular.ttf").unwrap();
+    let face = Face::parse(&data, 0).unwrap();
+    let glyph = |c: char| face.glyph_index(c).unwrap().0;
+    let svg = SvgGlyphs::new(&data);
+
+    assert_eq!(svg.units_per_em(), Some(1024));
+    // The pizza has a document of its own...
+    let pizza = svg.document(glyph('🍕')).unwrap();
+    assert!(pizza.starts_with(b"<svg"));
+    // ...but the globe shares one with its neighbours, which would draw all
+    // three globes on top of each other.
+    assert_eq!(svg.document(glyph('🌎')), None);
+}
+
+#[test]
+fn fonts_without_an_svg_table_have_no_documents() {
+    let data = fs::read("../example-1/Roboto-LightItalic.ttf").unwrap();
+    let svg = SvgGlyphs::new(&data);
+    assert_eq!(svg.units_per_em(), Some(2048));
+    assert_eq!(svg.document(1), None);
+    assert_eq!(SvgGlyphs::new(b"not a font").document(1), None);
+}
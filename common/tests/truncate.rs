@@ -0,0 +1,29 @@
+use common::truncate::truncate_with_ellipsis;
+
+/// Counts characters, so the ellipsis and each code point are one unit wide.
+fn chars(text: &str) -> f32 {
+    text.chars().count() as f32
+}
+
+#[test]
+fn text_that_fits_is_unchanged() {
+    assert_eq!(truncate_with_ellipsis("hello", 5.0, chars), "hello");
+    assert_eq!(truncate_with_ellipsis("hello", f32::INFINITY, chars), "hello");
+}
+
+#[test]
+fn cuts_never_split_a_grapheme() {
+    // Cutting by code point would leave "ae…", dropping the accent off the e.
+    assert_eq!(truncate_with_ellipsis("ae\u{301}bc", 3.0, chars), "a…");
+    // ...and "ab👩\u{200d}…" here, half of the emoji ZWJ sequence.
+    assert_eq!(truncate_with_ellipsis("ab👩\u{200d}💻c", 5.0, chars), "ab…");
+}
+
+#[test]
+fn empty_text_and_nan_widths_give_an_empty_string() {
+    assert_eq!(truncate_with_ellipsis("", f32::NAN, chars), "");
+    assert_eq!(truncate_with_ellipsis("", 10.0, chars), "");
+    assert_eq!(truncate_with_ellipsis("hello", f32::NAN, chars), "");
+    // Not even the ellipsis fits.
+    assert_eq!(truncate_with_ellipsis("hello", 0.5, chars), "");
+}
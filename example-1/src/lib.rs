@@ -0,0 +1,689 @@
+use common::align::centered_origin;
+use common::error::TextError;
+use common::picture::record_picture;
+use common::png::set_dpi;
+use common::raster::{
+    read_rgba, render_alpha_mask, render_color_managed, render_supersampled, to_grayscale,
+    BlendSpace,
+};
+use common::registry::TypefaceRegistry;
+use common::shaping::text_bounds;
+use common::style::TextStyle;
+use common::truncate::truncate_with_ellipsis;
+use skia_safe::{
+    color_filters, image_filters,
+    paint::{Join as PaintJoin, Style as PaintStyle},
+    AlphaType, BlendMode, Canvas, Color, Data, EncodedImageFormat, Font, ISize, Image,
+    ImageFilter, Matrix, Paint, Point, Rect, SamplingOptions, Surface, TileMode,
+};
+use std::fs;
+use std::path::PathBuf;
+
+/// Draws the text as configured and returns the image as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, TextError> {
+    run_with_registry(config, &mut TypefaceRegistry::new())
+}
+
+/// Like `run`, but loads the font through `typefaces`, so rendering many
+/// images with the same font only loads it once.
+pub fn run_with_registry(
+    config: &Config,
+    typefaces: &mut TypefaceRegistry,
+) -> Result<Vec<u8>, TextError> {
+    let image = render(config, typefaces)?;
+
+    // Encode the image as PNG.
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or(TextError::Encode { format: "PNG" })?;
+
+    // With --dpi, tag the PNG with a physical resolution for print tools.
+    let png_bytes = match config.dpi {
+        Some(dpi) => {
+            set_dpi(png_data.as_bytes(), dpi).ok_or(TextError::Encode { format: "PNG" })?
+        }
+        None => png_data.as_bytes().to_vec(),
+    };
+
+    Ok(png_bytes)
+}
+
+/// Draws the text as configured and returns the image's size and its
+/// raw RGBA8888 pixels, for tools that take uncompressed pixels.
+///
+/// The color channels are premultiplied by alpha, or with
+/// `config.straight_alpha` unpremultiplied. That only makes a difference for
+/// semi-transparent pixels, so use it together with `config.transparent`.
+pub fn run_rgba(config: &Config) -> Result<(ISize, Vec<u8>), TextError> {
+    let image = render(config, &mut TypefaceRegistry::new())?;
+    let alpha_type = if config.straight_alpha {
+        AlphaType::Unpremul
+    } else {
+        AlphaType::Premul
+    };
+    let pixels = read_rgba(&image, alpha_type).ok_or(TextError::Encode { format: "RGBA" })?;
+    Ok((image.dimensions(), pixels))
+}
+
+/// Draws the text as configured.
+fn render(config: &Config, typefaces: &mut TypefaceRegistry) -> Result<Image, TextError> {
+    // Load the custom typeface from the font file (or reuse it, if the
+    // registry has loaded it before).
+    let typeface = typefaces.load(&config.font_path, 0)?;
+
+    // Describe how the text should look: 32px (or --size), black (or --color,
+    // or the sticker's fill), sub-pixel anti-aliased. The style hands us a
+    // matching Font and Paint for drawing.
+    let style = TextStyle {
+        color: config.sticker.map_or(config.color, |sticker| sticker.fill),
+        ..TextStyle::new(typeface, config.font_size)
+    };
+    let mut font_obj = style.font();
+    let mut paint = style.paint();
+
+    // With --snap, also turn off sub-pixel glyph positioning (and keep
+    // baseline snapping on), so every glyph starts on a whole pixel too.
+    if config.snap_to_pixel {
+        font_obj.set_subpixel(false);
+        font_obj.set_baseline_snap(true);
+    }
+
+    let (text, origin) = layout(config, &font_obj);
+
+    // The alpha mask is drawn white on transparent (only coverage matters).
+    if config.alpha_mask {
+        paint.set_color(Color::WHITE);
+    } else if let Some(pattern_path) = &config.pattern {
+        // With --pattern, fill the glyphs with a tiled image instead of a flat
+        // color. The shader repeats the image in both directions, starting at
+        // the canvas origin, so wide text just picks up more tiles. The local
+        // matrix scales the pattern (--pattern-scale 0.5 gives smaller tiles).
+        let pattern_error = || TextError::ImageLoad {
+            path: pattern_path.clone(),
+        };
+        let pattern_bytes = fs::read(pattern_path).map_err(|_| pattern_error())?;
+        let pattern =
+            Image::from_encoded(Data::new_copy(&pattern_bytes)).ok_or_else(pattern_error)?;
+        let scale = Matrix::scale((config.pattern_scale, config.pattern_scale));
+        let shader = pattern
+            .to_shader(
+                (TileMode::Repeat, TileMode::Repeat),
+                SamplingOptions::default(),
+                &scale,
+            )
+            .ok_or_else(pattern_error)?;
+        paint.set_shader(shader);
+    }
+
+    // With --filter, the paint itself carries the effect: Skia draws the
+    // text into a temporary layer, runs the filter over it and composites
+    // the result, all as part of the one `draw_str`.
+    if let Some(filter) = &config.filter {
+        paint.set_image_filter(filter.image_filter());
+    }
+
+    // With --color-matrix, every color the paint puts down (the flat color
+    // or the pattern's pixels) goes through the matrix first. Only the text
+    // changes; the background is drawn separately and stays as it is.
+    if let Some(matrix) = &config.color_matrix {
+        paint.set_color_filter(color_filters::matrix_row_major(matrix, None));
+    }
+
+    // With --box, the text is clipped to the box it's centered in.
+    let (x, y) = TEXT_POSITION;
+    let text_box = config.text_box.map(|(w, h)| Rect::from_xywh(x, y, w, h));
+
+    let (text_width, _) = font_obj.measure_str(&text, Some(&paint));
+
+    // With --bounds, outline the text's logical box (advance width by line
+    // height) in blue and its ink box (tight around the glyphs) in red.
+    // They're measured for the glyphs Skia draws, relative to the origin.
+    let glyphs = font_obj.str_to_glyphs_vec(&text);
+    let mut positions = vec![Point::default(); glyphs.len()];
+    font_obj.get_pos(&glyphs, &mut positions, None);
+    let bounds = text_bounds(&font_obj, &glyphs, &positions, text_width);
+
+    // With --upside-down, turn the text 180° about the middle of its logical
+    // box, so it stays where it was rather than swinging around the origin.
+    let upside_down = config
+        .upside_down
+        .then(|| Matrix::rotate_deg_pivot(180.0, bounds.logical.with_offset(origin).center()));
+
+    let draw = |canvas: &Canvas| {
+        // With --mirror, flip the canvas horizontally around the text's center
+        // before drawing. Flipping around the center (rather than the canvas
+        // origin) keeps the mirrored text in the same spot, so it stays on-canvas.
+        canvas.save();
+        if let Some(rect) = text_box {
+            canvas.clip_rect(rect, None, true);
+        }
+        if config.mirror {
+            let center_x = origin.x + text_width / 2.0;
+            canvas.translate((center_x, 0.0));
+            canvas.scale((-1.0, 1.0));
+            canvas.translate((-center_x, 0.0));
+        }
+        if let Some(matrix) = &upside_down {
+            canvas.concat(matrix);
+        }
+        // With --faux-italic, skew the canvas around the baseline so the
+        // glyphs lean right while their baseline stays put.
+        if config.faux_italic {
+            canvas.translate((0.0, origin.y));
+            canvas.skew((-FAUX_ITALIC_SKEW, 0.0));
+            canvas.translate((0.0, -origin.y));
+        }
+        // With --sticker, first stroke the outlines in the outline color. A
+        // stroke is centered on the glyph's edge, so it is twice as wide as
+        // the outline that remains visible once the fill covers its inner half.
+        // Round joins keep sharp corners (like the tip of a "v") from
+        // shooting out as long miters.
+        if let Some(sticker) = &config.sticker {
+            // (The alpha mask only keeps coverage, so it's drawn white too.)
+            let mut outline_paint = Paint::default();
+            outline_paint.set_anti_alias(true);
+            outline_paint.set_color(if config.alpha_mask { Color::WHITE } else { sticker.outline });
+            outline_paint.set_style(PaintStyle::Stroke);
+            outline_paint.set_stroke_width(2.0 * sticker.outline_width);
+            outline_paint.set_stroke_join(PaintJoin::Round);
+            canvas.draw_str(&text, origin, &font_obj, &outline_paint);
+        }
+        // With --faux-bold, first stroke the outlines in the fill's paint (same
+        // color or pattern), which grows every glyph by half the stroke width on
+        // each side, then fill as usual on top.
+        // (Without the --filter, or the stroke would get a shadow of its own.)
+        if config.faux_bold {
+            let mut stroke_paint = paint.clone();
+            stroke_paint.set_image_filter(None);
+            stroke_paint.set_style(PaintStyle::Stroke);
+            stroke_paint.set_stroke_width(faux_bold_width(style.size));
+            stroke_paint.set_stroke_join(PaintJoin::Round);
+            canvas.draw_str(&text, origin, &font_obj, &stroke_paint);
+        }
+        canvas.draw_str(&text, origin, &font_obj, &paint);
+        if config.show_bounds {
+            let mut bounds_paint = Paint::default();
+            bounds_paint.set_anti_alias(true);
+            bounds_paint.set_style(PaintStyle::Stroke);
+            for (rect, color) in [(bounds.logical, Color::BLUE), (bounds.ink, Color::RED)] {
+                bounds_paint.set_color(color);
+                canvas.draw_rect(rect.with_offset(origin), &bounds_paint);
+            }
+        }
+        canvas.restore();
+    };
+
+    // Render onto a white 300x100 image, either directly or (with --supersample N)
+    // at N times the size and then scaled down for smoother small text.
+    // With --alpha-mask we instead keep just the text's coverage, as grayscale.
+    // With --overflow visible, the image grows to fit the text instead. A
+    // faux italic leans the top of the text right by up to ascent * skew, so
+    // then the image also grows to keep the same right margin as left.
+    let slant_overhang = if config.faux_italic {
+        -font_obj.metrics().1.ascent * FAUX_ITALIC_SKEW
+    } else {
+        0.0
+    };
+    // With --box, the box sets the size instead.
+    let (content_width, content_height) = match text_box {
+        Some(rect) => (rect.width(), rect.height()),
+        None if config.overflow == Overflow::Visible => (text_width + slant_overhang, 0.0),
+        None => (0.0, 0.0),
+    };
+    let (min_width, min_height) = IMAGE_SIZE;
+    let width = min_width.max((2.0 * x + content_width).ceil() as i32);
+    let height = min_height.max((2.0 * y + content_height).ceil() as i32);
+    // Upside down, the logical box lands on itself, but ink outside it (the
+    // italic's overhang, tall accents) ends up mirrored to the other side.
+    // With --overflow visible, grow the image so the turned ink keeps the
+    // usual margin too.
+    let (width, height) = match (&upside_down, config.overflow) {
+        (Some(matrix), Overflow::Visible) => {
+            let (ink, _) = matrix.map_rect(bounds.ink.with_offset(origin));
+            (
+                width.max((ink.right + x).ceil() as i32),
+                height.max((ink.bottom + y).ceil() as i32),
+            )
+        }
+        _ => (width, height),
+    };
+    let surface_error = || TextError::SurfaceCreation { width, height };
+    // With --transparent the background is left transparent instead, so the
+    // text's anti-aliased edges end up semi-transparent.
+    let background = if config.transparent {
+        Color::TRANSPARENT
+    } else {
+        Color::WHITE
+    };
+
+    // With --dump-picture, also record everything drawn (background
+    // included) and save it as an .skp, which replays to the same image.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), |canvas| {
+            canvas.clear(background);
+            draw(canvas);
+        })
+        .ok_or(TextError::Encode { format: "SKP" })?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+
+    // With --blend-space, draw on a color-managed surface that blends the
+    // anti-aliased edges in sRGB or in linear light. With --halftone, the
+    // text's coverage is redrawn as dots.
+    let image = match (config.halftone, config.supersample, config.blend_space) {
+        _ if config.alpha_mask => {
+            render_alpha_mask((width, height), draw).ok_or_else(surface_error)?
+        }
+        (Some(halftone), _, _) => {
+            render_halftone((width, height), halftone, background, paint.color(), draw)
+                .ok_or_else(surface_error)?
+        }
+        (None, Some(factor), _) => render_supersampled((width, height), factor, background, draw)
+            .ok_or_else(surface_error)?,
+        (None, None, Some(space)) => render_color_managed((width, height), space, background, draw)
+            .ok_or_else(surface_error)?,
+        (None, None, None) => {
+            let mut surface =
+                Surface::new_raster_n32_premul((width, height)).ok_or_else(surface_error)?;
+            let canvas = surface.canvas();
+
+            // Clear the canvas to the background color.
+            canvas.clear(background);
+            draw(canvas);
+
+            // Snapshot the surface as an image.
+            surface.image_snapshot()
+        }
+    };
+
+    // With --grayscale, keep one channel instead of three. Text drawn in
+    // black on white only has grays anyway, so nothing visible is lost.
+    if config.grayscale {
+        return to_grayscale(&image).ok_or(TextError::Encode { format: "grayscale" });
+    }
+
+    Ok(image)
+}
+
+/// Draws `draw` as a coverage mask, then redraws it in `color` as a grid of
+/// dots, one per `halftone.spacing` pixels, like a halftone print.
+///
+/// Each dot stands for the average coverage of its cell. It's the dot's area
+/// (not its radius) that follows the coverage, so a cell looks about as dark
+/// as the ink it replaces: solid parts of the glyphs get the biggest dots,
+/// edges smaller ones, and empty cells none.
+fn render_halftone(
+    size: impl Into<ISize>,
+    halftone: Halftone,
+    background: Color,
+    color: Color,
+    draw: impl FnOnce(&Canvas),
+) -> Option<Image> {
+    let size = size.into();
+    let mask = render_alpha_mask(size, draw)?;
+    // The mask is grayscale, so read back as RGBA each channel is the coverage.
+    let coverage = read_rgba(&mask, AlphaType::Premul)?;
+    let (width, height) = (size.width as usize, size.height as usize);
+    let spacing = halftone.spacing.max(1.0);
+
+    let mut surface = Surface::new_raster_n32_premul(size)?;
+    let canvas = surface.canvas();
+    canvas.clear(background);
+    let mut dot_paint = Paint::default();
+    dot_paint.set_anti_alias(true).set_color(color);
+
+    let mut cell_y = 0.0;
+    while cell_y < height as f32 {
+        let mut cell_x = 0.0;
+        while cell_x < width as f32 {
+            let xs = cell_x as usize..((cell_x + spacing) as usize).min(width);
+            let ys = cell_y as usize..((cell_y + spacing) as usize).min(height);
+            let pixel_count = xs.len() * ys.len();
+            let total: u32 = ys
+                .flat_map(|y| xs.clone().map(move |x| coverage[(y * width + x) * 4] as u32))
+                .sum();
+            let cell_coverage = total as f32 / (255.0 * pixel_count as f32);
+            if cell_coverage > 0.0 {
+                let center = (cell_x + spacing / 2.0, cell_y + spacing / 2.0);
+                canvas.draw_circle(center, halftone.max_radius * cell_coverage.sqrt(), &dot_paint);
+            }
+            cell_x += spacing;
+        }
+        cell_y += spacing;
+    }
+    Some(surface.image_snapshot())
+}
+
+/// Where the text goes, before anchoring.
+const TEXT_POSITION: (f32, f32) = (50.0, 50.0);
+
+/// The size of the image, unless the text box or `Overflow::Visible` make it
+/// bigger.
+const IMAGE_SIZE: (i32, i32) = (300, 100);
+
+/// Returns where `run` puts the start of the text's baseline.
+pub fn text_origin(config: &Config) -> Result<Point, TextError> {
+    let typeface = TypefaceRegistry::new().load(&config.font_path, 0)?;
+    let font = TextStyle::new(typeface, config.font_size).font();
+    Ok(layout(config, &font).1)
+}
+
+/// Returns the text to draw with `font` and where its baseline starts.
+fn layout(config: &Config, font: &Font) -> (String, Point) {
+    // Draw the text ("hello, world" by default) at coordinates (50, 50).
+    // Skia always draws text relative to its baseline, so with the default
+    // anchor (50, 50) is where the baseline starts, and most of the glyphs end
+    // up *above* y = 50. With the top-left anchor we shift the baseline down by
+    // the font's ascent (which Skia reports as a negative distance above the
+    // baseline), so the top of the tallest glyph in the font touches y = 50.
+    let (x, y) = TEXT_POSITION;
+    let baseline_y = match config.anchor {
+        Anchor::Baseline => y,
+        Anchor::TopLeft => {
+            let (_, metrics) = font.metrics();
+            y - metrics.ascent
+        }
+    };
+
+    // With --max-width, cut the text down (ending in "…") so it fits.
+    let text = match config.max_width {
+        Some(max_width) => {
+            truncate_with_ellipsis(&config.text, max_width, |s| font.measure_str(s, None).0)
+        }
+        None => config.text.clone(),
+    };
+
+    // With --box, center the text in a box of that size at (x, y) instead,
+    // like a button label. The anchor doesn't apply.
+    // With --overflow scroll-end, text too wide for the image (less the
+    // margins) is moved left until its end meets the right margin.
+    let origin = match (config.text_box, config.overflow) {
+        (Some((w, h)), _) => centered_origin(&text, font, Rect::from_xywh(x, y, w, h)),
+        (None, Overflow::ScrollEnd) => {
+            let available = IMAGE_SIZE.0 as f32 - 2.0 * x;
+            let (text_width, _) = font.measure_str(&text, None);
+            Point::new(x + (available - text_width).min(0.0), baseline_y)
+        }
+        (None, _) => Point::new(x, baseline_y),
+    };
+
+    // With --snap, round the origin to whole pixels. The ascent and the
+    // centering above are fractional, which would otherwise put the baseline
+    // and the left edge in the middle of a pixel row/column and blur them
+    // across two (see `Config::snap_to_pixel`).
+    let origin = if config.snap_to_pixel {
+        Point::new(origin.x.round(), origin.y.round())
+    } else {
+        origin
+    };
+    (text, origin)
+}
+
+/// Horizontal skew for faux italic: tan(12°), a typical oblique angle.
+const FAUX_ITALIC_SKEW: f32 = 0.2126;
+
+/// The stroke width for faux bold at `font_size` pixels.
+///
+/// A fixed width would barely show on big text and fill in the counters of
+/// small text, so it scales with the size, at roughly the ratio Skia uses
+/// for its own fake bold.
+fn faux_bold_width(font_size: f32) -> f32 {
+    font_size / 28.0
+}
+
+/// Colors for sticker text: a fill with a contrasting outline around it, so it
+/// stays readable over any background (e.g. captions over video).
+#[derive(Debug, Clone, Copy)]
+pub struct Sticker {
+    pub fill: Color,
+    pub outline: Color,
+    /// How far the outline reaches out from the glyphs, in pixels.
+    pub outline_width: f32,
+}
+
+impl Default for Sticker {
+    fn default() -> Self {
+        Sticker {
+            fill: Color::WHITE,
+            outline: Color::from_rgb(0x20, 0x20, 0x20),
+            outline_width: 3.0,
+        }
+    }
+}
+
+/// The dot grid for halftone text.
+#[derive(Debug, Clone, Copy)]
+pub struct Halftone {
+    /// Distance between dot centers, in pixels.
+    pub spacing: f32,
+    /// Radius of the dot for a fully covered cell, in pixels. Half the
+    /// spacing makes those dots just touch; more makes them merge.
+    pub max_radius: f32,
+}
+
+impl Default for Halftone {
+    fn default() -> Self {
+        Halftone {
+            spacing: 4.0,
+            max_radius: 2.0,
+        }
+    }
+}
+
+/// What happens to text that's wider than the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// The image keeps its size and the end of the text is cut off by its
+    /// right edge.
+    Clip,
+    /// The image grows to fit the text, with the same margin on the right as
+    /// on the left.
+    Visible,
+    /// The image keeps its size and the text is moved left so its end is in
+    /// view, up against the right margin, and its beginning is cut off
+    /// instead. It's how a single-line text field shows what's just been
+    /// typed. Text that fits stays where it is.
+    ScrollEnd,
+}
+
+/// An effect applied to the text as it's drawn, by an image filter on its
+/// paint.
+#[derive(Debug, Clone, Copy)]
+pub enum TextFilter {
+    /// A blurred copy of the text behind it, moved by `offset`.
+    DropShadow {
+        offset: (f32, f32),
+        /// Blur radius (the Gaussian's sigma), in pixels.
+        blur: f32,
+        color: Color,
+    },
+    /// The text itself blurred, with this sigma in pixels.
+    Blur(f32),
+    /// The text raised off the page and lit from the top left. The shading
+    /// replaces the text's color with grays.
+    Emboss,
+}
+
+impl TextFilter {
+    /// A soft shadow down and to the right, in half-transparent black.
+    pub const DROP_SHADOW: TextFilter = TextFilter::DropShadow {
+        offset: (3.0, 3.0),
+        blur: 2.0,
+        color: Color::from_argb(0x80, 0, 0, 0),
+    };
+
+    fn image_filter(&self) -> Option<ImageFilter> {
+        match *self {
+            // Draws the shadow and then the text on top of it, which is
+            // what drawing the text twice by hand would do.
+            TextFilter::DropShadow {
+                offset,
+                blur,
+                color,
+            } => image_filters::drop_shadow(offset, (blur, blur), color, None, None, None),
+            TextFilter::Blur(sigma) => image_filters::blur((sigma, sigma), None, None, None),
+            TextFilter::Emboss => {
+                // The lighting filter treats the alpha of its input as a
+                // height map, so a slightly blurred copy of the text gives
+                // sloped edges. It lights the whole layer, though, so the
+                // result is cut back down to the text's shape (`SrcIn`, with
+                // the unfiltered text as the background).
+                let height_map = image_filters::blur((1.0, 1.0), None, None, None);
+                let light_direction = (-1.0, -1.0, 1.0);
+                let lit = image_filters::distant_lit_diffuse(
+                    light_direction,
+                    Color::WHITE,
+                    2.0,
+                    1.0,
+                    height_map,
+                    None,
+                );
+                image_filters::blend(BlendMode::SrcIn, None, lit, None)
+            }
+        }
+    }
+}
+
+/// A color matrix that tones colors sepia: each color's brightness picks a
+/// shade between a dark brown (for black) and a warm off-white (for white).
+///
+/// Color matrices are row-major, 4 rows of 5: each row gives one output
+/// channel (red, green, blue, alpha) as a weighted sum of the input's
+/// unpremultiplied red, green, blue and alpha, plus an offset in the last
+/// column, all on a 0 to 1 scale. The brightness weights are Rec. 601's.
+pub const SEPIA_MATRIX: [f32; 20] = {
+    const DARK: [f32; 3] = [0.44, 0.26, 0.08];
+    const LIGHT: [f32; 3] = [1.0, 0.94, 0.82];
+    const fn row(channel: usize) -> [f32; 5] {
+        let range = LIGHT[channel] - DARK[channel];
+        [0.299 * range, 0.587 * range, 0.114 * range, 0.0, DARK[channel]]
+    }
+    let (r, g, b) = (row(0), row(1), row(2));
+    [
+        r[0], r[1], r[2], r[3], r[4], //
+        g[0], g[1], g[2], g[3], g[4], //
+        b[0], b[1], b[2], b[3], b[4], //
+        0.0, 0.0, 0.0, 1.0, 0.0,
+    ]
+};
+
+/// A color matrix that inverts colors (black becomes white) and keeps alpha.
+/// See `SEPIA_MATRIX` for the layout.
+pub const INVERT_MATRIX: [f32; 20] = [
+    -1.0, 0.0, 0.0, 0.0, 1.0, //
+    0.0, -1.0, 0.0, 0.0, 1.0, //
+    0.0, 0.0, -1.0, 0.0, 1.0, //
+    0.0, 0.0, 0.0, 1.0, 0.0,
+];
+
+/// Where the (x, y) passed to `draw_str` sits relative to the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    /// (x, y) is the start of the baseline. This is what Skia does natively.
+    Baseline,
+    /// (x, y) is the top-left corner of the text, like most UI toolkits.
+    TopLeft,
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    /// The font to draw with.
+    pub font_path: String,
+    /// The font size, in pixels.
+    pub font_size: f32,
+    pub anchor: Anchor,
+    pub max_width: Option<f32>,
+    /// What to do with text wider than the image. Not used with `text_box`,
+    /// which sizes the image and clips the text to the box.
+    pub overflow: Overflow,
+    pub mirror: bool,
+    pub supersample: Option<u32>,
+    pub alpha_mask: bool,
+    pub dpi: Option<f32>,
+    /// Image file to fill the text with, tiled.
+    pub pattern: Option<String>,
+    pub pattern_scale: f32,
+    pub faux_bold: bool,
+    pub faux_italic: bool,
+    /// The text color (ignored for the alpha mask and patterns).
+    pub color: Color,
+    /// Draw outlined "sticker" text; its fill replaces `color`.
+    pub sticker: Option<Sticker>,
+    /// Draw on a transparent background instead of white.
+    pub transparent: bool,
+    /// For `run_rgba`: unpremultiplied (straight) alpha instead of premultiplied.
+    pub straight_alpha: bool,
+    /// Center the text in a box this size, `(width, height)`, like a button
+    /// label.
+    pub text_box: Option<(f32, f32)>,
+    /// Outline the text's logical and ink boxes.
+    pub show_bounds: bool,
+    /// Put the baseline and every glyph on whole pixels.
+    ///
+    /// Horizontal edges (baseline, x-height, cap height) and vertical stems
+    /// then land on pixel boundaries and come out crisp, as in most UI
+    /// toolkits. The price is spacing: each glyph is moved by up to half a
+    /// pixel, so at small sizes letters look unevenly spaced ("rn" can drift
+    /// into "m"), and text animated sideways jumps a pixel at a time instead
+    /// of gliding. Sub-pixel positioning keeps the spacing the font designed,
+    /// with softer edges.
+    pub snap_to_pixel: bool,
+    /// Draw on a surface with this color space, which is what anti-aliasing
+    /// blends in (see `BlendSpace`). Not used with `supersample`.
+    pub blend_space: Option<BlendSpace>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    /// It's recorded at the output size, so it replays to the plain image
+    /// (not the supersampled or color-managed one).
+    pub dump_picture: Option<PathBuf>,
+    /// Output a single-channel (8-bit grayscale) image. Any color is turned
+    /// into its brightness.
+    pub grayscale: bool,
+    /// Turn the text 180° about its center.
+    pub upside_down: bool,
+    /// Draw the text as a grid of dots, sized by how much of each cell the
+    /// glyphs cover.
+    pub halftone: Option<Halftone>,
+    /// An effect for the text (not for the faux bold or sticker strokes).
+    pub filter: Option<TextFilter>,
+    /// A color matrix (like `SEPIA_MATRIX`) applied to the text's colors,
+    /// including its pattern and faux bold stroke but not the sticker
+    /// outline.
+    pub color_matrix: Option<[f32; 20]>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            text: "hello, world".to_string(),
+            font_path: "Roboto-LightItalic.ttf".to_string(),
+            font_size: 32.0,
+            anchor: Anchor::Baseline,
+            max_width: None,
+            overflow: Overflow::Clip,
+            mirror: false,
+            supersample: None,
+            alpha_mask: false,
+            dpi: None,
+            pattern: None,
+            pattern_scale: 1.0,
+            faux_bold: false,
+            faux_italic: false,
+            color: Color::BLACK,
+            sticker: None,
+            transparent: false,
+            straight_alpha: false,
+            text_box: None,
+            show_bounds: false,
+            snap_to_pixel: false,
+            blend_space: None,
+            dump_picture: None,
+            grayscale: false,
+            upside_down: false,
+            halftone: None,
+            filter: None,
+            color_matrix: None,
+        }
+    }
+}
@@ -1,58 +1,238 @@
-use skia_safe::{
-    Color, Data, EncodedImageFormat, Font, Paint, Surface, FontMgr
+use common::error::TextError;
+use common::info::{detect_format, font_info};
+use common::markup::parse_hex_color;
+use common::output::write_output;
+use common::raster::BlendSpace;
+use example_1::{
+    run, run_rgba, Anchor, Config, Halftone, Overflow, Sticker, TextFilter, INVERT_MATRIX,
+    SEPIA_MATRIX,
 };
-use std::error::Error;
+use skia_safe::Color;
+use std::env;
 use std::fs;
+use std::str::FromStr;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Load the font data from the file.
-    let font_path = "Roboto-LightItalic.ttf";
-    let font_file = fs::read(font_path)?;
-    let data = Data::new_copy(&font_file);
-
-    // Use the system font manager to load the custom typeface.
-    // This is similar to the docs example, but here we only need one font.
-    let font_mgr = FontMgr::new();
-    let typeface = font_mgr
-        .new_from_data(&data, None)
-        .ok_or("Failed to load the font from file")?;
-
-    // Create and configure a simple Font.
-    let font_size = 32.0;
-    let mut font_obj = Font::default();
-    font_obj.set_size(font_size);
-    font_obj.set_typeface(typeface);
-    // Enable sub-pixel anti-aliasing.
-    font_obj.set_edging(skia_safe::font::Edging::SubpixelAntiAlias);
-
-    // Set up a Paint for drawing the text.
-    let mut paint = Paint::default();
-    paint.set_color(Color::BLACK);
-    paint.set_anti_alias(true);
-
-    // Create a raster surface to draw on (e.g., 300x100 pixels).
-    let width = 300;
-    let height = 100;
-    let mut surface = Surface::new_raster_n32_premul((width, height))
-        .ok_or("Could not create a surface")?;
-    let canvas = surface.canvas();
-
-    // Clear the canvas with a white background.
-    canvas.clear(Color::WHITE);
-
-    // Draw the text "hello, world" at coordinates (50, 50).
-    canvas.draw_str("hello, world", (50, 50), &font_obj, &paint);
-
-    // Snapshot the surface as an image and encode it as PNG.
-    let image = surface.image_snapshot();
-    let png_data = image
-        .encode_to_data(EncodedImageFormat::PNG)
-        .ok_or("Failed to encode image")?;
-
-    // Write the PNG data to a file.
-    fs::write("output.png", png_data.as_bytes())?;
-
-    println!("Image written to output.png");
+/// The file to write.
+enum Output {
+    Png,
+    /// Raw RGBA8888 pixels, without any header.
+    Rgba,
+    /// Nothing: print what's in the font file instead.
+    Info,
+}
+
+fn main() -> Result<(), TextError> {
+    let (config, output, out_path) = config_from_args()?;
+    match output {
+        Output::Png => {
+            let png_bytes = run(&config)?;
+
+            // Write the PNG data to a file (or with --out -, to stdout; so
+            // from here on, messages go to stderr).
+            let path = out_path.as_deref().unwrap_or("output.png");
+            write_output(path, &png_bytes)?;
+
+            eprintln!("Image written to {path}");
+        }
+        Output::Info => {
+            let path = &config.font_path;
+            let font_data = fs::read(path)?;
+            match (font_info(&font_data), detect_format(&font_data)) {
+                (Some(info), _) => print!("{info}"),
+                (None, Some(format)) => println!("Format: {format} (decompress it to see more)"),
+                (None, None) => return Err(TextError::FontLoad { path: path.clone() }),
+            }
+        }
+        Output::Rgba => {
+            let (size, pixels) = run_rgba(&config)?;
+            let path = out_path.as_deref().unwrap_or("output.rgba");
+            write_output(path, &pixels)?;
+
+            // Raw pixels don't record their size, so tell the user.
+            let alpha = if config.straight_alpha { "straight" } else { "premultiplied" };
+            eprintln!(
+                "{}x{} RGBA pixels ({alpha} alpha) written to {path}",
+                size.width, size.height
+            );
+        }
+    }
+    if let (Some(path), false) = (&config.dump_picture, matches!(output, Output::Info)) {
+        eprintln!("Picture written to {}", path.display());
+    }
 
     Ok(())
 }
+
+/// Reads the `Config` and output format from the command line, e.g.
+/// `cargo run -- --anchor top-left` or `cargo run -- --rgba --transparent --straight-alpha`,
+/// and where to write it (`--out`, where `-` is stdout).
+fn config_from_args() -> Result<(Config, Output, Option<String>), TextError> {
+    let mut config = Config::default();
+    let mut output = Output::Png;
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--anchor" => {
+                config.anchor = match args.next().as_deref() {
+                    Some("baseline") => Anchor::Baseline,
+                    Some("top-left") => Anchor::TopLeft,
+                    _ => {
+                        return Err(TextError::InvalidArgument(
+                            "--anchor expects `baseline` or `top-left`".into(),
+                        ))
+                    }
+                }
+            }
+            "--overflow" => {
+                config.overflow = match args.next().as_deref() {
+                    Some("clip") => Overflow::Clip,
+                    Some("visible") => Overflow::Visible,
+                    Some("scroll-end") => Overflow::ScrollEnd,
+                    _ => {
+                        return Err(TextError::InvalidArgument(
+                            "--overflow expects `clip`, `visible` or `scroll-end`".into(),
+                        ))
+                    }
+                }
+            }
+            "--max-width" => {
+                config.max_width =
+                    Some(parse_value(args.next(), "--max-width expects a width in pixels")?);
+            }
+            "--mirror" => config.mirror = true,
+            "--upside-down" => config.upside_down = true,
+            "--supersample" => {
+                config.supersample =
+                    Some(parse_value(args.next(), "--supersample expects a factor, e.g. 3")?);
+            }
+            "--alpha-mask" => config.alpha_mask = true,
+            "--faux-bold" => config.faux_bold = true,
+            "--faux-italic" => config.faux_italic = true,
+            "--color" => config.color = parse_color(args.next(), "--color")?,
+            // Any of the sticker options turns sticker mode on.
+            "--sticker" => {
+                config.sticker.get_or_insert_with(Sticker::default);
+            }
+            "--outline-width" => {
+                config.sticker.get_or_insert_with(Sticker::default).outline_width =
+                    parse_value(args.next(), "--outline-width expects a width in pixels")?;
+            }
+            "--outline-color" => {
+                config.sticker.get_or_insert_with(Sticker::default).outline =
+                    parse_color(args.next(), "--outline-color")?;
+            }
+            "--fill-color" => {
+                config.sticker.get_or_insert_with(Sticker::default).fill =
+                    parse_color(args.next(), "--fill-color")?;
+            }
+            "--filter" => {
+                config.filter = Some(match args.next().as_deref() {
+                    Some("drop-shadow") => TextFilter::DROP_SHADOW,
+                    Some("blur") => TextFilter::Blur(2.0),
+                    Some("emboss") => TextFilter::Emboss,
+                    _ => {
+                        return Err(TextError::InvalidArgument(
+                            "--filter expects `drop-shadow`, `blur` or `emboss`".into(),
+                        ))
+                    }
+                })
+            }
+            "--color-matrix" => {
+                config.color_matrix = Some(match args.next().as_deref() {
+                    Some("sepia") => SEPIA_MATRIX,
+                    Some("invert") => INVERT_MATRIX,
+                    _ => {
+                        return Err(TextError::InvalidArgument(
+                            "--color-matrix expects `sepia` or `invert`".into(),
+                        ))
+                    }
+                })
+            }
+            // Any of the halftone options turns halftone mode on.
+            "--halftone" => {
+                config.halftone.get_or_insert_with(Halftone::default);
+            }
+            "--dot-spacing" => {
+                config.halftone.get_or_insert_with(Halftone::default).spacing =
+                    parse_value(args.next(), "--dot-spacing expects a distance in pixels")?;
+            }
+            "--dot-radius" => {
+                config.halftone.get_or_insert_with(Halftone::default).max_radius =
+                    parse_value(args.next(), "--dot-radius expects a radius in pixels")?;
+            }
+            "--box" => {
+                let expected = "--box expects a size like 200x60";
+                let size: String = parse_value(args.next(), expected)?;
+                let (width, height) = size.split_once('x').ok_or_else(|| {
+                    TextError::InvalidArgument(expected.to_string())
+                })?;
+                config.text_box = Some((
+                    parse_value(Some(width.to_string()), expected)?,
+                    parse_value(Some(height.to_string()), expected)?,
+                ));
+            }
+            "--bounds" => config.show_bounds = true,
+            "--snap" => config.snap_to_pixel = true,
+            "--grayscale" => config.grayscale = true,
+            "--dump-picture" => {
+                config.dump_picture =
+                    Some(parse_value(args.next(), "--dump-picture expects a file path")?);
+            }
+            "--blend-space" => {
+                config.blend_space = match args.next().as_deref() {
+                    Some("srgb") => Some(BlendSpace::Srgb),
+                    Some("linear") => Some(BlendSpace::Linear),
+                    _ => {
+                        return Err(TextError::InvalidArgument(
+                            "--blend-space expects `srgb` or `linear`".into(),
+                        ))
+                    }
+                }
+            }
+            "--transparent" => config.transparent = true,
+            "--text" => config.text = parse_value(args.next(), "--text expects a string")?,
+            "--font" => {
+                config.font_path = parse_value(args.next(), "--font expects a font file path")?;
+            }
+            "--size" => {
+                config.font_size = parse_value(args.next(), "--size expects a size in pixels")?;
+            }
+            "--out" => out_path = Some(parse_value(args.next(), "--out expects a path, or -")?),
+            "--rgba" => output = Output::Rgba,
+            "--info" => output = Output::Info,
+            "--straight-alpha" => config.straight_alpha = true,
+            "--dpi" => {
+                config.dpi =
+                    Some(parse_value(args.next(), "--dpi expects a resolution, e.g. 300")?);
+            }
+            "--pattern" => {
+                config.pattern = Some(parse_value(args.next(), "--pattern expects an image path")?);
+            }
+            "--pattern-scale" => {
+                config.pattern_scale =
+                    parse_value(args.next(), "--pattern-scale expects a factor, e.g. 0.5")?;
+            }
+            other => {
+                return Err(TextError::InvalidArgument(format!("Unknown argument: {other}")))
+            }
+        }
+    }
+    Ok((config, output, out_path))
+}
+
+/// Parses the value following a flag, or fails with `expected` if it's
+/// missing or malformed.
+fn parse_value<T: FromStr>(value: Option<String>, expected: &str) -> Result<T, TextError> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| TextError::InvalidArgument(expected.to_string()))
+}
+
+/// Parses the hex color following `flag`.
+fn parse_color(value: Option<String>, flag: &str) -> Result<Color, TextError> {
+    value
+        .as_deref()
+        .and_then(parse_hex_color)
+        .ok_or_else(|| TextError::InvalidArgument(format!("{flag} expects a color, e.g. #ff0000")))
+}
@@ -0,0 +1,703 @@
+use common::error::TextError;
+use common::info::{font_info, FontFormat};
+use common::picture::replay_picture;
+use common::raster::{read_rgba, BlendSpace};
+use common::registry::TypefaceRegistry;
+use common::style::TextStyle;
+use example_1::{
+    run, run_rgba, text_origin, Anchor, Config, Halftone, Overflow, Sticker, TextFilter,
+    INVERT_MATRIX, SEPIA_MATRIX,
+};
+use skia_safe::{AlphaType, Color, EncodedImageFormat, Paint, Rect, Surface};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn missing_font_is_a_font_load_error() {
+    let config = Config {
+        font_path: "does-not-exist.ttf".to_string(),
+        ..Config::default()
+    };
+    match run(&config) {
+        Err(TextError::FontLoad { path }) => assert_eq!(path, "does-not-exist.ttf"),
+        other => panic!("expected a FontLoad error, got {other:?}"),
+    }
+}
+
+#[test]
+fn straight_alpha_unpremultiplies_text_edges() {
+    // White text on a transparent background: every pixel that has any
+    // coverage is white, only its alpha varies.
+    let config = Config {
+        color: Color::WHITE,
+        transparent: true,
+        ..Config::default()
+    };
+    let (_, premultiplied) = run_rgba(&config).unwrap();
+    let (_, straight) = run_rgba(&Config {
+        straight_alpha: true,
+        ..config
+    })
+    .unwrap();
+
+    let mut edges = 0;
+    for (premul, unpremul) in premultiplied.chunks(4).zip(straight.chunks(4)) {
+        let alpha = premul[3];
+        assert_eq!(alpha, unpremul[3]);
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        // Premultiplied, white at alpha `a` is stored as (a, a, a, a);
+        // straight, it is (255, 255, 255, a).
+        edges += 1;
+        assert_eq!(&premul[..3], &[alpha; 3]);
+        assert!(unpremul[..3].iter().all(|&channel| channel >= 254));
+    }
+    assert!(edges > 0, "expected some semi-transparent pixels");
+}
+
+#[test]
+fn font_info_reports_the_bundled_font() {
+    let font_data = std::fs::read("Roboto-LightItalic.ttf").unwrap();
+    let info = font_info(&font_data).unwrap();
+    assert_eq!(info.format, FontFormat::TrueType);
+    assert_eq!(info.family.as_deref(), Some("Roboto"));
+    assert_eq!(info.style.as_deref(), Some("Light Italic"));
+    assert_eq!(info.units_per_em, 2048);
+    assert!(info.features.iter().any(|feature| feature == "kern"));
+}
+
+#[test]
+fn sticker_outline_surrounds_the_fill() {
+    // White fill, black outline, nothing behind them.
+    let config = Config {
+        sticker: Some(Sticker {
+            outline: Color::BLACK,
+            ..Sticker::default()
+        }),
+        transparent: true,
+        ..Config::default()
+    };
+    let (size, pixels) = run_rgba(&config).unwrap();
+    let (width, height) = (size.width as usize, size.height as usize);
+    let alpha = |x: usize, y: usize| pixels[(y * width + x) * 4 + 3];
+    let red = |x: usize, y: usize| pixels[(y * width + x) * 4];
+
+    // Wherever the fill shows (a light pixel), every neighbor must be fully
+    // covered: by more fill, or by the outline. A gap would let the
+    // background touch the fill.
+    let mut fill_pixels = 0;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            if red(x, y) < 128 {
+                continue;
+            }
+            fill_pixels += 1;
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                assert_eq!(alpha(nx, ny), 255, "fill at ({x}, {y}) touches the background");
+            }
+        }
+    }
+    assert!(fill_pixels > 0);
+}
+
+#[test]
+fn boxed_text_stays_in_the_box() {
+    // "hello, world" is about 170px wide, so a 120px box has to clip it.
+    let config = Config {
+        text_box: Some((120.0, 40.0)),
+        ..Config::default()
+    };
+    let (size, pixels) = run_rgba(&config).unwrap();
+    assert_eq!((size.width, size.height), (300, 140));
+
+    // The box sits at (50, 50), and the text is black on white.
+    let (mut inside, mut outside) = (0, 0);
+    for (i, pixel) in pixels.chunks(4).enumerate() {
+        if pixel[0] < 255 {
+            let (x, y) = (i as i32 % size.width, i as i32 / size.width);
+            if (50..170).contains(&x) && (50..90).contains(&y) {
+                inside += 1;
+            } else {
+                outside += 1;
+            }
+        }
+    }
+    assert!(inside > 0, "no text drawn");
+    assert_eq!(outside, 0, "text drawn outside the box");
+}
+
+#[test]
+fn bounds_are_outlined_in_blue_and_red() {
+    // How many pixels are mostly blue, and how many mostly red.
+    let blue_and_red = |config: &Config| {
+        let (_, pixels) = run_rgba(config).unwrap();
+        let blue = pixels.chunks(4).filter(|p| p[2] > p[0].saturating_add(100)).count();
+        let red = pixels.chunks(4).filter(|p| p[0] > p[2].saturating_add(100)).count();
+        (blue, red)
+    };
+    // Black text has neither; the logical box is blue, the ink box red.
+    assert_eq!(blue_and_red(&Config::default()), (0, 0));
+    let (blue, red) = blue_and_red(&Config {
+        show_bounds: true,
+        ..Config::default()
+    });
+    assert!(blue > 0 && red > 0, "{blue} blue, {red} red");
+}
+
+#[test]
+fn snapping_puts_the_origin_on_whole_pixels() {
+    // Anchored at its top-left, the baseline sits one (fractional) ascent
+    // below y = 50; centered in a box, the left edge is fractional too.
+    let config = Config {
+        anchor: Anchor::TopLeft,
+        ..Config::default()
+    };
+    let boxed = Config {
+        text_box: Some((201.0, 61.0)),
+        ..Config::default()
+    };
+    for config in [config, boxed] {
+        let unsnapped = text_origin(&config).unwrap();
+        assert!(unsnapped.x.fract() != 0.0 || unsnapped.y.fract() != 0.0);
+
+        let snapped = text_origin(&Config {
+            snap_to_pixel: true,
+            ..config
+        })
+        .unwrap();
+        assert_eq!(snapped.x.fract(), 0.0, "left edge at {}", snapped.x);
+        assert_eq!(snapped.y.fract(), 0.0, "baseline at {}", snapped.y);
+        assert!((snapped - unsnapped).length() <= 0.75);
+    }
+}
+
+#[test]
+fn more_samples_get_closer_to_a_well_sampled_reference() {
+    // Small text, where the glyph rasterizer's own coverage is coarsest.
+    let small = |supersample| Config {
+        font_size: 12.0,
+        supersample,
+        ..Config::default()
+    };
+    let (_, reference) = run_rgba(&small(Some(8))).unwrap();
+    let error = |supersample| {
+        let (_, pixels) = run_rgba(&small(supersample)).unwrap();
+        let total: u32 = pixels
+            .chunks(4)
+            .zip(reference.chunks(4))
+            .map(|(pixel, reference)| pixel[0].abs_diff(reference[0]) as u32)
+            .sum();
+        total as f32 / (pixels.len() / 4) as f32
+    };
+
+    // Each output pixel averages more of the big rendering's samples, so
+    // its edges come closer to the reference's.
+    let (one, two, four) = (error(None), error(Some(2)), error(Some(4)));
+    assert!(one > 0.0, "supersampling changed nothing");
+    assert!(four < two, "error with 4x4 samples {four}, with 2x2 samples {two}");
+    assert!(four < one, "error with 4x4 samples {four}, without supersampling {one}");
+}
+
+#[test]
+fn linear_blending_makes_dark_text_lighter() {
+    // Blended in linear light, an edge pixel half covered by black keeps
+    // half the white's light, which encodes as about 188 rather than sRGB's
+    // 128. The same glyphs leave less ink.
+    let (_, srgb_ink) = ink(&Config::default());
+    let (_, linear_ink) = ink(&Config {
+        blend_space: Some(BlendSpace::Linear),
+        ..Config::default()
+    });
+    assert!(linear_ink < srgb_ink, "{linear_ink} vs {srgb_ink}");
+}
+
+#[test]
+fn dumped_picture_replays_to_the_same_image() {
+    let path = std::env::temp_dir().join("example-1-dump.skp");
+    let config = Config {
+        dump_picture: Some(path.clone()),
+        ..Config::default()
+    };
+    let (size, pixels) = run_rgba(&config).unwrap();
+
+    let skp = std::fs::read(&path).unwrap();
+    assert!(!skp.is_empty());
+    let replayed = replay_picture(&skp, size).unwrap();
+    assert_eq!(read_rgba(&replayed, AlphaType::Premul).unwrap(), pixels);
+}
+
+#[test]
+fn out_dash_writes_only_the_png_to_stdout() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_example-1"))
+        .args(["--out", "-"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // The whole of stdout is the image, so it can be piped to a file.
+    let image = skia_safe::Image::from_encoded(skia_safe::Data::new_copy(&output.stdout));
+    assert!(output.stdout.starts_with(PNG_SIGNATURE));
+    assert!(image.is_some(), "stdout isn't a valid PNG");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Image written to -"));
+}
+
+#[test]
+fn grayscale_png_has_one_channel_and_matches_the_color_one() {
+    let gray_config = Config {
+        grayscale: true,
+        ..Config::default()
+    };
+    let gray_png = run(&gray_config).unwrap();
+    assert!(gray_png.starts_with(PNG_SIGNATURE));
+    // IHDR comes first: bit depth 8, color type 0 (grayscale, no alpha).
+    assert_eq!(&gray_png[12..16], b"IHDR");
+    assert_eq!((gray_png[24], gray_png[25]), (8, 0));
+
+    // Black text on white is all grays, so every pixel keeps its value.
+    let (_, color) = run_rgba(&Config::default()).unwrap();
+    let (_, gray) = run_rgba(&gray_config).unwrap();
+    for (color, gray) in color.chunks(4).zip(gray.chunks(4)) {
+        assert!(color[0].abs_diff(gray[0]) <= 1, "{color:?} became {gray:?}");
+    }
+}
+
+#[test]
+fn alpha_mask_is_one_channel_of_glyph_coverage() {
+    let mask_config = Config {
+        alpha_mask: true,
+        ..Config::default()
+    };
+    let mask_png = run(&mask_config).unwrap();
+    // Bit depth 8, color type 0: one gray channel, no alpha.
+    assert_eq!(&mask_png[12..16], b"IHDR");
+    assert_eq!((mask_png[24], mask_png[25]), (8, 0));
+
+    // Each gray value is how much of the pixel the glyphs cover, which for
+    // text drawn on a transparent background is its alpha.
+    let (size, mask) = run_rgba(&mask_config).unwrap();
+    let (_, coverage) = run_rgba(&Config {
+        transparent: true,
+        ..Config::default()
+    })
+    .unwrap();
+    assert_eq!((size.width, size.height), (300, 100));
+    let (mut solid, mut edges) = (0, 0);
+    for (mask, coverage) in mask.chunks(4).zip(coverage.chunks(4)) {
+        assert!(mask[..3].iter().all(|&gray| gray == mask[0]), "{mask:?} isn't gray");
+        assert!(mask[0].abs_diff(coverage[3]) <= 1, "{} for coverage {}", mask[0], coverage[3]);
+        match mask[0] {
+            255 => solid += 1,
+            0 => {}
+            _ => edges += 1,
+        }
+    }
+    assert!(solid > 0 && edges > 0, "{solid} solid, {edges} edge pixels");
+}
+
+#[test]
+fn dpi_is_stored_in_the_phys_chunk() {
+    let png = run(&Config {
+        dpi: Some(300.0),
+        ..Config::default()
+    })
+    .unwrap();
+
+    // Walk the chunks (length, type, data, CRC) to the pHYs one.
+    let mut offset = PNG_SIGNATURE.len();
+    let phys = loop {
+        let length = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+        let chunk_type = &png[offset + 4..offset + 8];
+        assert_ne!(chunk_type, b"IDAT", "no pHYs chunk before the image data");
+        if chunk_type == b"pHYs" {
+            break &png[offset + 8..offset + 8 + length];
+        }
+        offset += 12 + length;
+    };
+    // 300 dots per inch is 11811 per meter, the same across and down, and
+    // the unit byte 1 says it's meters.
+    assert_eq!(phys.len(), 9);
+    assert_eq!(&phys[0..4], &11811u32.to_be_bytes());
+    assert_eq!(&phys[4..8], &11811u32.to_be_bytes());
+    assert_eq!(phys[8], 1);
+
+    // And the image around it still decodes.
+    let image = skia_safe::Image::from_encoded(skia_safe::Data::new_copy(&png)).unwrap();
+    assert_eq!((image.width(), image.height()), (300, 100));
+}
+
+/// The bounds of the non-white pixels, as (left, top, right, bottom) pixel
+/// edges, and the total amount of ink in them.
+fn ink(config: &Config) -> ((i32, i32, i32, i32), u32) {
+    let (size, pixels) = run_rgba(config).unwrap();
+    let (mut left, mut top, mut right, mut bottom) = (size.width, size.height, 0, 0);
+    let mut total = 0;
+    for (i, pixel) in pixels.chunks(4).enumerate() {
+        if pixel[0] < 255 {
+            let (x, y) = (i as i32 % size.width, i as i32 / size.width);
+            (left, top) = (left.min(x), top.min(y));
+            (right, bottom) = (right.max(x + 1), bottom.max(y + 1));
+            total += u32::from(255 - pixel[0]);
+        }
+    }
+    ((left, top, right, bottom), total)
+}
+
+#[test]
+fn top_left_anchor_puts_the_top_of_the_ink_at_y_50() {
+    // The ring of "Å" reaches the font's ascent (1908 of its 2048 units,
+    // against an ascent of 1900), so anchored at its top-left its ink starts
+    // at y = 50. The lowercase letters stop short of the ascent, a little
+    // further down.
+    let top_left = |text: &str| Config {
+        text: text.to_string(),
+        anchor: Anchor::TopLeft,
+        ..Config::default()
+    };
+    let ((left, top, _, _), _) = ink(&top_left("Åhello"));
+    assert!((49..=50).contains(&top), "ink starts at y = {top}");
+    assert!((48..=55).contains(&left), "ink starts at x = {left}");
+    let ((_, top, _, _), _) = ink(&top_left("hello"));
+    assert!((50..=57).contains(&top), "ink starts at y = {top}");
+
+    // Anchored at the baseline instead, the same ink sits an ascent higher.
+    let ((_, baseline_top, _, _), _) = ink(&Config {
+        anchor: Anchor::Baseline,
+        ..top_left("hello")
+    });
+    assert!((top - baseline_top - 30).abs() <= 1, "{baseline_top} vs {top}");
+}
+
+#[test]
+fn mirror_flips_the_pixels_about_the_text_center() {
+    let (size, plain) = run_rgba(&Config::default()).unwrap();
+    let (_, mirrored) = run_rgba(&Config {
+        mirror: true,
+        ..Config::default()
+    })
+    .unwrap();
+
+    // The flip is about the middle of the text's advance width.
+    let typeface = TypefaceRegistry::new().load("Roboto-LightItalic.ttf", 0).unwrap();
+    let font = TextStyle::new(typeface, 32.0).font();
+    let (text_width, _) = font.measure_str("hello, world", None);
+    let center_x = text_origin(&Config::default()).unwrap().x + text_width / 2.0;
+
+    // How far apart the mirrored image and `expected` are, on average per
+    // pixel, where `expected` gives the plain pixel to compare with.
+    let width = size.width as usize;
+    let distance = |expected: &dyn Fn(usize) -> Option<usize>| {
+        let mut total = 0;
+        for (i, pixel) in mirrored.chunks(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let plain_x = expected(x);
+            let plain = plain_x.map_or(255, |plain_x| plain[(y * width + plain_x) * 4]);
+            total += u32::from(pixel[0].abs_diff(plain));
+        }
+        total as f32 / (mirrored.len() / 4) as f32
+    };
+    let flipped = distance(&|x| {
+        let plain_x = (2.0 * center_x - x as f32 - 1.0).round();
+        (0.0..width as f32).contains(&plain_x).then_some(plain_x as usize)
+    });
+    let unflipped = distance(&Some);
+    // Only anti-aliasing differs, the mirror being sub-pixel off the grid.
+    assert!(flipped * 4.0 < unflipped, "{flipped} flipped vs {unflipped} unflipped");
+
+    // Each row keeps its ink, moved across.
+    let row_ink = |pixels: &[u8], y: usize| -> u32 {
+        pixels[y * width * 4..(y + 1) * width * 4].chunks(4).map(|p| 255 - p[0] as u32).sum()
+    };
+    for y in 0..size.height as usize {
+        let (before, after) = (row_ink(&plain, y), row_ink(&mirrored, y));
+        assert!(before.abs_diff(after) <= before / 20 + 255, "row {y}: {before} vs {after}");
+    }
+}
+
+#[test]
+fn upside_down_text_is_turned_about_its_center() {
+    // In a box at (50, 50), the text is centered on the box's center,
+    // (150, 80), which is what it turns about.
+    let upright = Config {
+        text_box: Some((200.0, 60.0)),
+        ..Config::default()
+    };
+    let ((left, top, right, bottom), upright_ink) = ink(&upright);
+    let (turned, turned_ink) = ink(&Config {
+        upside_down: true,
+        ..upright
+    });
+    let mirrored = (300 - right, 160 - bottom, 300 - left, 160 - top);
+    for (edge, expected) in [
+        (turned.0, mirrored.0),
+        (turned.1, mirrored.1),
+        (turned.2, mirrored.2),
+        (turned.3, mirrored.3),
+    ] {
+        assert!((edge - expected).abs() <= 1, "{turned:?}, expected {mirrored:?}");
+    }
+    // Same glyphs, so about the same amount of ink (anti-aliasing differs).
+    let ratio = turned_ink as f32 / upright_ink as f32;
+    assert!((0.98..1.02).contains(&ratio), "ink ratio {ratio}");
+}
+
+#[test]
+fn upside_down_text_stays_in_the_image() {
+    let config = Config {
+        upside_down: true,
+        ..Config::default()
+    };
+    let (size, _) = run_rgba(&config).unwrap();
+    let ((left, top, right, bottom), total) = ink(&config);
+    assert!(total > 0, "no text drawn");
+    assert!(left > 0 && top > 0 && right < size.width && bottom < size.height);
+}
+
+#[test]
+fn halftone_dots_are_biggest_where_the_glyphs_are_solid() {
+    // Big, bold strokes, so some of the 4x4 dot cells are entirely inside them.
+    let plain = Config {
+        text: "III".to_string(),
+        font_size: 120.0,
+        faux_bold: true,
+        text_box: Some((300.0, 160.0)),
+        ..Config::default()
+    };
+    let halftone = Config {
+        halftone: Some(Halftone {
+            spacing: 4.0,
+            max_radius: 2.0,
+        }),
+        ..plain.clone()
+    };
+    let (size, plain_pixels) = run_rgba(&plain).unwrap();
+    let (_, dot_pixels) = run_rgba(&halftone).unwrap();
+
+    // How dark each dot cell is on average, from 0 (white) to 1 (black).
+    let (width, height) = (size.width as usize, size.height as usize);
+    let darkness = |pixels: &[u8]| {
+        let mut cells = Vec::new();
+        for cell_y in (0..height - 3).step_by(4) {
+            for cell_x in (0..width - 3).step_by(4) {
+                let total: u32 = (cell_y..cell_y + 4)
+                    .flat_map(|y| (cell_x..cell_x + 4).map(move |x| (y * width + x) * 4))
+                    .map(|i| 255 - pixels[i] as u32)
+                    .sum();
+                cells.push(total as f32 / (16.0 * 255.0));
+            }
+        }
+        cells
+    };
+    let cells: Vec<(f32, f32)> =
+        darkness(&plain_pixels).into_iter().zip(darkness(&dot_pixels)).collect();
+
+    // Solid cells get the biggest dots: a circle just touching the cell's
+    // sides covers π/4 of it.
+    let solid: Vec<f32> = cells.iter().filter(|(plain, _)| *plain > 0.98).map(|c| c.1).collect();
+    assert!(!solid.is_empty(), "no solid cells");
+    assert!(solid.iter().all(|dots| (0.65..0.9).contains(dots)), "solid cells: {solid:?}");
+    // Cells with no ink get no dot at all.
+    let empty: Vec<f32> = cells.iter().filter(|(plain, _)| *plain == 0.0).map(|c| c.1).collect();
+    assert!(!empty.is_empty(), "no empty cells");
+    assert!(empty.iter().all(|dots| *dots < 0.05), "ink outside the glyphs");
+}
+
+#[test]
+fn overflow_decides_which_part_of_long_text_is_shown() {
+    // Far wider than the 200px between the margins of the 300px image.
+    let long = |overflow| Config {
+        text: "The beginning of this is cut off, but not its end".to_string(),
+        overflow,
+        ..Config::default()
+    };
+
+    // Clipped, the text starts at the margin and runs off the right edge.
+    let (size, _) = run_rgba(&long(Overflow::Clip)).unwrap();
+    assert_eq!((size.width, size.height), (300, 100));
+    let ((left, _, right, _), _) = ink(&long(Overflow::Clip));
+    assert!((48..=55).contains(&left) && right == 300, "{left}..{right}");
+
+    // Scrolled to the end, the text ends at the right margin instead, and
+    // it's the beginning that runs off the left edge.
+    let scrolled = long(Overflow::ScrollEnd);
+    let (size, _) = run_rgba(&scrolled).unwrap();
+    assert_eq!((size.width, size.height), (300, 100));
+    let ((left, _, right, _), _) = ink(&scrolled);
+    assert!(left == 0 && (240..=256).contains(&right), "{left}..{right}");
+    assert!(text_origin(&scrolled).unwrap().x < 0.0);
+
+    // Visible, the image grows to fit it all, with a margin on both sides.
+    let (size, _) = run_rgba(&long(Overflow::Visible)).unwrap();
+    let ((left, _, right, _), _) = ink(&long(Overflow::Visible));
+    assert!(size.width > 300 && (48..=55).contains(&left), "{left}");
+    assert!(size.width - right >= 45, "{right} of {}", size.width);
+
+    // Text that fits is left where it is.
+    assert_eq!(
+        text_origin(&Config {
+            overflow: Overflow::ScrollEnd,
+            ..Config::default()
+        })
+        .unwrap(),
+        text_origin(&Config::default()).unwrap(),
+    );
+}
+
+#[test]
+fn faux_italic_slants_the_ink_right_and_keeps_it_in_the_image() {
+    let italic = Config {
+        faux_italic: true,
+        ..Config::default()
+    };
+    // The middle of the ink, across, in rows `ys`.
+    let ink_center = |config: &Config, ys: std::ops::Range<usize>| {
+        let (size, pixels) = run_rgba(config).unwrap();
+        let width = size.width as usize;
+        let (mut weighted, mut total) = (0.0, 0.0);
+        for y in ys {
+            for x in 0..width {
+                let ink = f32::from(255 - pixels[(y * width + x) * 4]);
+                (weighted, total) = (weighted + ink * x as f32, total + ink);
+            }
+        }
+        weighted / total
+    };
+    // The baseline is at y = 50 and stays put, while the tops of "h", "l"
+    // and "d", some 20px above it, move about 4px right.
+    let top_shift = ink_center(&italic, 26..34) - ink_center(&Config::default(), 26..34);
+    let bottom_shift = ink_center(&italic, 46..50) - ink_center(&Config::default(), 46..50);
+    assert!(top_shift > 2.5, "tops moved {top_shift}px");
+    assert!(bottom_shift.abs() < 1.5, "bottoms moved {bottom_shift}px");
+
+    // Text that grows the image doesn't lose its slanted top to the right
+    // edge: the image grows by the overhang too, keeping the margin.
+    let long = Config {
+        text: "Long enough to run past the right edge".to_string(),
+        overflow: Overflow::Visible,
+        ..italic
+    };
+    let (size, _) = run_rgba(&long).unwrap();
+    let ((left, _, right, _), _) = ink(&long);
+    let (upright_size, _) = run_rgba(&Config {
+        faux_italic: false,
+        ..long.clone()
+    })
+    .unwrap();
+    assert!(size.width > upright_size.width, "{} vs {}", size.width, upright_size.width);
+    assert!(size.width - right >= 45, "{right} of {}", size.width);
+    assert!((48..=55).contains(&left), "{left}");
+}
+
+#[test]
+fn drop_shadow_filter_draws_the_shadow_in_the_same_pass() {
+    // A red shadow, so it can't be mistaken for the black text's edges.
+    let shadowed = Config {
+        filter: Some(TextFilter::DropShadow {
+            offset: (4.0, 6.0),
+            blur: 1.0,
+            color: Color::RED,
+        }),
+        ..Config::default()
+    };
+    let count = |config: &Config, matches: fn(&[u8]) -> bool| {
+        let (_, pixels) = run_rgba(config).unwrap();
+        pixels.chunks(4).filter(|pixel| matches(pixel)).count()
+    };
+    let red = |pixel: &[u8]| pixel[0] > 200 && pixel[1] < 100 && pixel[2] < 100;
+    let black = |pixel: &[u8]| pixel[0] < 60 && pixel[1] < 60 && pixel[2] < 60;
+
+    assert_eq!(count(&Config::default(), red), 0);
+    assert!(count(&shadowed, red) > 100, "no shadow");
+    // The text is still drawn on top, black.
+    assert!(count(&shadowed, black) > 100, "no text");
+
+    for filter in [TextFilter::Blur(2.0), TextFilter::Emboss] {
+        let config = Config {
+            filter: Some(filter),
+            ..Config::default()
+        };
+        assert!(run(&config).unwrap().starts_with(PNG_SIGNATURE));
+    }
+}
+
+#[test]
+fn pattern_fills_the_glyphs_with_the_image() {
+    // An 8x8 tile: a red stripe on the left, a blue one on the right.
+    let mut surface = Surface::new_raster_n32_premul((8, 8)).unwrap();
+    let canvas = surface.canvas();
+    canvas.clear(Color::BLUE);
+    let mut red = Paint::default();
+    red.set_color(Color::RED);
+    canvas.draw_rect(Rect::from_xywh(0.0, 0.0, 4.0, 8.0), &red);
+    let tile = surface.image_snapshot().encode_to_data(EncodedImageFormat::PNG).unwrap();
+    let path = std::env::temp_dir().join("example-1-stripes.png");
+    std::fs::write(&path, tile.as_bytes()).unwrap();
+
+    // Big and bold, for plenty of pixels well inside the glyphs.
+    let config = Config {
+        font_size: 48.0,
+        faux_bold: true,
+        ..Config::default()
+    };
+    let (size, plain) = run_rgba(&config).unwrap();
+    let (_, patterned) = run_rgba(&Config {
+        pattern: Some(path.to_str().unwrap().to_string()),
+        ..config
+    })
+    .unwrap();
+
+    // Where the black text covers a pixel completely, the pattern shows
+    // through instead: the tile repeats from the image's top-left, so the
+    // stripe depends on the column alone. The background stays white.
+    let mut covered = 0;
+    for (i, (plain, patterned)) in plain.chunks(4).zip(patterned.chunks(4)).enumerate() {
+        match plain[0] {
+            0 => {
+                covered += 1;
+                let x = i % size.width as usize;
+                let stripe = if x % 8 < 4 { [255, 0, 0, 255] } else { [0, 0, 255, 255] };
+                assert_eq!(patterned, stripe, "pixel {i}");
+            }
+            255 => assert_eq!(patterned, [255; 4], "pixel {i}"),
+            _ => {}
+        }
+    }
+    assert!(covered > 50, "only {covered} covered pixels");
+}
+
+#[test]
+fn color_matrix_recolors_the_glyphs() {
+    // On a transparent background with straight alpha, the glyphs' pixels
+    // carry the text's color alone; the fully covered ones are compared.
+    let glyph_colors = |color_matrix: Option<[f32; 20]>| {
+        let config = Config {
+            color_matrix,
+            transparent: true,
+            straight_alpha: true,
+            ..Config::default()
+        };
+        let (_, pixels) = run_rgba(&config).unwrap();
+        let solid: Vec<[u8; 3]> = pixels
+            .chunks(4)
+            .filter(|pixel| pixel[3] == 255)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        assert!(solid.len() > 20, "no solid glyph pixels");
+        solid
+    };
+
+    assert!(glyph_colors(None).iter().all(|&color| color == [0, 0, 0]));
+    // Black text comes out dark brown: red over green over blue.
+    for [r, g, b] in glyph_colors(Some(SEPIA_MATRIX)) {
+        assert!(r > g && g > b && r > 80 && r < 140, "{:?}", [r, g, b]);
+    }
+    // Black text comes out white, ready for a dark background.
+    assert!(glyph_colors(Some(INVERT_MATRIX)).iter().all(|&color| color == [255, 255, 255]));
+}
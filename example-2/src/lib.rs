@@ -0,0 +1,213 @@
+use common::fallback::{fallback_runs, missing_chars};
+use common::markup::parse_markup;
+use common::metrics::line_metrics;
+use common::picture::record_picture;
+use common::style::TextStyle;
+use skia_safe::{
+    Canvas, Color, Data, EncodedImageFormat, Font, FontMgr, Paint, Surface
+};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Draws the mixed-style, mixed-font line and returns it as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // ---------------------------
+    // 1. Load the fonts
+    // ---------------------------
+
+    // Primary font (e.g., Roboto for Latin text).
+    let primary_font_path = "Roboto-LightItalic.ttf";
+    let primary_font_data = Data::new_copy(&fs::read(primary_font_path)?);
+    let font_mgr = FontMgr::new();
+    let primary_typeface = font_mgr
+        .new_from_data(&primary_font_data, None)
+        .ok_or("Failed to load the primary font")?;
+
+    // Fallback font (e.g., an emoji-capable font such as NotoColorEmoji).
+    let fallback_font_path = "NotoColorEmoji-Regular.ttf";
+    let fallback_font_data = Data::new_copy(&fs::read(fallback_font_path)?);
+    let fallback_typeface = font_mgr
+        .new_from_data(&fallback_font_data, None)
+        .ok_or("Failed to load the fallback font")?;
+
+    // The text styles we'll use: 20px body text, and a bigger 40px heading.
+    let body = TextStyle::new(primary_typeface, 20.0);
+    let heading = TextStyle {
+        size: 40.0,
+        ..body.clone()
+    };
+
+    // ---------------------------
+    // 2. Prepare the text with fallback
+    // ---------------------------
+
+    // Our mixed text, as spans of (text, style). Mixing sizes on one line
+    // is what you get from e.g. a heading run or a superscript.
+    // The first "e" carries a combining acute accent (U+0301), which has to
+    // stay attached to its base letter.
+    //
+    // With --markup, the spans come from markup instead, like
+    // "[b]Hi[/b] [color=#f00]there[/color]", with the body style as the base.
+    let mut spans: Vec<(String, TextStyle)> = match &config.markup {
+        Some(markup) => parse_markup(markup, &body),
+        None => vec![
+            ("he\u{301}llo, ".to_string(), body.clone()),
+            ("world 🌎".to_string(), heading.clone()),
+        ],
+    };
+
+    // With --opacity, fade spans independently (e.g. one fading out while
+    // the next fades in, for a crossfade). The runs split off a span,
+    // fallback emoji included, inherit its opacity.
+    for ((_, style), &opacity) in spans.iter_mut().zip(&config.opacities) {
+        style.opacity = opacity;
+    }
+
+    // Warn about characters that neither font can draw: they'll come out as
+    // the fallback font's "missing glyph" box.
+    let text: String = spans.iter().map(|(text, _)| text.as_str()).collect();
+    let missing_from_primary: String = missing_chars(&body.font(), &text).into_iter().collect();
+    let fallback_font = Font::from_typeface(fallback_typeface.clone(), body.size);
+    let uncovered = missing_chars(&fallback_font, &missing_from_primary);
+    if !uncovered.is_empty() {
+        let list: Vec<String> = uncovered.iter().map(|c| format!("{c:?}")).collect();
+        eprintln!("Warning: no font can draw {}", list.join(", "));
+    }
+
+    // We split each span into runs: each run is a String along with the
+    // style to draw it with. Characters the primary font can't render get the
+    // span's style, but with the fallback typeface swapped in.
+    //
+    // `fallback_runs` works on whole grapheme clusters rather than chars: a
+    // base letter and its combining marks (or a multi-codepoint emoji) must
+    // end up in the same run, so that `draw_str` gets them together and the
+    // font can place the mark over its base instead of after it.
+    //
+    // With --emoji-scale, fallback (emoji) runs are drawn that much bigger
+    // than the text around them, and moved down so their middle lines up with
+    // the middle of the text's capitals instead of sitting on the baseline.
+    // Each run carries that vertical offset along.
+    let runs: Vec<(String, TextStyle, f32)> = spans
+        .iter()
+        .flat_map(|(text, style)| {
+            let fallback_typeface = &fallback_typeface;
+            fallback_runs(text, style, fallback_typeface)
+                .into_iter()
+                .map(move |(run, run_style)| {
+                    let is_fallback =
+                        run_style.typeface.unique_id() == fallback_typeface.unique_id();
+                    match config.emoji_scale {
+                        Some(scale) if is_fallback => {
+                            let scaled = TextStyle {
+                                size: run_style.size * scale,
+                                ..run_style
+                            };
+                            let offset = centering_offset(style, &scaled);
+                            (run, scaled, offset)
+                        }
+                        _ => (run, run_style, 0.0),
+                    }
+                })
+        })
+        .collect();
+
+    // ---------------------------
+    // 3. Set up drawing surface
+    // ---------------------------
+
+    // Each run gets a Font and Paint from its style. Since the run's font is
+    // the scaled one, its measured width (and so the next run's x) is too.
+    let styled_runs: Vec<(String, Font, Paint, f32)> = runs
+        .into_iter()
+        .map(|(run, style, offset)| (run, style.font(), style.paint(), offset))
+        .collect();
+
+    // All runs share a single baseline, no matter their size. Drawing each run
+    // at its own "top" would make the small text float up next to the large
+    // text. Instead the line box covers every run's font (moved by its
+    // offset), so the tallest run, usually an emoji, fits without clipping.
+    let line = line_metrics(styled_runs.iter().map(|(_, font, _, offset)| {
+        let (_, mut metrics) = font.metrics();
+        metrics.ascent += offset;
+        metrics.descent += offset;
+        metrics
+    }));
+
+    // The image is at least 100px tall, and taller if the line needs it.
+    let top = 10.0;
+    let width = 500;
+    let height = ((2.0 * top + line.height()).ceil() as i32).max(100);
+
+    // ---------------------------
+    // 4. Draw the text runs
+    // ---------------------------
+
+    // Starting coordinates: the baseline goes low enough for the line's
+    // ascent to fit below the top margin (ascent is negative, i.e. above the
+    // baseline).
+    let y = top - line.ascent;
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+
+        // For each run, draw it on the shared baseline,
+        // then update x for the next run based on measured width.
+        let mut x = 50.0;
+        for (run, font, paint, offset) in &styled_runs {
+            // Draw the text run.
+            canvas.draw_str(run, (x, y + offset), font, paint);
+
+            // Measure the width of the run to update the x coordinate.
+            let (run_width, _) = font.measure_str(run, Some(paint));
+            x += run_width;
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((width, height))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    // ---------------------------
+    // 5. Save the result
+    // ---------------------------
+
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// How far down to move a run drawn in `emoji` so that it is vertically
+/// centered on text drawn in `text`.
+///
+/// Emoji glyphs fill roughly the font's whole ascent-to-descent box, while
+/// the visual middle of a line of text is about half its cap height above
+/// the baseline. Lining those two up keeps a bigger emoji from towering over
+/// the text or dropping below it.
+fn centering_offset(text: &TextStyle, emoji: &TextStyle) -> f32 {
+    let (_, text_metrics) = text.font().metrics();
+    let (_, emoji_metrics) = emoji.font().metrics();
+    let text_center = -text_metrics.cap_height / 2.0;
+    let emoji_center = (emoji_metrics.ascent + emoji_metrics.descent) / 2.0;
+    text_center - emoji_center
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Size of fallback (emoji) runs relative to their span's size.
+    pub emoji_scale: Option<f32>,
+    /// Markup to draw instead of the built-in text, see `parse_markup`.
+    pub markup: Option<String>,
+    /// Opacity of each span in turn, from 0 to 1. Spans past the end of the
+    /// list are drawn fully opaque.
+    pub opacities: Vec<f32>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
@@ -0,0 +1,82 @@
+use common::fallback::fallback_runs;
+use common::raster::read_rgba;
+use common::style::TextStyle;
+use example_2::{run, Config};
+use skia_safe::{AlphaType, Data, FontMgr, Image, Typeface};
+use std::fs;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn markup_colors_its_span() {
+    // How many pixels of the image are mostly red.
+    let red_pixels = |markup: &str| {
+        let png = run(&Config {
+            markup: Some(markup.to_string()),
+            ..Config::default()
+        })
+        .unwrap();
+        let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+        let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+        pixels.chunks(4).filter(|p| p[0] > p[1].saturating_add(100)).count()
+    };
+    assert_eq!(red_pixels("[b]Hi[/b] there"), 0);
+    assert!(red_pixels("[b]Hi[/b] [color=#f00]there[/color]") > 0);
+}
+
+#[test]
+fn renders_a_png_with_faded_spans() {
+    let config = Config {
+        opacities: vec![1.0, 0.5],
+        ..Config::default()
+    };
+    let png = run(&config).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+    assert_ne!(png, run(&Config::default()).unwrap());
+}
+
+#[test]
+fn image_grows_to_fit_big_emoji() {
+    let config = Config {
+        emoji_scale: Some(4.0),
+        ..Config::default()
+    };
+    let png = run(&config).unwrap();
+    // The height is the second big-endian u32 in IHDR.
+    assert_eq!(&png[12..16], b"IHDR");
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    assert!(height > 100, "height {height}");
+}
+
+fn typeface(path: &str) -> Typeface {
+    let data = Data::new_copy(&fs::read(path).unwrap());
+    FontMgr::new().new_from_data(&data, None).unwrap()
+}
+
+#[test]
+fn clusters_are_never_split_between_fonts() {
+    let style = TextStyle::new(typeface("Roboto-LightItalic.ttf"), 20.0);
+    let emoji = typeface("NotoColorEmoji-Regular.ttf");
+    // Woman + ZWJ + laptop is one grapheme, which the emoji font draws as a
+    // single glyph only if it gets all three characters in one run. The
+    // accent stays in the same run as its "e" the same way.
+    let text = "Hi 👩\u{200d}💻 e\u{301}!";
+    let runs: Vec<(String, bool)> = fallback_runs(text, &style, &emoji)
+        .into_iter()
+        .map(|(run, run_style)| (run, run_style.typeface.unique_id() == emoji.unique_id()))
+        .collect();
+    assert_eq!(
+        runs,
+        [
+            ("Hi ".to_string(), false),
+            ("👩\u{200d}💻".to_string(), true),
+            (" e\u{301}!".to_string(), false),
+        ],
+    );
+}
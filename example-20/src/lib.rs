@@ -0,0 +1,116 @@
+use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder};
+use rustybuzz::{shape, Direction, Face, UnicodeBuffer};
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Transform};
+use std::error::Error;
+use std::fs;
+
+/// Shapes the Devanagari conjunct, fills its outline with tiny-skia and
+/// returns the image as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // Load the font data. Unlike example-6 we don't need FreeType at all:
+    // rustybuzz re-exports ttf-parser, which can hand us the glyph outlines.
+    let font_path = "NotoSans-VariableFont.ttf";
+    let font_data = fs::read(font_path)?;
+    let face = Face::from_slice(&font_data, 0).ok_or("Failed to parse the font")?;
+
+    // Outlines and shaping positions are both in font units; scale them to pixels.
+    let desired_font_size = config.font_size;
+    let upem = face.units_per_em() as f32;
+    let scale = desired_font_size / upem;
+
+    // Build the UnicodeBuffer and shape it (same text as example-6).
+    let text = "ड्ड";
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_direction(Direction::LeftToRight);
+
+    let glyph_buffer = shape(&face, &[], buffer);
+    let glyph_infos = glyph_buffer.glyph_infos();
+    let glyph_positions = glyph_buffer.glyph_positions();
+
+    // Create a drawing surface. tiny-skia calls this a Pixmap.
+    let width = 500;
+    let height = 200;
+    let mut pixmap = Pixmap::new(width, height).ok_or("Could not create pixmap")?;
+    pixmap.fill(Color::WHITE);
+
+    let mut paint = Paint::default();
+    paint.set_color(Color::BLACK);
+    paint.anti_alias = true;
+
+    let origin_x = 50.0;
+    let origin_y = 100.0;
+    let mut x_accum = 0.0;
+
+    for (info, pos) in glyph_infos.iter().zip(glyph_positions.iter()) {
+        let x_offset = pos.x_offset as f32 * scale;
+        let y_offset = pos.y_offset as f32 * scale;
+        let x_advance = pos.x_advance as f32 * scale;
+
+        // The shaper's y axis points up and the pixmap's down, so a mark
+        // that GPOS raises has to move up here.
+        let glyph_origin_x = origin_x + x_accum + x_offset;
+        let glyph_origin_y = origin_y - y_offset;
+
+        // Collect the outline in font units, then let the transform do the
+        // scaling, the y-flip (fonts are y-up, pixmaps are y-down) and the
+        // translation to the glyph origin.
+        let mut builder = PathOutline(PathBuilder::new());
+        let glyph_id = GlyphId(info.glyph_id as u16);
+        if face.outline_glyph(glyph_id, &mut builder).is_some() {
+            if let Some(path) = builder.0.finish() {
+                let transform = Transform::from_row(
+                    scale,
+                    0.0,
+                    0.0,
+                    -scale,
+                    glyph_origin_x,
+                    glyph_origin_y,
+                );
+                pixmap.fill_path(&path, &paint, FillRule::Winding, transform, None);
+            }
+        }
+
+        x_accum += x_advance;
+    }
+
+    Ok(pixmap.encode_png()?)
+}
+
+/// Adapts tiny-skia's `PathBuilder` to ttf-parser's `OutlineBuilder`.
+struct PathOutline(PathBuilder);
+
+impl OutlineBuilder for PathOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.0.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Font size in pixels.
+    pub font_size: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { font_size: 40.0 }
+    }
+}
@@ -0,0 +1,38 @@
+use example_20::{run, Config};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let out_path = out_path_from_args()?;
+    let png_bytes = run(&Config::default())?;
+    let path = out_path.as_deref().unwrap_or("output_ltr.png");
+
+    // Like `common::output::write_output`: `-` is stdout, for piping. This
+    // example doesn't depend on `common`, which would pull in Skia.
+    if path == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&png_bytes)?;
+        stdout.flush()?;
+    } else {
+        fs::write(path, png_bytes)?;
+    }
+
+    eprintln!("Image saved as {path}");
+    Ok(())
+}
+
+/// Reads the output path (`--out`) from the command line, e.g.
+/// `cargo run -- --out - | display`.
+fn out_path_from_args() -> Result<Option<String>, Box<dyn Error>> {
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok(out_path)
+}
@@ -0,0 +1,36 @@
+use example_20::{run, Config};
+use tiny_skia::Pixmap;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+/// The box around the dark pixels of a PNG, as (left, top, right, bottom)
+/// pixel edges.
+fn ink_bounds(png: &[u8]) -> (u32, u32, u32, u32) {
+    let pixmap = Pixmap::decode_png(png).unwrap();
+    let inked = |x: u32, y: u32| pixmap.pixel(x, y).unwrap().red() < 128;
+    let columns: Vec<u32> =
+        (0..pixmap.width()).filter(|&x| (0..pixmap.height()).any(|y| inked(x, y))).collect();
+    let rows: Vec<u32> =
+        (0..pixmap.height()).filter(|&y| (0..pixmap.width()).any(|x| inked(x, y))).collect();
+    (columns[0], rows[0], columns[columns.len() - 1] + 1, rows[rows.len() - 1] + 1)
+}
+
+#[test]
+fn matches_example_6() {
+    // example-6 draws the same conjunct with the same font, size and origin,
+    // with Skia filling FreeType's outlines. The two rasterizers anti-alias
+    // edges a little differently, so compare where the ink is rather than
+    // the exact pixels.
+    let ours = ink_bounds(&run(&Config::default()).unwrap());
+    let skia = ink_bounds(&std::fs::read("../example-6/output_ltr.png").unwrap());
+    let (ours, skia) = ([ours.0, ours.1, ours.2, ours.3], [skia.0, skia.1, skia.2, skia.3]);
+    for (a, b) in ours.iter().zip(&skia) {
+        assert!(a.abs_diff(*b) <= 1, "tiny-skia {ours:?}, Skia {skia:?}");
+    }
+}
@@ -0,0 +1,91 @@
+use skia_safe::{
+    Color, Data, EncodedImageFormat, Font, FontMgr, Paint, Picture, PictureRecorder, Rect, Surface,
+};
+use std::error::Error;
+use std::fs;
+
+/// The size of the recorded picture, before any replay scaling.
+const PICTURE_SIZE: (i32, i32) = (300, 100);
+
+/// Records the text into a picture, round-trips it through the .skp format
+/// and replays it at `config.scale`, returning the result as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    replay_picture(&record_picture()?, config.scale)
+}
+
+/// Records "hello, world" into a Picture and returns it serialized (.skp).
+pub fn record_picture() -> Result<Vec<u8>, Box<dyn Error>> {
+    // ---------------------------
+    // 1. Load the font
+    // ---------------------------
+
+    let font_path = "Roboto-LightItalic.ttf";
+    let data = Data::new_copy(&fs::read(font_path)?);
+    let typeface = FontMgr::new()
+        .new_from_data(&data, None)
+        .ok_or("Failed to load the font from file")?;
+    let mut font = Font::from_typeface(typeface, 32.0);
+    font.set_edging(skia_safe::font::Edging::SubpixelAntiAlias);
+
+    let mut paint = Paint::default();
+    paint.set_color(Color::BLACK);
+    paint.set_anti_alias(true);
+
+    // ---------------------------
+    // 2. Record the drawing into a Picture
+    // ---------------------------
+
+    // A PictureRecorder hands out a canvas that doesn't rasterize anything;
+    // it just records the draw calls. The result is a resolution-independent
+    // list of commands that can be replayed as often as we like.
+    let (width, height) = PICTURE_SIZE;
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(width as f32, height as f32), None);
+    canvas.clear(Color::WHITE);
+    canvas.draw_str("hello, world", (50, 50), &font, &paint);
+    let picture = recorder
+        .finish_recording_as_picture(None)
+        .ok_or("Failed to record the picture")?;
+
+    // ---------------------------
+    // 3. Serialize it (the .skp format)
+    // ---------------------------
+
+    // The serialized picture embeds the typeface, so it can be replayed
+    // without the original font file (and inspected with Skia's debugger).
+    Ok(picture.serialize().as_bytes().to_vec())
+}
+
+/// Reads a serialized picture back and replays it at `scale`, returning the
+/// result as PNG bytes.
+pub fn replay_picture(skp: &[u8], scale: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    let replayed = Picture::from_bytes(skp).ok_or("Failed to deserialize the picture")?;
+
+    // Because the picture stores draw commands rather than pixels, a 2x
+    // replay re-rasterizes the glyphs at the larger size: it stays sharp.
+    let (width, height) = PICTURE_SIZE;
+    let mut surface = Surface::new_raster_n32_premul((width * scale, height * scale))
+        .ok_or("Could not create a surface")?;
+    let canvas = surface.canvas();
+    canvas.scale((scale as f32, scale as f32));
+    canvas.draw_picture(&replayed, None, None);
+
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// How much bigger than recorded to replay the picture.
+    pub scale: i32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { scale: 1 }
+    }
+}
@@ -0,0 +1,19 @@
+use example_21::{record_picture, replay_picture};
+use std::error::Error;
+use std::fs;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Record the drawing and save it to disk as an .skp file.
+    fs::write("output.skp", record_picture()?)?;
+    println!("Picture written to output.skp");
+
+    // Read it back and replay it at two scales.
+    let skp = fs::read("output.skp")?;
+    for scale in [1, 2] {
+        let output_path = format!("output_{scale}x.png");
+        fs::write(&output_path, replay_picture(&skp, scale)?)?;
+        println!("Image written to {output_path}");
+    }
+
+    Ok(())
+}
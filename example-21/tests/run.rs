@@ -0,0 +1,20 @@
+use example_21::{run, Config};
+use skia_safe::{Data, Image};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn replays_at_twice_the_size() {
+    let size = |scale| {
+        let png = run(&Config { scale }).unwrap();
+        Image::from_encoded(Data::new_copy(&png)).unwrap().dimensions()
+    };
+    let (once, twice) = (size(1), size(2));
+    assert_eq!((twice.width, twice.height), (2 * once.width, 2 * once.height));
+}
@@ -0,0 +1,229 @@
+use common::paragraph::{FontFiles, Paragraph};
+use common::picture::record_picture;
+use common::script_fonts::ScriptFontMap;
+use common::style::TextStyle;
+use common::wrap::fit_to_box;
+use skia_safe::{Canvas, Color, Data, EncodedImageFormat, FontMgr, Surface, Typeface};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Lays out the paragraphs and returns them as one PNG image.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(render(config)?.0)
+}
+
+/// Like `run`, but returns where each paragraph went and what it was drawn
+/// in, in document order (and no image).
+pub fn paragraph_boxes(config: &Config) -> Result<Vec<ParagraphBox>, Box<dyn Error>> {
+    Ok(render(config)?.1)
+}
+
+/// Where a paragraph of the document ended up, and in what style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParagraphBox {
+    /// Distance from the top of the image to the top of the first line.
+    pub top: f32,
+    /// The height of all its lines.
+    pub height: f32,
+    pub line_count: usize,
+    /// The font size it was drawn at, which --fit can make smaller than
+    /// its style's.
+    pub font_size: f32,
+    pub color: Color,
+}
+
+/// Draws the paragraphs, and notes where each one went.
+fn render(config: &Config) -> Result<(Vec<u8>, Vec<ParagraphBox>), Box<dyn Error>> {
+    // ---------------------------
+    // 1. Load the fonts
+    // ---------------------------
+
+    let font_mgr = FontMgr::new();
+    let primary_data = Data::new_copy(&fs::read("Roboto-LightItalic.ttf")?);
+    let primary_typeface = font_mgr
+        .new_from_data(&primary_data, None)
+        .ok_or("Failed to load the primary font")?;
+    let fallback_data = Data::new_copy(&fs::read("NotoColorEmoji-Regular.ttf")?);
+    let fallback_typeface = font_mgr
+        .new_from_data(&fallback_data, None)
+        .ok_or("Failed to load the fallback font")?;
+
+    // Which typeface draws which script. The document is all Latin, so
+    // Roboto draws everything; one with Arabic in it would also map
+    // `Script::Arabic` to an Arabic font here. The emoji are drawn with the
+    // fallback font, whatever script they're next to.
+    let fonts = ScriptFontMap::new(primary_typeface.clone());
+    // HarfBuzz shapes from the font files themselves; read them once for
+    // all the measuring --fit does.
+    let files = FontFiles::new(&fonts, &fallback_typeface);
+
+    // ---------------------------
+    // 2. Describe the document
+    // ---------------------------
+
+    // Each paragraph is some text and the style to draw all of it in. The
+    // text is all left-to-right: `Paragraph` lines always start at the left
+    // edge.
+    let body = TextStyle::new(primary_typeface, 18.0);
+    let heading = TextStyle {
+        size: 32.0,
+        ..body.clone()
+    };
+    // The colophon is in small caps, which Roboto draws with its own
+    // glyphs when the shaper asks for them.
+    let aside = TextStyle {
+        size: 14.0,
+        color: Color::from_rgb(0x60, 0x60, 0x60),
+        features: vec![(*b"smcp", 1)],
+        ..body.clone()
+    };
+    let paragraphs = [
+        ("Composing a document 📄", &heading),
+        (
+            "Every paragraph is wrapped to the same width, but keeps its own \
+             style. Words that don't fit move to the next line, and emoji \
+             like 🎉 are drawn with the fallback font.",
+            &body,
+        ),
+        (
+            "Paragraphs are stacked top to bottom with a fixed gap between \
+             them, so the final image is exactly as tall as the text needs.",
+            &body,
+        ),
+        ("Set in Roboto Light Italic, with Noto Color Emoji. ✏️", &aside),
+    ];
+
+    // ---------------------------
+    // 3. Lay out the paragraphs
+    // ---------------------------
+
+    // Each paragraph becomes a `Paragraph`, which wraps and shapes it once
+    // and keeps the result. An app that redraws the document every frame
+    // would keep these around and only draw them again.
+    let margin = 20.0;
+    let mut laid_out = Vec::new();
+    let mut boxes = Vec::new();
+    let mut height = margin;
+    for (index, (text, style)) in paragraphs.into_iter().enumerate() {
+        if index > 0 {
+            height += config.spacing;
+        }
+
+        // With --fit, every paragraph has to fit in a box of --width by --fit
+        // pixels, and gets a smaller font size until it does.
+        let style = match config.fit_height {
+            Some(box_height) => {
+                let sized = |size| TextStyle {
+                    size,
+                    ..style.clone()
+                };
+                let fitted = fit_to_box(
+                    text,
+                    config.width,
+                    box_height,
+                    style.size,
+                    MIN_FIT_SIZE,
+                    |s, size| measure(s, &sized(size), &fonts, &fallback_typeface, &files),
+                    |size| sized(size).font().metrics().0,
+                );
+                if !fitted.fits {
+                    eprintln!("Paragraph {index} doesn't fit even at {MIN_FIT_SIZE}px");
+                }
+                sized(fitted.font_size)
+            }
+            None => style.clone(),
+        };
+
+        let paragraph =
+            Paragraph::new(text, &style, &fonts, &fallback_typeface, config.width);
+        boxes.push(ParagraphBox {
+            top: height,
+            height: paragraph.height(),
+            line_count: paragraph.line_count(),
+            font_size: style.size,
+            color: style.color,
+        });
+        laid_out.push((paragraph, height));
+        height += laid_out.last().map_or(0.0, |(paragraph, _)| paragraph.height());
+    }
+    height += margin;
+
+    // ---------------------------
+    // 4. Draw everything into one tall image
+    // ---------------------------
+
+    let width = (config.width + 2.0 * margin).ceil() as i32;
+    let height = height.ceil() as i32;
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+        for (paragraph, top) in &laid_out {
+            paragraph.draw(canvas, (margin, *top));
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((width, height))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    // ---------------------------
+    // 5. Save the result
+    // ---------------------------
+
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    let line_count: usize = laid_out.iter().map(|(paragraph, _)| paragraph.line_count()).sum();
+    eprintln!("Laid out {line_count} lines");
+
+    Ok((png_data.as_bytes().to_vec(), boxes))
+}
+
+/// The smallest font size --fit shrinks a paragraph to.
+const MIN_FIT_SIZE: f32 = 8.0;
+
+/// Measures `text` the way a `Paragraph` will draw it, i.e. shaped in the
+/// script's typeface, with the fallback font for emoji that typeface can't
+/// render.
+fn measure(
+    text: &str,
+    style: &TextStyle,
+    fonts: &ScriptFontMap,
+    fallback: &Typeface,
+    files: &FontFiles,
+) -> f32 {
+    Paragraph::runs(text, style, fonts, fallback)
+        .iter()
+        .map(|(run, run_style)| files.shape(run, run_style).width)
+        .sum()
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Width every paragraph is wrapped to, in pixels.
+    pub width: f32,
+    /// Extra space between paragraphs, in pixels.
+    pub spacing: f32,
+    /// Height of the box each paragraph is shrunk to fit, if any.
+    pub fit_height: Option<f32>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 400.0,
+            spacing: 16.0,
+            fit_height: None,
+            dump_picture: None,
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use common::output::write_output;
+use example_22::{run, Config};
+use std::env;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (config, out_path) = config_from_args()?;
+    let png_bytes = run(&config)?;
+    let path = out_path.as_deref().unwrap_or("output_paragraphs.png");
+    write_output(path, &png_bytes)?;
+    eprintln!("Image written to {path}");
+
+    Ok(())
+}
+
+/// Reads the `Config` and the output path (`--out`) from the command line, e.g.
+/// `cargo run -- --width 300 --spacing 24 --fit 40`.
+fn config_from_args() -> Result<(Config, Option<String>), Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => {
+                let value = args.next().ok_or("--width expects a width in pixels")?;
+                config.width = value.parse()?;
+            }
+            "--spacing" => {
+                let value = args.next().ok_or("--spacing expects a gap in pixels")?;
+                config.spacing = value.parse()?;
+            }
+            "--fit" => {
+                let value = args.next().ok_or("--fit expects a box height in pixels")?;
+                config.fit_height = Some(value.parse()?);
+            }
+            "--dump-picture" => {
+                let path = args.next().ok_or("--dump-picture expects a file path")?;
+                config.dump_picture = Some(path.into());
+            }
+            // Where to write the image; `-` is stdout, for piping.
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok((config, out_path))
+}
@@ -0,0 +1,87 @@
+use common::raster::read_rgba;
+use example_22::{paragraph_boxes, run, Config, ParagraphBox};
+use skia_safe::{AlphaType, Color, Data, Image};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn fitting_shrinks_the_long_paragraphs() {
+    let height = |config: &Config| {
+        let png = run(config).unwrap();
+        Image::from_encoded(Data::new_copy(&png)).unwrap().height()
+    };
+    // At their own size the body paragraphs wrap to more lines than a 40px
+    // box holds, so they get smaller and the image gets shorter.
+    let fitted = Config {
+        fit_height: Some(40.0),
+        ..Config::default()
+    };
+    assert!(height(&fitted) < height(&Config::default()));
+}
+
+#[test]
+fn paragraphs_are_stacked_in_order_with_the_spacing_between() {
+    let config = Config::default();
+    let boxes = paragraph_boxes(&config).unwrap();
+    assert_eq!(boxes.len(), 4);
+    // Each paragraph starts `spacing` below the end of the one before.
+    for pair in boxes.windows(2) {
+        let gap = pair[1].top - (pair[0].top + pair[0].height);
+        assert!((gap - config.spacing).abs() < 0.01, "{boxes:?}");
+    }
+
+    // More spacing moves every paragraph after the first further down, by
+    // the extra space once per gap above it.
+    let spaced = paragraph_boxes(&Config {
+        spacing: config.spacing + 24.0,
+        ..Config::default()
+    })
+    .unwrap();
+    for (index, (paragraph, spaced)) in boxes.iter().zip(&spaced).enumerate() {
+        let moved = spaced.top - paragraph.top;
+        assert!((moved - 24.0 * index as f32).abs() < 0.01, "paragraph {index} moved {moved}");
+    }
+}
+
+#[test]
+fn each_paragraph_keeps_its_own_style() {
+    let boxes = paragraph_boxes(&Config::default()).unwrap();
+    let styles: Vec<(f32, Color)> =
+        boxes.iter().map(|paragraph| (paragraph.font_size, paragraph.color)).collect();
+    let gray = Color::from_rgb(0x60, 0x60, 0x60);
+    assert_eq!(
+        styles,
+        [(32.0, Color::BLACK), (18.0, Color::BLACK), (18.0, Color::BLACK), (14.0, gray)],
+    );
+    // And the lines are as tall as their own font makes them.
+    let line_height = |paragraph: &ParagraphBox| paragraph.height / paragraph.line_count as f32;
+    assert!(line_height(&boxes[0]) > line_height(&boxes[1]), "{boxes:?}");
+    assert!(line_height(&boxes[1]) > line_height(&boxes[3]), "{boxes:?}");
+}
+
+#[test]
+fn the_ink_stays_inside_each_paragraph() {
+    let config = Config::default();
+    let boxes = paragraph_boxes(&config).unwrap();
+    let png = run(&config).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let row_bytes = image.width() as usize * 4;
+    let inked = |y: usize| pixels[y * row_bytes..(y + 1) * row_bytes].iter().any(|&c| c < 200);
+
+    // Every paragraph has ink in it, and the gaps between them have none.
+    for paragraph in &boxes {
+        let mut rows = paragraph.top as usize..(paragraph.top + paragraph.height) as usize;
+        assert!(rows.any(inked), "nothing drawn in {paragraph:?}");
+    }
+    for pair in boxes.windows(2) {
+        let mut gap = (pair[0].top + pair[0].height).ceil() as usize..pair[1].top as usize;
+        assert!(!gap.any(inked), "ink between {:?} and {:?}", pair[0], pair[1]);
+    }
+}
@@ -0,0 +1,110 @@
+use common::cluster::caret_offset;
+use common::font::LoadedFont;
+use harfbuzz_rs::{shape, GlyphBuffer, UnicodeBuffer};
+use skia_safe::{Color, EncodedImageFormat, Paint, Point, Rect, Surface, TextBlobBuilder};
+use std::error::Error;
+use std::fs;
+
+/// Draws the line twice, with and without the caret (the two states of a
+/// blinking caret), and returns both frames as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    // 1. Load the font and shape the line.
+    let font_data = fs::read("Roboto-LightItalic.ttf")?;
+    let font = LoadedFont::from_bytes(&font_data, config.font_size)?;
+    let skia_font = font.skia_font();
+    let text = config.text.as_str();
+    let shaped = shape_line(&font, text);
+    let infos = shaped.get_glyph_infos();
+    let positions = shaped.get_glyph_positions();
+
+    // 2. Build a blob for the line, HarfBuzz positions are 26.6 fixed point.
+    let margin = 40.0;
+    let (_, metrics) = skia_font.metrics();
+    let baseline = margin - metrics.ascent;
+    let mut builder = TextBlobBuilder::new();
+    let (glyphs, points) = builder.alloc_run_pos(&skia_font, infos.len(), None);
+    let mut pen = 0.0;
+    for (i, (info, pos)) in infos.iter().zip(positions).enumerate() {
+        glyphs[i] = info.codepoint as u16;
+        points[i] = Point::new(
+            margin + pen + pos.x_offset as f32 / 64.0,
+            baseline - pos.y_offset as f32 / 64.0,
+        );
+        pen += pos.x_advance as f32 / 64.0;
+    }
+    let blob = builder.make();
+
+    // 3. The caret spans the font's full ascent and descent, so it is as
+    //    tall as the line box whatever letters are next to it, and is
+    //    centered on the caret position.
+    let caret_x = margin + caret_offset(text, &shaped, config.caret);
+    let caret_width = (config.font_size / 16.0).max(1.0);
+    let caret = Rect::new(
+        caret_x - caret_width / 2.0,
+        baseline + metrics.ascent,
+        caret_x + caret_width / 2.0,
+        baseline + metrics.descent,
+    );
+    let mut caret_paint = Paint::default();
+    caret_paint.set_color(Color::from_rgb(0x20, 0x60, 0xff));
+
+    // 4. Draw both frames. A real editor would flip between them every
+    //    half second or so, and reset to visible whenever the caret moves.
+    let width = (2.0 * margin + pen).ceil() as i32;
+    let height = (2.0 * margin + metrics.descent - metrics.ascent).ceil() as i32;
+    let mut frames = Vec::new();
+    for caret_visible in [true, false] {
+        let mut surface = Surface::new_raster_n32_premul((width, height))
+            .ok_or("Could not create a surface")?;
+        let canvas = surface.canvas();
+        canvas.clear(Color::WHITE);
+        if let Some(blob) = &blob {
+            canvas.draw_text_blob(blob, (0, 0), &Paint::default());
+        }
+        if caret_visible {
+            canvas.draw_rect(caret, &caret_paint);
+        }
+
+        let image = surface.image_snapshot();
+        let png_data = image
+            .encode_to_data(EncodedImageFormat::PNG)
+            .ok_or("Failed to encode image")?;
+        frames.push(png_data.as_bytes().to_vec());
+    }
+    Ok(frames)
+}
+
+/// Returns how far from the start of the line the caret is drawn, in pixels.
+pub fn caret_x(config: &Config) -> Result<f32, Box<dyn Error>> {
+    let font_data = fs::read("Roboto-LightItalic.ttf")?;
+    let font = LoadedFont::from_bytes(&font_data, config.font_size)?;
+    let shaped = shape_line(&font, &config.text);
+    Ok(caret_offset(&config.text, &shaped, config.caret))
+}
+
+/// Shapes the line with HarfBuzz, guessing direction and script.
+fn shape_line(font: &LoadedFont, text: &str) -> GlyphBuffer {
+    let buffer = UnicodeBuffer::new().add_str(text).guess_segment_properties();
+    shape(&font.hb_font, buffer, &[])
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    /// The caret goes before this character (counting chars, not bytes);
+    /// the text's length puts it at the end.
+    pub caret: usize,
+    pub font_size: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            // Put the caret inside "ffi", which most fonts draw as a ligature.
+            text: "An office coffee".to_string(),
+            caret: 5,
+            font_size: 32.0,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use example_24::{run, Config};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = config_from_args()?;
+    for (index, png_bytes) in run(&config)?.into_iter().enumerate() {
+        let output_path = format!("output_caret_{index}.png");
+        fs::write(&output_path, png_bytes)?;
+        println!("Frame written to {output_path}");
+    }
+
+    Ok(())
+}
+
+/// Reads the `Config` from the command line, e.g. `cargo run -- --text "fish" --caret 2`.
+fn config_from_args() -> Result<Config, Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            "--caret" => {
+                let value = args.next().ok_or("--caret expects a character index")?;
+                config.caret = value.parse()?;
+            }
+            "--size" => {
+                let value = args.next().ok_or("--size expects a size in pixels")?;
+                config.font_size = value.parse()?;
+            }
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok(config)
+}
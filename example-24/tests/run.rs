@@ -0,0 +1,33 @@
+use example_24::{caret_x, run, Config};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_two_frames() {
+    let frames = run(&Config::default()).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert!(frames.iter().all(|frame| frame.starts_with(PNG_SIGNATURE)));
+    assert_ne!(frames[0], frames[1]);
+}
+
+#[test]
+fn caret_sits_between_the_right_glyphs() {
+    let at = |text: &str, caret| {
+        let config = Config {
+            text: text.to_string(),
+            caret,
+            ..Config::default()
+        };
+        caret_x(&config).unwrap()
+    };
+
+    // "mmmm" is four identical glyphs, so the caret before the third "m" is
+    // exactly two "m" advances in, and halfway across the line.
+    assert_eq!(at("mmmm", 0), 0.0);
+    assert!((at("mmmm", 2) - at("mmmm", 4) / 2.0).abs() < 0.01);
+
+    // Every caret position moves right, even inside a ligature.
+    let text = "An office coffee";
+    let offsets: Vec<f32> = (0..=text.chars().count()).map(|caret| at(text, caret)).collect();
+    assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]), "{offsets:?}");
+}
@@ -0,0 +1,144 @@
+use common::error::TextError;
+use common::markup::parse_hex_color;
+use common::registry::TypefaceRegistry;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reads the batch file and renders every job in it, writing each image to
+/// the job's output path. Returns how each job went, in file order.
+///
+/// Only a file that can't be read or parsed fails the whole batch. A job
+/// that is invalid or fails to render is reported, and the rest still run.
+pub fn run(config: &Config) -> Result<Vec<JobReport>, Box<dyn Error>> {
+    let source = fs::read_to_string(&config.batch_path)?;
+    let batch = parse_batch(&source)?;
+    let base_dir = config.batch_path.parent().unwrap_or(Path::new(""));
+    let progress = |done, total| eprint!("\rRendered {done} of {total} images");
+    let reports = run_batch(&batch, base_dir, progress);
+    if !reports.is_empty() {
+        eprintln!();
+    }
+    Ok(reports)
+}
+
+/// Parses a batch file (TOML, with one `[[job]]` table per image).
+pub fn parse_batch(source: &str) -> Result<Batch, toml::de::Error> {
+    toml::from_str(source)
+}
+
+/// Renders every job in `batch`, resolving relative font and output paths
+/// against `base_dir`.
+///
+/// All jobs share one `TypefaceRegistry`, so a font that several jobs use
+/// is only loaded once.
+///
+/// `progress` is called after each job, once its PNG is written (or it has
+/// failed), with how many jobs are done and how many there are in all.
+pub fn run_batch(
+    batch: &Batch,
+    base_dir: &Path,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<JobReport> {
+    let mut typefaces = TypefaceRegistry::new();
+    let total = batch.jobs.len();
+    batch
+        .jobs
+        .iter()
+        .enumerate()
+        .map(|(index, job)| {
+            let output = base_dir.join(&job.output);
+            let result = job.to_config(base_dir).and_then(|config| {
+                let png_bytes = example_1::run_with_registry(&config, &mut typefaces)?;
+                Ok(fs::write(&output, png_bytes)?)
+            });
+            progress(index + 1, total);
+            JobReport { output, result }
+        })
+        .collect()
+}
+
+/// The contents of a batch file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Batch {
+    #[serde(rename = "job", default)]
+    pub jobs: Vec<Job>,
+}
+
+/// One image to render. Everything but `text` and `output` falls back to
+/// example-1's defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    pub text: String,
+    /// The font file to draw with.
+    pub font: Option<PathBuf>,
+    /// The font size, in pixels.
+    pub size: Option<f32>,
+    /// The text color, `#rgb` or `#rrggbb`.
+    pub color: Option<String>,
+    /// Where to write the PNG.
+    pub output: PathBuf,
+}
+
+impl Job {
+    /// Checks the job and turns it into an example-1 `Config`.
+    ///
+    /// The file format only checks types, so this catches the values that
+    /// parse but can't be drawn, before anything is rendered.
+    fn to_config(&self, base_dir: &Path) -> Result<example_1::Config, TextError> {
+        let invalid = |message: &str| Err(TextError::InvalidArgument(message.to_string()));
+        if self.text.is_empty() {
+            return invalid("text is empty");
+        }
+        if self.output.extension() != Some("png".as_ref()) {
+            return invalid("output must be a .png file");
+        }
+        // Every image is as wide as its text, however long that is.
+        let mut config = example_1::Config {
+            text: self.text.clone(),
+            overflow: example_1::Overflow::Visible,
+            ..example_1::Config::default()
+        };
+        let font = self.font.clone().unwrap_or_else(|| config.font_path.clone().into());
+        config.font_path = base_dir.join(font).to_string_lossy().into_owned();
+        if let Some(size) = self.size {
+            if !(size.is_finite() && size > 0.0) {
+                return invalid("size must be a positive number of pixels");
+            }
+            config.font_size = size;
+        }
+        if let Some(color) = &self.color {
+            match parse_hex_color(color) {
+                Some(color) => config.color = color,
+                None => return invalid("color must look like #rgb or #rrggbb"),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// How one job went.
+#[derive(Debug)]
+pub struct JobReport {
+    /// Where the image was (or would have been) written.
+    pub output: PathBuf,
+    pub result: Result<(), TextError>,
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The batch file. Relative paths in it are relative to its directory.
+    pub batch_path: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            batch_path: PathBuf::from("batch.toml"),
+        }
+    }
+}
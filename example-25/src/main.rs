@@ -0,0 +1,39 @@
+use example_25::{run, Config};
+use std::env;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = config_from_args()?;
+    let reports = run(&config)?;
+
+    // Report every job, and only fail at the end, so one bad job doesn't
+    // hide how the others went.
+    let mut failed = 0;
+    for report in &reports {
+        match &report.result {
+            Ok(()) => println!("Image written to {}", report.output.display()),
+            Err(error) => {
+                failed += 1;
+                eprintln!("Failed to render {}: {error}", report.output.display());
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} jobs failed", reports.len()).into());
+    }
+    Ok(())
+}
+
+/// Reads the `Config` from the command line, e.g. `cargo run -- batch.toml`.
+fn config_from_args() -> Result<Config, Box<dyn Error>> {
+    let mut config = Config::default();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            other if other.starts_with("--") => {
+                return Err(format!("Unknown argument: {other}").into())
+            }
+            path => config.batch_path = path.into(),
+        }
+    }
+    Ok(config)
+}
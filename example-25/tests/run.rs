@@ -0,0 +1,82 @@
+use example_25::{parse_batch, run_batch};
+use std::fs;
+use std::path::PathBuf;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// An empty directory for one test's output.
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A job for `batch`, drawn with the font shipped next to this example.
+fn job(text: &str, extra: &str, output: &str) -> String {
+    let font = concat!(env!("CARGO_MANIFEST_DIR"), "/Roboto-LightItalic.ttf");
+    format!("[[job]]\ntext = {text:?}\nfont = {font:?}\n{extra}\noutput = {output:?}\n\n")
+}
+
+#[test]
+fn three_jobs_make_three_pngs() {
+    let dir = output_dir("example-25-three-jobs");
+    let source = [
+        job("one", "", "one.png"),
+        job("two", "size = 20", "two.png"),
+        job("three", "color = \"#f00\"", "three.png"),
+    ]
+    .concat();
+    let reports = run_batch(&parse_batch(&source).unwrap(), &dir, |_, _| {});
+
+    assert_eq!(reports.len(), 3);
+    for (report, name) in reports.iter().zip(["one.png", "two.png", "three.png"]) {
+        assert!(report.result.is_ok(), "{report:?}");
+        assert_eq!(report.output, dir.join(name));
+        assert!(fs::read(&report.output).unwrap().starts_with(PNG_SIGNATURE));
+    }
+}
+
+#[test]
+fn bad_jobs_are_reported_and_the_rest_still_run() {
+    let dir = output_dir("example-25-bad-jobs");
+    let source = [
+        job("bad color", "color = \"red\"", "color.png"),
+        job("fine", "", "fine.png"),
+        job("bad size", "size = -3", "size.png"),
+        "[[job]]\ntext = \"missing font\"\nfont = \"nope.ttf\"\noutput = \"font.png\"\n".into(),
+    ]
+    .concat();
+    let reports = run_batch(&parse_batch(&source).unwrap(), &dir, |_, _| {});
+
+    let ok: Vec<bool> = reports.iter().map(|report| report.result.is_ok()).collect();
+    assert_eq!(ok, [false, true, false, false]);
+    assert!(dir.join("fine.png").exists());
+    assert!(!dir.join("color.png").exists());
+}
+
+#[test]
+fn progress_is_reported_once_per_job() {
+    let dir = output_dir("example-25-progress");
+    let source = [
+        job("one", "", "one.png"),
+        job("bad size", "size = -3", "size.png"),
+        job("three", "", "three.png"),
+    ]
+    .concat();
+    let batch = parse_batch(&source).unwrap();
+    let mut calls = Vec::new();
+    run_batch(&batch, &dir, |done, total| {
+        // The job's image is already written by the time it's reported.
+        let written = ["one.png", "three.png"].iter().filter(|name| dir.join(name).exists());
+        calls.push((done, total, written.count()));
+    });
+
+    assert_eq!(calls.len(), batch.jobs.len());
+    assert_eq!(calls, [(1, 3, 1), (2, 3, 1), (3, 3, 2)]);
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    assert!(parse_batch("[[job]]\ntext = \"hi\"\noutput = \"hi.png\"\nsise = 12\n").is_err());
+}
@@ -0,0 +1,167 @@
+use common::font::LoadedFont;
+use common::shaping::shape_text;
+use freetype as ft;
+use skia_safe::{Color, EncodedImageFormat, Paint, Path, Rect, Surface};
+use std::error::Error;
+use std::fs;
+
+/// Draws the text once per frame, each glyph popping in (scaling up from
+/// nothing) a little after the one before it, and returns the frames as PNG
+/// bytes.
+pub fn run(config: &Config) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    let glyphs = glyph_outlines(config)?;
+
+    // The image fits the text at full size, plus a margin.
+    let margin = 20.0;
+    let ink = glyphs.iter().fold(Rect::new_empty(), |mut ink, glyph| {
+        ink.join(glyph.path.bounds());
+        ink
+    });
+    let width = (ink.right + margin).ceil() as i32;
+    let height = (ink.bottom + margin).ceil() as i32;
+
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+
+    let mut frames = Vec::new();
+    for frame in 0..frame_count(config, glyphs.len()) {
+        let mut surface = Surface::new_raster_n32_premul((width, height))
+            .ok_or("Could not create a surface")?;
+        let canvas = surface.canvas();
+        canvas.clear(Color::WHITE);
+
+        // Each glyph is scaled about the center of its own outline, so it
+        // grows out of the spot it ends up in rather than sliding in from
+        // its origin on the baseline.
+        for (index, glyph) in glyphs.iter().enumerate() {
+            let scale = glyph_scale(config, index, frame);
+            if scale <= 0.0 {
+                continue;
+            }
+            // With --fade, the glyph also fades in as it grows.
+            if config.fade_in {
+                paint.set_alpha_f(scale);
+            }
+            let center = glyph.path.bounds().center();
+            canvas.save();
+            canvas.translate(center);
+            canvas.scale((scale, scale));
+            canvas.translate(-center);
+            canvas.draw_path(&glyph.path, &paint);
+            canvas.restore();
+        }
+
+        let image = surface.image_snapshot();
+        let png_data = image
+            .encode_to_data(EncodedImageFormat::PNG)
+            .ok_or("Failed to encode image")?;
+        frames.push(png_data.as_bytes().to_vec());
+    }
+    Ok(frames)
+}
+
+/// How much glyph `index` is scaled in `frame`: 0 before its turn, easing
+/// out to 1 over `config.ramp_frames`, then 1 for the rest of the animation.
+///
+/// Glyph `index` starts `index * config.stagger_frames` frames in.
+pub fn glyph_scale(config: &Config, index: usize, frame: usize) -> f32 {
+    let start = index * config.stagger_frames;
+    if frame < start {
+        return 0.0;
+    }
+    let t = ((frame - start) as f32 / config.ramp_frames.max(1) as f32).min(1.0);
+    // Ease out (cubic): fast at first, settling gently at full size.
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// The number of frames for `glyph_count` glyphs: until the last one has
+/// reached full size, plus that final frame.
+pub fn frame_count(config: &Config, glyph_count: usize) -> usize {
+    glyph_count.saturating_sub(1) * config.stagger_frames + config.ramp_frames + 1
+}
+
+/// The ink bounds of each glyph at full size, in image coordinates. Glyphs
+/// without an outline (spaces) have no entry, and don't count for the
+/// stagger either.
+pub fn glyph_boxes(config: &Config) -> Result<Vec<Rect>, Box<dyn Error>> {
+    Ok(glyph_outlines(config)?.iter().map(|glyph| *glyph.path.bounds()).collect())
+}
+
+/// One glyph's outline, already placed where it goes in the image.
+struct GlyphOutline {
+    path: Path,
+}
+
+/// Shapes the text and loads each glyph's FreeType outline as a Skia path.
+fn glyph_outlines(config: &Config) -> Result<Vec<GlyphOutline>, Box<dyn Error>> {
+    let font_data = fs::read("Rubik-VariableFont_wght.ttf")?;
+    let font = LoadedFont::from_bytes(&font_data, config.font_size)?;
+    let run = shape_text(&font.hb_font, &config.text, &[]);
+
+    let origin_x = 20.0;
+    let origin_y = 20.0 + config.font_size;
+    let mut outlines = Vec::new();
+    for (&glyph_id, position) in run.glyphs.iter().zip(&run.positions) {
+        font.ft_face.load_glyph(glyph_id as u32, ft::face::LoadFlag::NO_BITMAP)?;
+        let Some(outline) = font.ft_face.glyph().outline() else {
+            continue;
+        };
+        let mut path = outline_to_path(&outline);
+        if path.bounds().is_empty() {
+            continue;
+        }
+        path.offset((origin_x + position.x, origin_y + position.y));
+        outlines.push(GlyphOutline { path });
+    }
+    Ok(outlines)
+}
+
+/// Converts a FreeType outline (26.6 fixed point, y up) to a Skia path
+/// (pixels, y down), the same way example-5 does.
+fn outline_to_path(outline: &ft::outline::Outline) -> Path {
+    let point = |v: &ft::Vector| (v.x as f32 / 64.0, -v.y as f32 / 64.0);
+    let mut path = Path::new();
+    for contour in outline.contours_iter() {
+        path.move_to(point(contour.start()));
+        for curve in contour {
+            match curve {
+                ft::outline::Curve::Line(pt) => {
+                    path.line_to(point(&pt));
+                }
+                ft::outline::Curve::Bezier2(pt1, pt2) => {
+                    path.quad_to(point(&pt1), point(&pt2));
+                }
+                ft::outline::Curve::Bezier3(pt1, pt2, pt3) => {
+                    path.cubic_to(point(&pt1), point(&pt2), point(&pt3));
+                }
+            }
+        }
+        path.close();
+    }
+    path
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    pub font_size: f32,
+    /// Frames between one glyph starting to pop in and the next.
+    pub stagger_frames: usize,
+    /// Frames each glyph takes to grow to full size.
+    pub ramp_frames: usize,
+    /// Ramp each glyph's opacity along with its scale.
+    pub fade_in: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            text: "Pop in!".to_string(),
+            font_size: 64.0,
+            stagger_frames: 2,
+            ramp_frames: 6,
+            fade_in: false,
+        }
+    }
+}
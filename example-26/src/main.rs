@@ -0,0 +1,41 @@
+use example_26::{run, Config};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = config_from_args()?;
+    let frames = run(&config)?;
+    for (index, png_bytes) in frames.iter().enumerate() {
+        fs::write(format!("output_pop_{index:03}.png"), png_bytes)?;
+    }
+
+    println!("{} frames written to output_pop_000.png and on", frames.len());
+    Ok(())
+}
+
+/// Reads the `Config` from the command line, e.g. `cargo run -- --text "Hi" --stagger 3`.
+fn config_from_args() -> Result<Config, Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            "--size" => {
+                let value = args.next().ok_or("--size expects a size in pixels")?;
+                config.font_size = value.parse()?;
+            }
+            "--stagger" => {
+                let value = args.next().ok_or("--stagger expects a number of frames")?;
+                config.stagger_frames = value.parse()?;
+            }
+            "--ramp" => {
+                let value = args.next().ok_or("--ramp expects a number of frames")?;
+                config.ramp_frames = value.parse()?;
+            }
+            "--fade" => config.fade_in = true,
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok(config)
+}
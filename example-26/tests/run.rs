@@ -0,0 +1,68 @@
+use common::raster::read_rgba;
+use example_26::{frame_count, glyph_boxes, glyph_scale, run, Config};
+use skia_safe::{AlphaType, Contains, Data, Image, Point};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_png_frames() {
+    let config = Config::default();
+    let frames = run(&config).unwrap();
+    assert_eq!(frames.len(), frame_count(&config, glyph_boxes(&config).unwrap().len()));
+    assert!(frames.iter().all(|png| png.starts_with(PNG_SIGNATURE)));
+}
+
+#[test]
+fn glyphs_pop_in_one_after_another() {
+    let config = Config::default();
+    assert_eq!(glyph_scale(&config, 0, 0), 0.0);
+    assert_eq!(glyph_scale(&config, 0, config.ramp_frames), 1.0);
+    assert_eq!(glyph_scale(&config, 3, 3 * config.stagger_frames), 0.0);
+    let halfway = glyph_scale(&config, 3, 3 * config.stagger_frames + config.ramp_frames / 2);
+    assert!(halfway > 0.5 && halfway < 1.0);
+}
+
+/// Which pixels of `png` have ink on them (anything darker than white),
+/// with the center of each pixel.
+fn ink(png: &[u8]) -> Vec<(Point, bool)> {
+    let image = Image::from_encoded(Data::new_copy(png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let width = image.width() as usize;
+    pixels
+        .chunks(4)
+        .enumerate()
+        .map(|(index, pixel)| {
+            let center = Point::new((index % width) as f32 + 0.5, (index / width) as f32 + 0.5);
+            (center, pixel[0] < 255)
+        })
+        .collect()
+}
+
+#[test]
+fn early_frames_show_only_the_first_glyph_at_full_size() {
+    // The second glyph starts just as the first one reaches full size.
+    let config = Config {
+        text: "AB".to_string(),
+        stagger_frames: 4,
+        ramp_frames: 4,
+        ..Config::default()
+    };
+    let boxes = glyph_boxes(&config).unwrap();
+    let frames = run(&config).unwrap();
+    let early = ink(&frames[4]);
+    let last = ink(&frames[frames.len() - 1]);
+
+    // Everything drawn is inside the first glyph's box (give or take the
+    // anti-aliasing), and where the second glyph can't reach, it's drawn
+    // exactly as in the last frame.
+    let first = boxes[0].with_outset((1.0, 1.0));
+    let second = boxes[1].with_outset((1.0, 1.0));
+    assert!(early.iter().any(|&(_, ink)| ink));
+    for (&(center, early), &(_, last)) in early.iter().zip(&last) {
+        if !first.contains(center) {
+            assert!(!early, "ink outside the first glyph at {center:?}");
+        } else if !second.contains(center) {
+            assert_eq!(early, last, "first glyph differs at {center:?}");
+        }
+    }
+}
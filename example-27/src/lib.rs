@@ -0,0 +1,108 @@
+use common::blob::{build_blob, FontRun};
+use common::font::LoadedFont;
+use common::picture::record_picture;
+use common::shaping::shape_text;
+use skia_safe::{Canvas, Color, EncodedImageFormat, Paint, Rect, Surface};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Draws the text once per theme, in bands stacked top to bottom, and
+/// returns the image as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // 1. Shape the text once. The blob holds glyphs and positions but no
+    //    color: that comes from the paint it's drawn with, so every theme
+    //    draws the very same blob.
+    let font_data = fs::read("Roboto-LightItalic.ttf")?;
+    let font = LoadedFont::from_bytes(&font_data, config.font_size)?;
+    let skia_font = font.skia_font();
+    let glyphs = shape_text(&font.hb_font, &config.text, &[]);
+    let width = glyphs.width;
+    let blob = build_blob(&[FontRun {
+        font: skia_font.clone(),
+        glyphs,
+    }]);
+
+    // 2. Each band is one line box tall, plus a margin all around, rounded
+    //    up to whole pixels so no row is shared by two backgrounds.
+    let margin = 20.0;
+    let (_, metrics) = skia_font.metrics();
+    let band_height = (2.0 * margin + metrics.descent - metrics.ascent).ceil();
+    let image_width = (2.0 * margin + width).ceil() as i32;
+    let image_height = band_height as i32 * config.themes.len() as i32;
+
+    // 3. Fill each band with its theme's background, then draw the blob on
+    //    top in its foreground color.
+    let draw = |canvas: &Canvas| {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        for (index, theme) in config.themes.iter().enumerate() {
+            let top = band_height * index as f32;
+            paint.set_color(theme.background);
+            canvas.draw_rect(
+                Rect::from_xywh(0.0, top, image_width as f32, band_height),
+                &paint,
+            );
+            if let Some(blob) = &blob {
+                paint.set_color(theme.foreground);
+                canvas.draw_text_blob(blob, (margin, top + margin - metrics.ascent), &paint);
+            }
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((image_width, image_height), draw)
+            .ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((image_width, image_height))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// A text color on a background color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        foreground: Color::BLACK,
+        background: Color::WHITE,
+    };
+    pub const DARK: Theme = Theme {
+        foreground: Color::WHITE,
+        background: Color::BLACK,
+    };
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    pub font_size: f32,
+    /// One band per theme, top to bottom.
+    pub themes: Vec<Theme>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            text: "Light and dark".to_string(),
+            font_size: 32.0,
+            themes: vec![Theme::LIGHT, Theme::DARK],
+            dump_picture: None,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use common::markup::parse_hex_color;
+use common::output::write_output;
+use example_27::{run, Config, Theme};
+use std::env;
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (config, out_path) = config_from_args()?;
+    let png_bytes = run(&config)?;
+    let path = out_path.as_deref().unwrap_or("output_themes.png");
+    write_output(path, &png_bytes)?;
+    eprintln!("Image written to {path}");
+
+    Ok(())
+}
+
+/// Reads the `Config` and the output path (`--out`) from the command line, e.g.
+/// `cargo run -- --text "Preview"`,
+/// or `cargo run -- --theme "#333,#fafafa" --theme "#eee,#1e1e1e"` for other themes
+/// (text color first, then background).
+fn config_from_args() -> Result<(Config, Option<String>), Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut out_path = None;
+    let mut themes = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            "--size" => {
+                let value = args.next().ok_or("--size expects a size in pixels")?;
+                config.font_size = value.parse()?;
+            }
+            "--theme" => {
+                let usage = "--theme expects two colors, like #000,#fff";
+                let value = args.next().ok_or(usage)?;
+                let (foreground, background) = value.split_once(',').ok_or(usage)?;
+                themes.push(Theme {
+                    foreground: parse_hex_color(foreground).ok_or(usage)?,
+                    background: parse_hex_color(background).ok_or(usage)?,
+                });
+            }
+            "--dump-picture" => {
+                let path = args.next().ok_or("--dump-picture expects a file path")?;
+                config.dump_picture = Some(path.into());
+            }
+            // Where to write the image; `-` is stdout, for piping.
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    if !themes.is_empty() {
+        config.themes = themes;
+    }
+    Ok((config, out_path))
+}
@@ -0,0 +1,87 @@
+use common::picture::replay_picture;
+use common::raster::read_rgba;
+use example_27::{run, Config, Theme};
+use skia_safe::{AlphaType, Data, Image};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn light_and_dark_bands_are_inverted() {
+    let png = run(&Config::default()).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+
+    // Two bands of equal height; the text is gray, so red is enough.
+    let band = |index: usize| -> Vec<u8> {
+        let rows = height / 2;
+        (index * rows..(index + 1) * rows)
+            .flat_map(|y| (0..width).map(move |x| (y * width + x) * 4))
+            .map(|offset| pixels[offset])
+            .collect()
+    };
+    let (light, dark) = (band(0), band(1));
+
+    // The background is the corner pixel; the text reaches the other end.
+    assert_eq!((light[0], dark[0]), (255, 0));
+    assert!(*light.iter().min().unwrap() < 30, "no dark text on the light band");
+    assert!(*dark.iter().max().unwrap() > 225, "no light text on the dark band");
+
+    // Same blob in the same spot, so the text covers the same pixels. (Not
+    // with exactly the same coverage: Skia tunes anti-aliasing for light
+    // text on dark differently from dark on light.)
+    let inked = |values: &[u8], background: u8| -> Vec<usize> {
+        (0..values.len()).filter(|&i| values[i].abs_diff(background) > 128).collect()
+    };
+    let bounds = |ink: Vec<usize>| -> [usize; 4] {
+        let columns = || ink.iter().map(|i| i % width);
+        let rows = || ink.iter().map(|i| i / width);
+        [columns().min(), columns().max(), rows().min(), rows().max()].map(Option::unwrap)
+    };
+    let light_bounds = bounds(inked(&light, 255));
+    let dark_bounds = bounds(inked(&dark, 0));
+    for (a, b) in light_bounds.iter().zip(&dark_bounds) {
+        assert!(a.abs_diff(*b) <= 1, "{light_bounds:?} vs {dark_bounds:?}");
+    }
+}
+
+#[test]
+fn one_band_per_theme() {
+    let one = Config {
+        themes: vec![Theme::DARK],
+        ..Config::default()
+    };
+    let three = Config {
+        themes: vec![Theme::LIGHT, Theme::DARK, Theme::LIGHT],
+        ..Config::default()
+    };
+    let height = |config: &Config| {
+        let png = run(config).unwrap();
+        Image::from_encoded(Data::new_copy(&png)).unwrap().height()
+    };
+    assert_eq!(height(&three), 3 * height(&one));
+}
+
+#[test]
+fn dumped_picture_replays_to_the_same_image() {
+    let path = std::env::temp_dir().join("example-27-dump.skp");
+    let config = Config {
+        dump_picture: Some(path.clone()),
+        ..Config::default()
+    };
+    let png = run(&config).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+
+    let skp = std::fs::read(&path).unwrap();
+    let replayed = replay_picture(&skp, image.dimensions()).unwrap();
+    assert_eq!(
+        read_rgba(&replayed, AlphaType::Premul).unwrap(),
+        read_rgba(&image, AlphaType::Premul).unwrap(),
+    );
+}
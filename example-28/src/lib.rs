@@ -0,0 +1,125 @@
+use common::font::LoadedFont;
+use common::picture::record_picture;
+use common::shaping::stream_glyphs;
+use skia_safe::{Canvas, Color, EncodedImageFormat, GlyphId, Paint, Point, RSXform, Surface};
+use std::error::Error;
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::PathBuf;
+
+/// Draws the text along a spiral, from the middle outwards, and returns the
+/// image as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // 1. Load the font at the size the text is drawn at.
+    let font_data = fs::read("Roboto-LightItalic.ttf")?;
+    let font = LoadedFont::from_bytes(&font_data, config.font_size)?;
+    let skia_font = font.skia_font();
+    let (spacing, _) = skia_font.metrics();
+
+    // 2. An Archimedean spiral: the radius grows by one line per turn, so
+    //    the turns are stacked like lines of text.
+    let center = Point::new(config.size as f32 / 2.0, config.size as f32 / 2.0);
+    let inner_radius = 2.0 * spacing;
+    let radius = |angle: f32| inner_radius + spacing * angle / TAU;
+    let max_radius = config.size as f32 / 2.0 - spacing;
+
+    // 3. Walk the glyphs as they're shaped, word by word, and draw them in
+    //    batches of `config.batch`. Only one batch and one word's shaping are
+    //    held at a time, however long the text is. Once the spiral is full we
+    //    stop asking for glyphs, and the rest of the text is never shaped.
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        let mut glyphs = Vec::with_capacity(config.batch);
+        let mut xforms = Vec::with_capacity(config.batch);
+        let flush = |glyphs: &mut Vec<GlyphId>, xforms: &mut Vec<RSXform>| {
+            canvas.draw_glyphs_at(glyphs, &xforms[..], Point::default(), &skia_font, &paint);
+            glyphs.clear();
+            xforms.clear();
+        };
+
+        let (mut angle, mut distance) = (0.0, 0.0);
+        let words = config.text.split_inclusive(' ');
+        for (glyph, position, advance) in stream_glyphs(&font.hb_font, words, &[]) {
+            // The baseline is the spiral, so the distance along the baseline is
+            // the distance along the curve. Each glyph is centered on the spiral
+            // by its middle, so it's turned to follow the curve under it, and an
+            // arc of length d at radius r spans d / r radians.
+            let middle = position.x + advance / 2.0;
+            angle += (middle - distance) / radius(angle);
+            distance = middle;
+            if radius(angle) > max_radius {
+                break;
+            }
+
+            // Turned a quarter past the angle, the glyph's baseline runs along
+            // the spiral (clockwise) and its top points outwards.
+            let (sin, cos) = angle.sin_cos();
+            let on_spiral = center + Point::new(cos, sin) * radius(angle);
+            let (scos, ssin) = (-sin, cos);
+            // Where the glyph's origin has to go for its middle (and its offset
+            // from the baseline) to end up on the spiral.
+            let origin = on_spiral
+                - Point::new(
+                    scos * advance / 2.0 - ssin * position.y,
+                    ssin * advance / 2.0 + scos * position.y,
+                );
+            glyphs.push(glyph);
+            xforms.push(RSXform::new(scos, ssin, origin));
+            if glyphs.len() == config.batch {
+                flush(&mut glyphs, &mut xforms);
+            }
+        }
+        flush(&mut glyphs, &mut xforms);
+    };
+
+    // With --dump-picture, also save the drawing as an .skp. The text is
+    // streamed while it's drawn, so this shapes it a second time.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((config.size, config.size), draw)
+            .ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((config.size, config.size))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The text to draw. Whatever doesn't fit on the spiral is left out.
+    pub text: String,
+    pub font_size: f32,
+    /// Width and height of the (square) image, in pixels.
+    pub size: i32,
+    /// How many glyphs to collect before drawing them.
+    pub batch: usize,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let paragraph = "Alice was beginning to get very tired of sitting by her sister on \
+            the bank, and of having nothing to do: once or twice she had peeped into the \
+            book her sister was reading, but it had no pictures or conversations in it, \
+            \u{2018}and what is the use of a book,\u{2019} thought Alice \u{2018}without \
+            pictures or conversations?\u{2019}";
+        Config {
+            // More than fits, so the spiral is full.
+            text: [paragraph; 20].join(" "),
+            font_size: 14.0,
+            size: 600,
+            batch: 64,
+            dump_picture: None,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use common::output::write_output;
+use example_28::{run, Config};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (config, out_path) = config_from_args()?;
+    let png_bytes = run(&config)?;
+    let path = out_path.as_deref().unwrap_or("output_spiral.png");
+    write_output(path, &png_bytes)?;
+    eprintln!("Image written to {path}");
+
+    Ok(())
+}
+
+/// Reads the `Config` and the output path (`--out`) from the command line, e.g.
+/// `cargo run -- --file book.txt`,
+/// which streams a whole book onto the spiral until it's full.
+fn config_from_args() -> Result<(Config, Option<String>), Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            // Line breaks become spaces, since the spiral is one long line.
+            "--file" => {
+                let path = args.next().ok_or("--file expects a path")?;
+                config.text = fs::read_to_string(path)?.replace('\n', " ");
+            }
+            "--size" => {
+                let value = args.next().ok_or("--size expects a size in pixels")?;
+                config.font_size = value.parse()?;
+            }
+            "--batch" => {
+                let value = args.next().ok_or("--batch expects a number of glyphs")?;
+                config.batch = value.parse()?;
+            }
+            "--dump-picture" => {
+                let path = args.next().ok_or("--dump-picture expects a file path")?;
+                config.dump_picture = Some(path.into());
+            }
+            // Where to write the image; `-` is stdout, for piping.
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok((config, out_path))
+}
@@ -0,0 +1,40 @@
+use example_28::{run, Config};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn batch_size_doesnt_change_the_image() {
+    let with_batch = |batch| {
+        run(&Config {
+            batch,
+            ..Config::default()
+        })
+        .unwrap()
+    };
+    let default = with_batch(64);
+    assert_eq!(with_batch(1), default);
+    assert_eq!(with_batch(1000), default);
+}
+
+#[test]
+fn text_past_the_end_of_the_spiral_is_left_out() {
+    // The default text already fills the spiral, so more of it draws nothing
+    // more, while less of it does leave a gap.
+    let config = Config::default();
+    let with_text = |text: String| {
+        run(&Config {
+            text,
+            ..config.clone()
+        })
+        .unwrap()
+    };
+    let full = run(&config).unwrap();
+    assert_eq!(with_text(format!("{} {}", config.text, config.text)), full);
+    assert_ne!(with_text("Alice".to_string()), full);
+}
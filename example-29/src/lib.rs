@@ -0,0 +1,186 @@
+use common::cluster::caret_offset;
+use common::font::LoadedFont;
+use harfbuzz_rs::{shape, GlyphBuffer, UnicodeBuffer};
+use skia_safe::{Color, Image, Paint, Point, Rect, Surface, TextBlob, TextBlobBuilder};
+use std::error::Error;
+
+/// A change to the line, usually from a key press.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Type this text at the caret.
+    Insert(String),
+    /// Delete the character before the caret.
+    Backspace,
+    /// Delete the character after the caret.
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+/// One line of editable text with a caret, shaped and ready to draw.
+///
+/// The line is reshaped after every edit that changes the text, and only
+/// then: moving the caret, or drawing the same state again (a window redraws
+/// whenever it's uncovered or resized), reuses the shaped glyphs and the
+/// blob built from them.
+///
+/// The caret counts characters, not grapheme clusters, like example-24. A
+/// real editor would step over a whole "é" typed as e + combining accent.
+pub struct Editor<'a> {
+    font: LoadedFont<'a>,
+    text: String,
+    /// The caret is before this character; the text's length puts it at the
+    /// end.
+    caret: usize,
+    shaped: GlyphBuffer,
+    blob: Option<TextBlob>,
+    /// How many times the line has been shaped.
+    shape_count: usize,
+}
+
+impl<'a> Editor<'a> {
+    /// An empty line in the font in `font_data`, at `font_size` pixels.
+    pub fn new(font_data: &'a [u8], font_size: f32) -> Result<Self, Box<dyn Error>> {
+        let font = LoadedFont::from_bytes(font_data, font_size)?;
+        // Nothing to draw yet, so no blob.
+        let shaped = shape(&font.hb_font, UnicodeBuffer::new(), &[]);
+        Ok(Editor {
+            font,
+            text: String::new(),
+            caret: 0,
+            shaped,
+            blob: None,
+            shape_count: 1,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The caret's position, in characters from the start.
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// How far from the start of the line the caret is drawn, in pixels.
+    pub fn caret_x(&self) -> f32 {
+        caret_offset(&self.text, &self.shaped, self.caret)
+    }
+
+    pub fn shape_count(&self) -> usize {
+        self.shape_count
+    }
+
+    /// Applies `edit`, reshaping the line if the text changed.
+    pub fn apply(&mut self, edit: Edit) {
+        let (char_count, byte_count) = (self.text.chars().count(), self.text.len());
+        match edit {
+            Edit::Insert(text) => {
+                // Enter, tab and the like come through as control characters.
+                let text: String = text.chars().filter(|c| !c.is_control()).collect();
+                if text.is_empty() {
+                    return;
+                }
+                self.text.insert_str(self.byte_index(self.caret), &text);
+                self.caret += text.chars().count();
+            }
+            Edit::Backspace if self.caret > 0 => {
+                self.caret -= 1;
+                self.remove_char(self.caret);
+            }
+            Edit::Delete if self.caret < char_count => self.remove_char(self.caret),
+            Edit::Left => self.caret = self.caret.saturating_sub(1),
+            Edit::Right => self.caret = (self.caret + 1).min(char_count),
+            Edit::Home => self.caret = 0,
+            Edit::End => self.caret = char_count,
+            // Backspace at the start or delete at the end.
+            _ => {}
+        }
+        if self.text.len() != byte_count {
+            self.reshape();
+        }
+    }
+
+    /// Draws the line and (if `caret_visible`) the caret on a white image
+    /// of `size`. Text past the right edge is cut off.
+    pub fn render(&self, size: (i32, i32), caret_visible: bool) -> Option<Image> {
+        let mut surface = Surface::new_raster_n32_premul(size)?;
+        let canvas = surface.canvas();
+        canvas.clear(Color::WHITE);
+
+        let skia_font = self.font.skia_font();
+        let (_, metrics) = skia_font.metrics();
+        let origin = Point::new(MARGIN, MARGIN - metrics.ascent);
+        if let Some(blob) = &self.blob {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            canvas.draw_text_blob(blob, origin, &paint);
+        }
+
+        // The caret spans the line box, as in example-24.
+        if caret_visible {
+            let caret_x = origin.x + self.caret_x();
+            let caret_width = (self.font.px_size / 16.0).max(1.0);
+            let caret = Rect::new(
+                caret_x - caret_width / 2.0,
+                origin.y + metrics.ascent,
+                caret_x + caret_width / 2.0,
+                origin.y + metrics.descent,
+            );
+            let mut caret_paint = Paint::default();
+            caret_paint.set_color(CARET_COLOR);
+            canvas.draw_rect(caret, &caret_paint);
+        }
+        Some(surface.image_snapshot())
+    }
+
+    /// Shapes the whole line again and rebuilds its blob.
+    ///
+    /// HarfBuzz has no way to patch an earlier result: a new letter can
+    /// change the glyphs before it (joining, ligatures, kerning), so every
+    /// edit shapes the line from scratch. For one line that's well under a
+    /// millisecond; longer texts would be split into paragraphs and only the
+    /// edited one reshaped.
+    fn reshape(&mut self) {
+        let buffer = UnicodeBuffer::new().add_str(&self.text).guess_segment_properties();
+        self.shaped = shape(&self.font.hb_font, buffer, &[]);
+        self.shape_count += 1;
+
+        let infos = self.shaped.get_glyph_infos();
+        let positions = self.shaped.get_glyph_positions();
+        // Everything deleted: there's no run to build.
+        if infos.is_empty() {
+            self.blob = None;
+            return;
+        }
+        let mut builder = TextBlobBuilder::new();
+        let skia_font = self.font.skia_font();
+        let (glyphs, points) = builder.alloc_run_pos(&skia_font, infos.len(), None);
+        let mut pen = 0.0;
+        for (i, (info, pos)) in infos.iter().zip(positions).enumerate() {
+            glyphs[i] = info.codepoint as u16;
+            points[i] = Point::new(pen + pos.x_offset as f32 / 64.0, -pos.y_offset as f32 / 64.0);
+            pen += pos.x_advance as f32 / 64.0;
+        }
+        self.blob = builder.make();
+    }
+
+    /// Character index => byte offset into the text.
+    fn byte_index(&self, caret: usize) -> usize {
+        self.text.char_indices().nth(caret).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn remove_char(&mut self, index: usize) {
+        let start = self.byte_index(index);
+        self.text.remove(start);
+    }
+}
+
+/// Space between the edges of the image and the line box, in pixels.
+const MARGIN: f32 = 20.0;
+
+/// The caret's color, a blue that stands out from the black text.
+pub const CARET_COLOR: Color = Color::from_rgb(0x20, 0x60, 0xff);
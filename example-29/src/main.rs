@@ -0,0 +1,62 @@
+use common::output::write_output;
+use example_29::{Edit, Editor};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+#[cfg(feature = "gui")]
+mod window;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let font_data = fs::read("Roboto-LightItalic.ttf")?;
+    let mut editor = Editor::new(&font_data, FONT_SIZE)?;
+
+    // With the gui feature (`cargo run --features gui`), type into a window.
+    #[cfg(feature = "gui")]
+    if env::args().len() == 1 {
+        return window::run(editor);
+    }
+
+    // Otherwise replay the command line as key presses, e.g.
+    // `cargo run -- --type "Hello" --key left --key backspace --type "p!"`,
+    // and save the result (to --out, or output_editor.png).
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let edit = match arg.as_str() {
+            "--type" => Edit::Insert(args.next().ok_or("--type expects some text")?),
+            "--key" => match args.next().as_deref() {
+                Some("backspace") => Edit::Backspace,
+                Some("delete") => Edit::Delete,
+                Some("left") => Edit::Left,
+                Some("right") => Edit::Right,
+                Some("home") => Edit::Home,
+                Some("end") => Edit::End,
+                _ => {
+                    return Err("--key expects backspace, delete, left, right, home or end".into())
+                }
+            },
+            // Where to write the image; `-` is stdout, for piping.
+            "--out" => {
+                out_path = Some(args.next().ok_or("--out expects a path, or -")?);
+                continue;
+            }
+            other => return Err(format!("Unknown argument: {other}").into()),
+        };
+        editor.apply(edit);
+    }
+
+    let image = editor.render(IMAGE_SIZE, true).ok_or("Could not create a surface")?;
+    let png_data = image
+        .encode_to_data(skia_safe::EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    let path = out_path.as_deref().unwrap_or("output_editor.png");
+    write_output(path, png_data.as_bytes())?;
+    eprintln!("{:?} (caret at {}) written to {path}", editor.text(), editor.caret());
+    Ok(())
+}
+
+const FONT_SIZE: f32 = 32.0;
+
+/// The size of the saved image, and of the window when it opens.
+const IMAGE_SIZE: (i32, i32) = (600, 80);
@@ -0,0 +1,112 @@
+//! The live editor: a winit window whose key presses become `Edit`s, and
+//! whose pixels come from `Editor::render` through softbuffer.
+
+use crate::IMAGE_SIZE;
+use common::raster::read_rgba;
+use example_29::{Edit, Editor};
+use skia_safe::AlphaType;
+use std::error::Error;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{Key, NamedKey};
+use winit::window::{Window, WindowId};
+
+/// Opens the window and runs until it's closed.
+pub fn run(editor: Editor) -> Result<(), Box<dyn Error>> {
+    let event_loop = EventLoop::new()?;
+    let mut app = App {
+        editor,
+        window: None,
+    };
+    event_loop.run_app(&mut app)?;
+    Ok(())
+}
+
+struct App<'a> {
+    editor: Editor<'a>,
+    /// Created once the event loop is running (see `resumed`).
+    window: Option<(Rc<Window>, softbuffer::Surface<Rc<Window>, Rc<Window>>)>,
+}
+
+impl ApplicationHandler for App<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let (width, height) = IMAGE_SIZE;
+        let attributes = Window::default_attributes()
+            .with_title("Type something")
+            .with_inner_size(PhysicalSize::new(width as u32, height as u32));
+        let window = event_loop.create_window(attributes).expect("Could not open a window");
+        let window = Rc::new(window);
+        let context = softbuffer::Context::new(window.clone()).expect("No display to draw on");
+        let surface = softbuffer::Surface::new(&context, window.clone())
+            .expect("Could not draw to the window");
+        self.window = Some((window, surface));
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        let Some((window, surface)) = &mut self.window else {
+            return;
+        };
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        logical_key,
+                        text,
+                        ..
+                    },
+                ..
+            } => {
+                let edit = match logical_key {
+                    Key::Named(NamedKey::Backspace) => Edit::Backspace,
+                    Key::Named(NamedKey::Delete) => Edit::Delete,
+                    Key::Named(NamedKey::ArrowLeft) => Edit::Left,
+                    Key::Named(NamedKey::ArrowRight) => Edit::Right,
+                    Key::Named(NamedKey::Home) => Edit::Home,
+                    Key::Named(NamedKey::End) => Edit::End,
+                    // Whatever the key typed, after the keyboard layout and
+                    // dead keys are taken into account.
+                    _ => match text {
+                        Some(text) => Edit::Insert(text.to_string()),
+                        None => return,
+                    },
+                };
+                self.editor.apply(edit);
+                window.request_redraw();
+            }
+            WindowEvent::RedrawRequested => {
+                let size = window.inner_size();
+                let (Some(width), Some(height)) =
+                    (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+                else {
+                    return;
+                };
+                surface.resize(width, height).expect("Could not resize the window's buffer");
+
+                // Skia draws into its own raster surface; softbuffer wants
+                // one u32 per pixel, 0RGB.
+                let frame = self
+                    .editor
+                    .render((size.width as i32, size.height as i32), true)
+                    .and_then(|image| read_rgba(&image, AlphaType::Premul));
+                let Some(pixels) = frame else {
+                    return;
+                };
+                let mut buffer = surface.buffer_mut().expect("Could not draw to the window");
+                for (target, pixel) in buffer.iter_mut().zip(pixels.chunks(4)) {
+                    *target = u32::from_be_bytes([0, pixel[0], pixel[1], pixel[2]]);
+                }
+                buffer.present().expect("Could not draw to the window");
+            }
+            _ => {}
+        }
+    }
+}
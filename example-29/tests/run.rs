@@ -0,0 +1,86 @@
+use common::raster::read_rgba;
+use example_29::{Edit, Editor, CARET_COLOR};
+use skia_safe::AlphaType;
+use std::fs;
+
+fn font_data() -> Vec<u8> {
+    fs::read("Roboto-LightItalic.ttf").unwrap()
+}
+
+#[test]
+fn typing_moves_the_caret_along_the_text() {
+    let font_data = font_data();
+    let mut editor = Editor::new(&font_data, 32.0).unwrap();
+    assert_eq!((editor.text(), editor.caret(), editor.caret_x()), ("", 0, 0.0));
+
+    editor.apply(Edit::Insert("Hel".to_string()));
+    let after_hel = editor.caret_x();
+    editor.apply(Edit::Insert("lo".to_string()));
+    assert_eq!((editor.text(), editor.caret()), ("Hello", 5));
+    assert!(editor.caret_x() > after_hel && after_hel > 0.0);
+
+    // Backspace takes the caret back to where it was after the same text.
+    editor.apply(Edit::Backspace);
+    editor.apply(Edit::Backspace);
+    assert_eq!((editor.text(), editor.caret()), ("Hel", 3));
+    assert_eq!(editor.caret_x(), after_hel);
+
+    // Typing in the middle inserts there.
+    editor.apply(Edit::Home);
+    editor.apply(Edit::Right);
+    editor.apply(Edit::Insert("a".to_string()));
+    editor.apply(Edit::Delete);
+    assert_eq!((editor.text(), editor.caret()), ("Hal", 2));
+
+    // Control characters (Enter, Tab) aren't text in a one-line editor.
+    editor.apply(Edit::Insert("\r".to_string()));
+    assert_eq!(editor.text(), "Hal");
+}
+
+#[test]
+fn only_changes_to_the_text_reshape() {
+    let font_data = font_data();
+    let mut editor = Editor::new(&font_data, 32.0).unwrap();
+    editor.apply(Edit::Insert("coffee".to_string()));
+    let shaped = editor.shape_count();
+
+    for edit in [Edit::Left, Edit::Home, Edit::Backspace, Edit::End, Edit::Delete] {
+        editor.apply(edit);
+    }
+    editor.render((300, 80), true).unwrap();
+    assert_eq!(editor.shape_count(), shaped);
+
+    editor.apply(Edit::Backspace);
+    assert_eq!(editor.shape_count(), shaped + 1);
+}
+
+#[test]
+fn rendered_caret_follows_the_typing() {
+    let font_data = font_data();
+    let mut editor = Editor::new(&font_data, 32.0).unwrap();
+    let size = (300, 80);
+
+    // The caret is the only blue, so its leftmost column is where it's drawn.
+    let caret_column = |editor: &Editor| {
+        let pixels = read_rgba(&editor.render(size, true).unwrap(), AlphaType::Premul).unwrap();
+        let blue = [CARET_COLOR.r(), CARET_COLOR.g(), CARET_COLOR.b()];
+        (0..pixels.len() / 4)
+            .filter(|i| pixels[i * 4..i * 4 + 3] == blue)
+            .map(|i| i % size.0 as usize)
+            .min()
+            .unwrap()
+    };
+    let empty = editor.render(size, false).unwrap();
+    let start = caret_column(&editor);
+
+    editor.apply(Edit::Insert("Hi".to_string()));
+    let typed = editor.render(size, false).unwrap();
+    assert_ne!(
+        read_rgba(&typed, AlphaType::Premul),
+        read_rgba(&empty, AlphaType::Premul),
+        "the text wasn't drawn",
+    );
+    let after_hi = caret_column(&editor);
+    assert!(after_hi > start + 10, "{start} -> {after_hi}");
+    assert!((after_hi as f32 - start as f32 - editor.caret_x()).abs() <= 1.0);
+}
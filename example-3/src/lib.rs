@@ -0,0 +1,405 @@
+use common::picture::record_picture;
+use harfbuzz_rs::{
+    Face, Font as HbFont, Owned, UnicodeBuffer, shape, Direction, Language,
+};
+use skia_safe::{
+    Canvas, Color, Data, EncodedImageFormat, Font, FontMgr, Matrix, Paint, Point, Rect, Surface,
+    TextBlobBuilder,
+};
+use unicode_bidi::{BidiInfo, Level};
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Shapes and draws the right-to-left lines and returns them as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // 1. Load the font, for Skia and for HarfBuzz, at 20px.
+    let font_data = fs::read(FONT_PATH)?;
+    let (skia_font, hb_font) = load_fonts(&font_data)?;
+    let font_size = skia_font.size();
+
+    // 2. Take the lines in logical order, no manual Bidi reorder. By default
+    //    they're Arabic, and the second line mixes in European digits, which
+    //    must stay left-to-right. With --native-digits those digits become
+    //    the language's own first (see `localize_digits`).
+    let language = Language::from_str(&config.language)
+        .map_err(|_| format!("Invalid language: {}", config.language))?;
+    let lines: Vec<String> = config
+        .lines
+        .iter()
+        .map(|line| {
+            if config.native_digits {
+                localize_digits(line, &config.language)
+            } else {
+                line.clone()
+            }
+        })
+        .collect();
+
+    // 3. Shape each line run-by-run and build a Skia TextBlob from the glyphs.
+    let mut builder = TextBlobBuilder::new();
+    let mut placed_lines = Vec::new();
+    for (line_index, text) in lines.iter().enumerate() {
+        let baseline = line_index as f32 * font_size * 1.5;
+        let placed = add_bidi_line(
+            &mut builder,
+            &skia_font,
+            &hb_font,
+            text,
+            config.direction,
+            language,
+            baseline,
+        );
+        placed_lines.push((placed, baseline));
+    }
+    let text_blob = builder.make().ok_or("Failed to build text blob")?;
+
+    // 4. Draw the TextBlob onto a Skia surface.
+    let width = 500;
+    let height = 100;
+
+    // Example: place the text blob near x=50, y=40.
+    // (If you want right alignment to a specific edge, subtract the line width, etc.)
+    let origin_x = 50.0;
+    let origin_y = 40.0;
+
+    // Highlight a selection (the word "شريط" in the first line, if it's there)
+    // behind the text. The selection is a logical byte range, and in bidi text
+    // that range can map to several visually separate pieces, so we get back a
+    // list of rectangles.
+    let (selected_line, selected_baseline) = placed_lines.first().ok_or("There is no text")?;
+    let mut selected_rects = Vec::new();
+    if let Some(word_start) = lines[0].find("شريط") {
+        let selection = word_start..word_start + "شريط".len();
+        let (_, metrics) = skia_font.metrics();
+        selected_rects = selection_rects(
+            selected_line,
+            selection,
+            selected_baseline + metrics.ascent,
+            selected_baseline + metrics.descent,
+        );
+    }
+
+    // Everything below is drawn in the blob's own coordinates (the first
+    // baseline starts at 0, 0), through the --transform matrix. Because the
+    // matrix is applied after moving to the origin, a shear slants the glyphs
+    // around their baseline and a mirror flips them in place.
+    let matrix = config.transform.matrix(text_blob.bounds());
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+        canvas.save();
+        canvas.translate((origin_x, origin_y));
+        canvas.concat(&matrix);
+
+        let mut selection_paint = Paint::default();
+        selection_paint.set_color(Color::from_rgb(0xb4, 0xd5, 0xfe));
+        for &rect in &selected_rects {
+            canvas.draw_rect(rect, &selection_paint);
+        }
+
+        canvas.draw_text_blob(&text_blob, (0, 0), &Paint::default());
+        canvas.restore();
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((width, height))
+        .ok_or("Could not create surface")?;
+    draw(surface.canvas());
+
+    // 5. Encode the result as a PNG.
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+const FONT_PATH: &str = "Rubik-VariableFont_wght.ttf";
+
+/// Sets up a Skia font and a HarfBuzz font for `font_data`, both at 20px.
+fn load_fonts(font_data: &[u8]) -> Result<(Font, Owned<HbFont<'_>>), Box<dyn Error>> {
+    let skia_data = Data::new_copy(font_data);
+    let font_mgr = FontMgr::new();
+    let typeface = font_mgr
+        .new_from_data(&skia_data, None)
+        .ok_or("Failed to load typeface")?;
+    let font_size = 20.0;
+    let mut skia_font = Font::default();
+    skia_font.set_size(font_size);
+    skia_font.set_typeface(typeface);
+    skia_font.set_edging(skia_safe::font::Edging::SubpixelAntiAlias);
+
+    // The HarfBuzz font at the same size. HarfBuzz uses 26.6 fixed-point
+    // units, so multiply by 64.
+    let hb_face = Face::from_bytes(font_data, 0);
+    let mut hb_font = HbFont::new(hb_face);
+    let hb_scale = (font_size * 64.0) as i32;
+    hb_font.set_scale(hb_scale, hb_scale);
+
+    // (Optional) You can also set "pixels per EM" (PPEM) for hinting:
+    // hb_font.set_ppem(font_size as u32, font_size as u32);
+    Ok((skia_font, hb_font))
+}
+
+/// A glyph placed on a line, in visual (left-to-right) order.
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedGlyph {
+    /// Byte offset into the line's text of the cluster this glyph belongs to.
+    pub cluster: usize,
+    pub x: f32,
+    pub advance: f32,
+}
+
+/// Shapes `text` as one line the way `run` does (with `config`'s direction
+/// and language) and returns its glyphs in visual order, starting at x = 0.
+pub fn place_line(config: &Config, text: &str) -> Result<Vec<PlacedGlyph>, Box<dyn Error>> {
+    let font_data = fs::read(FONT_PATH)?;
+    let (skia_font, hb_font) = load_fonts(&font_data)?;
+    let language = Language::from_str(&config.language)
+        .map_err(|_| format!("Invalid language: {}", config.language))?;
+    let mut builder = TextBlobBuilder::new();
+    Ok(add_bidi_line(
+        &mut builder,
+        &skia_font,
+        &hb_font,
+        text,
+        config.direction,
+        language,
+        0.0,
+    ))
+}
+
+/// Shapes one line (a paragraph of its own) and appends it to `builder`.
+/// Returns where each glyph ended up, for hit-testing and selection.
+///
+/// Shaping the whole line as a single RTL buffer would also reverse any
+/// European digits in it ("2024" would come out as "4202"). Instead we run the
+/// Unicode Bidirectional Algorithm first (see `visual_runs`), which hands us
+/// the directional runs already in visual (left-to-right) order, and shape
+/// each one in its own direction. HarfBuzz picks each run's script from its
+/// text (Arabic, Hebrew, Latin, ...), and RTL runs are shaped in `language`.
+fn add_bidi_line(
+    builder: &mut TextBlobBuilder,
+    skia_font: &Font,
+    hb_font: &HbFont,
+    text: &str,
+    direction: Option<Level>,
+    language: Language,
+    baseline: f32,
+) -> Vec<PlacedGlyph> {
+    let mut x_accum = 0.0;
+    let mut placed = Vec::new();
+
+    for (run, level) in visual_runs(text, direction) {
+        let run_start = run.start;
+        let hb_buffer = if level.is_rtl() {
+            UnicodeBuffer::new()
+                .add_str(&text[run])
+                .set_direction(Direction::Rtl)
+                .set_language(language)
+                .guess_segment_properties()
+        } else {
+            // An LTR run can be digits or embedded English.
+            UnicodeBuffer::new()
+                .add_str(&text[run])
+                .set_direction(Direction::Ltr)
+                .guess_segment_properties()
+        };
+
+        let shaped_result = shape(hb_font, hb_buffer, &[]);
+        let glyph_infos = shaped_result.get_glyph_infos();
+        let glyph_positions = shaped_result.get_glyph_positions();
+
+        let count = glyph_infos.len();
+        if count == 0 {
+            continue;
+        }
+        // alloc_run_pos: pass None for the optional bounding box.
+        let (glyphs, positions) = builder.alloc_run_pos(skia_font, count, None);
+
+        // HarfBuzz already returns RTL glyphs in visual order, so we can keep
+        // walking left-to-right across runs.
+        for i in 0..count {
+            glyphs[i] = glyph_infos[i].codepoint as u16;
+
+            // HarfBuzz returns x_offset, x_advance, etc. in 26.6 fixed point => divide by 64.0.
+            let x_offset = glyph_positions[i].x_offset as f32 / 64.0;
+            let y_offset = glyph_positions[i].y_offset as f32 / 64.0;
+            let x_advance = glyph_positions[i].x_advance as f32 / 64.0;
+
+            positions[i] = Point::new(x_accum + x_offset, baseline + y_offset);
+            placed.push(PlacedGlyph {
+                // HarfBuzz clusters are byte offsets into the run we shaped.
+                cluster: run_start + glyph_infos[i].cluster as usize,
+                x: x_accum,
+                advance: x_advance,
+            });
+            x_accum += x_advance;
+        }
+    }
+    placed
+}
+
+/// Runs the Unicode Bidirectional Algorithm (UAX #9) over `text` as one
+/// paragraph, and returns its directional runs in visual (left-to-right)
+/// order with their embedding levels. `direction` is the paragraph direction,
+/// or `None` to take it from the first strong character.
+///
+/// This is unicode-bidi's implementation, which covers the whole algorithm
+/// for a single line:
+/// - numbers: digits next to RTL text get an even level (rules W1-W7 and
+///   I2), so "2024" stays left-to-right inside Arabic;
+/// - explicit embeddings and overrides (LRE, RLE, LRO, RLO, PDF);
+/// - isolates (LRI, RLI, FSI, PDI): the text between an initiator and its
+///   PDI resolves its own direction, and to the text around it the whole
+///   isolate is a single neutral character. So a number after an isolated
+///   Arabic phrase in English stays with the English, where without the
+///   isolate it would be pulled into the Arabic run. The control characters
+///   themselves go back to the paragraph level (rule L1), and HarfBuzz draws
+///   them as nothing.
+///
+/// Not covered here: lines wrapped out of one paragraph (see example-7,
+/// which reorders per line), and mirroring brackets like "(" in RTL runs,
+/// which HarfBuzz does while shaping.
+pub fn visual_runs(text: &str, direction: Option<Level>) -> Vec<(Range<usize>, Level)> {
+    let bidi_info = BidiInfo::new(text, direction);
+    let mut visual = Vec::new();
+    for paragraph in &bidi_info.paragraphs {
+        let (levels, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+        visual.extend(runs.into_iter().map(|run| {
+            let level = levels[run.start];
+            (run, level)
+        }));
+    }
+    visual
+}
+
+/// Replaces the ASCII digits in `text` with the digits used for `language`
+/// (a BCP 47 tag like "ar" or "fa-IR"): Arabic-Indic digits (٠١٢...) for
+/// Arabic, and the Extended Arabic-Indic ones (۰۱۲...) for Persian and Urdu,
+/// whose 4, 5 and 6 look different. Text in any other language is returned
+/// as it is.
+///
+/// This is a localization transform, not part of shaping. The native digits
+/// are different characters from the ASCII ones, not alternate glyphs for
+/// them, so the change happens to the text, before HarfBuzz sees it, and
+/// anything that reads the text afterwards (selection, copy and paste,
+/// search) gets the native digits too. There's no OpenType feature that
+/// could do it instead: a font may have both sets of digits, but only the
+/// text can say which one is meant.
+///
+/// Both kinds of digits still read left-to-right inside RTL text, so the
+/// bidi runs come out the same either way.
+pub fn localize_digits(text: &str, language: &str) -> String {
+    let zero = match language.split(['-', '_']).next().unwrap_or_default() {
+        "ar" => '\u{0660}',
+        "fa" | "ur" => '\u{06F0}',
+        _ => return text.to_string(),
+    };
+    text.chars()
+        .map(|c| match c {
+            '0'..='9' => char::from_u32(zero as u32 + (c as u32 - '0' as u32)).unwrap(),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Computes the highlight rectangles for the logical byte range `selection`.
+///
+/// In RTL (and mixed-direction) text a contiguous logical range is not always
+/// visually contiguous: when it crosses a direction boundary it splits into
+/// several pieces. We walk the glyphs in visual order and start a new rectangle
+/// whenever we go from unselected to selected. A glyph counts as selected when
+/// its cluster starts inside the range, so a ligature is selected as a whole.
+pub fn selection_rects(
+    glyphs: &[PlacedGlyph],
+    selection: Range<usize>,
+    top: f32,
+    bottom: f32,
+) -> Vec<Rect> {
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut extending = false;
+    for glyph in glyphs {
+        if !selection.contains(&glyph.cluster) {
+            extending = false;
+            continue;
+        }
+        match rects.last_mut() {
+            Some(rect) if extending => rect.right = glyph.x + glyph.advance,
+            _ => rects.push(Rect::new(glyph.x, top, glyph.x + glyph.advance, bottom)),
+        }
+        extending = true;
+    }
+    rects
+}
+
+/// A transform for the whole text block, given with `--transform`.
+#[derive(Debug, Clone, Copy)]
+pub enum Transform {
+    Identity,
+    /// Flip horizontally within the text's own bounds.
+    Mirror,
+    /// Slant the glyphs to the right, like a faux italic.
+    Shear,
+    /// Any affine matrix, e.g. `--matrix 1,-0.2,0,0,1,0`.
+    Custom(Matrix),
+}
+
+impl Transform {
+    /// Returns the matrix for this transform; `bounds` are the text's bounds
+    /// in its own coordinates.
+    fn matrix(&self, bounds: &Rect) -> Matrix {
+        match self {
+            Transform::Identity => Matrix::new_identity(),
+            Transform::Mirror => {
+                // x -> left + right - x keeps the mirrored text where it was.
+                let mut matrix = Matrix::scale((-1.0, 1.0));
+                matrix.post_translate((bounds.left + bounds.right, 0.0));
+                matrix
+            }
+            // y is negative above the baseline, so a negative x skew moves the
+            // tops of the glyphs right (about 12 degrees) and keeps the baseline.
+            Transform::Shear => Matrix::skew((-0.21, 0.0)),
+            Transform::Custom(matrix) => *matrix,
+        }
+    }
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub transform: Transform,
+    /// The lines to draw, in logical order.
+    pub lines: Vec<String>,
+    /// The direction of every line, or `None` to detect it per line.
+    pub direction: Option<Level>,
+    /// The language of the RTL text, as a BCP 47 tag, e.g. "ar" or "fa".
+    pub language: String,
+    /// Show ASCII digits as the language's native digits, see
+    /// `localize_digits`.
+    pub native_digits: bool,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            transform: Transform::Identity,
+            lines: vec![
+                "يحتوي على شريط التمرير على الجانب الأيمن".to_string(),
+                "عدد 2024".to_string(),
+            ],
+            direction: Some(Level::rtl()),
+            language: "ar".to_string(),
+            native_digits: false,
+            dump_picture: None,
+        }
+    }
+}
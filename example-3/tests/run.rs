@@ -0,0 +1,175 @@
+use common::raster::read_rgba;
+use example_3::{
+    localize_digits, place_line, run, selection_rects, visual_runs, Config, Transform,
+};
+use skia_safe::{AlphaType, Data, Image};
+use unicode_bidi::Level;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+/// The pixels of the image `run` draws, as RGBA, and its width.
+fn pixels(config: &Config) -> (Vec<u8>, usize) {
+    let png = run(config).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+    (read_rgba(&image, AlphaType::Premul).unwrap(), image.width() as usize)
+}
+
+#[test]
+fn mirror_flips_the_text_in_place() {
+    // The first and last columns with dark pixels in them.
+    let ink_columns = |config: &Config| {
+        let (pixels, width) = pixels(config);
+        let columns: Vec<usize> = pixels
+            .chunks(4)
+            .enumerate()
+            .filter(|(_, p)| p[0] < 128)
+            .map(|(i, _)| i % width)
+            .collect();
+        (*columns.iter().min().unwrap(), *columns.iter().max().unwrap())
+    };
+    let mirrored = Config {
+        transform: Transform::Mirror,
+        ..Config::default()
+    };
+    assert_ne!(pixels(&mirrored), pixels(&Config::default()));
+    // Flipped within its own bounds, the text covers as wide a stretch.
+    let (left, right) = ink_columns(&Config::default());
+    let (mirrored_left, mirrored_right) = ink_columns(&mirrored);
+    assert!(
+        ((mirrored_right - mirrored_left) as i32 - (right - left) as i32).abs() <= 2,
+        "{mirrored_left}..{mirrored_right} vs {left}..{right}"
+    );
+}
+
+/// The mean x of the dark pixels in the topmost `rows` rows that have any,
+/// i.e. along the tops of the tallest letters.
+fn top_ink_x(config: &Config, rows: usize) -> f32 {
+    let (pixels, width) = pixels(config);
+    let xs: Vec<usize> = pixels
+        .chunks(4 * width)
+        .map(|row| (0..width).filter(|x| row[x * 4] < 128).collect::<Vec<_>>())
+        .filter(|xs| !xs.is_empty())
+        .take(rows)
+        .flatten()
+        .collect();
+    xs.iter().sum::<usize>() as f32 / xs.len() as f32
+}
+
+#[test]
+fn shear_slants_the_tops_of_the_letters_right() {
+    // The shear keeps the baseline in place, so everything above it moves
+    // right, more the higher it is.
+    let upright = top_ink_x(&Config::default(), 3);
+    let sheared = top_ink_x(
+        &Config {
+            transform: Transform::Shear,
+            ..Config::default()
+        },
+        3,
+    );
+    assert!(sheared > upright + 1.0, "{sheared} vs {upright}");
+}
+
+/// The runs of `text` in an LTR paragraph, in visual order, as (text, RTL?).
+fn runs(text: &str) -> Vec<(&str, bool)> {
+    visual_runs(text, Some(Level::ltr()))
+        .into_iter()
+        .map(|(range, level)| (&text[range], level.is_rtl()))
+        .collect()
+}
+
+#[test]
+fn digits_stay_left_to_right_inside_arabic() {
+    // The digits after an Arabic word are Arabic numbers (rule W2), one
+    // level above the Arabic: a left-to-right run of their own, placed
+    // before (to the left of) the word instead of being reversed with it.
+    assert_eq!(runs("عدد 2024"), [("2024", false), ("عدد ", true)]);
+
+    // The same in an RTL paragraph, where the digits are still at an even
+    // level and come out in the order they were typed.
+    let text = "عدد 2024";
+    let rtl_runs: Vec<(&str, u8)> = visual_runs(text, Some(Level::rtl()))
+        .into_iter()
+        .map(|(range, level)| (&text[range], level.number()))
+        .collect();
+    assert_eq!(rtl_runs, [("2024", 2), ("عدد ", 1)]);
+}
+
+#[test]
+fn isolated_arabic_phrase_keeps_the_number_after_it_in_english() {
+    // Without the isolate, "5" follows Arabic and joins its run, and ends
+    // up to the left of the phrase.
+    assert_eq!(
+        runs("He said مرحبا 5 times"),
+        [("He said ", false), ("5", false), ("مرحبا ", true), (" times", false)],
+    );
+
+    // RLI ... PDI resolves the phrase on its own, and the number stays in
+    // the English after it. The controls themselves are at the paragraph's
+    // level.
+    assert_eq!(
+        runs("He said \u{2067}مرحبا\u{2069} 5 times"),
+        [("He said \u{2067}", false), ("مرحبا", true), ("\u{2069} 5 times", false)],
+    );
+
+    let config = Config {
+        lines: vec!["He said \u{2067}مرحبا\u{2069} 5 times".to_string()],
+        direction: Some(Level::ltr()),
+        ..Config::default()
+    };
+    assert!(run(&config).unwrap().starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn native_digits_draw_2024_in_arabic_indic() {
+    assert_eq!(localize_digits("عدد 2024", "ar"), "عدد ٢٠٢٤");
+    assert_eq!(localize_digits("2024", "fa-IR"), "۲۰۲۴");
+    assert_eq!(localize_digits("2024", "en"), "2024");
+
+    // With the option on, "2024" comes out exactly like "٢٠٢٤" typed in.
+    let line = |text: &str, native_digits| Config {
+        lines: vec![format!("عدد {text}")],
+        native_digits,
+        ..Config::default()
+    };
+    let localized = run(&line("2024", true)).unwrap();
+    assert_eq!(localized, run(&line("٢٠٢٤", false)).unwrap());
+    assert_ne!(localized, run(&line("2024", false)).unwrap());
+}
+
+#[test]
+fn selecting_an_arabic_word_covers_just_its_glyphs() {
+    let config = Config::default();
+    let text = &config.lines[0];
+    let glyphs = place_line(&config, text).unwrap();
+    let word = |word: &str| {
+        let start = text.find(word).unwrap();
+        start..start + word.len()
+    };
+
+    let selection = word("شريط");
+    let rects = selection_rects(&glyphs, selection.clone(), -20.0, 5.0);
+    assert_eq!(rects.len(), 1, "{rects:?}");
+    let rect = rects[0];
+    for glyph in &glyphs {
+        let inside = glyph.x >= rect.left - 0.01 && glyph.x + glyph.advance <= rect.right + 0.01;
+        assert_eq!(inside, selection.contains(&glyph.cluster), "{glyph:?} vs {rect:?}");
+    }
+
+    // The word runs right to left: its first letter is its rightmost glyph,
+    // and the word before it ("على") is further right still.
+    let clusters: Vec<usize> = glyphs
+        .iter()
+        .filter(|glyph| selection.contains(&glyph.cluster))
+        .map(|glyph| glyph.cluster)
+        .collect();
+    assert!(clusters.windows(2).all(|pair| pair[0] >= pair[1]), "{clusters:?}");
+    let before = selection_rects(&glyphs, word("على"), -20.0, 5.0);
+    assert!(before[0].left >= rect.right - 0.01, "{before:?} vs {rect:?}");
+}
@@ -0,0 +1,778 @@
+use common::blob::{build_blob, FontRun};
+use common::cache::ShapeCache;
+use common::features::{supports_feature, tag_from_bytes};
+use common::layer::{draw_layers, TextLayer};
+use common::monospace::cell_width;
+use common::picture::record_picture;
+use common::font::LoadedFont;
+use common::shaping::{kerning_adjustment, shape_text, GlyphRun};
+use common::svg_glyph::SvgGlyphs;
+use harfbuzz_rs::{
+    Feature, UnicodeBuffer, GlyphInfo, GlyphPosition, Tag,
+    shape
+};
+use skia_safe::{
+    canvas::SaveLayerRec, color_filters, svg, Canvas, Color, ColorMatrix, EncodedImageFormat,
+    FontMgr, Paint, Point, Surface,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Shapes the text with font fallback, draws it and returns it as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    // -------------------------------------------------
+    // 1. Load and create HarfBuzz + Skia fonts
+    // -------------------------------------------------
+
+    // The primary font is baked into the binary, the fallback is read at runtime.
+    // Either way we end up with a byte slice, and `LoadedFont` builds both the
+    // HarfBuzz font (for shaping) and the Skia typeface (for drawing) from it.
+    let primary_data: &[u8] = include_bytes!("../Roboto-LightItalic.ttf"); // or any Latin-capable font
+    let fallback_data = fs::read(&config.fallback_font_path)?; // or any emoji-capable font
+
+    // We'll shape at 20px => 20 * 64 = 1280 in 26.6 fixed point
+    let px_size = 20.0;
+    let primary = LoadedFont::from_bytes(primary_data, px_size)?;
+    let fallback = LoadedFont::from_bytes(&fallback_data, px_size)?;
+    // Optional: set_ppem if you want hinting
+    // primary.hb_font.set_ppem(px_size as u32, px_size as u32);
+
+    let hb_font_primary = &primary.hb_font;
+    let skia_font_primary = primary.skia_font();
+    let skia_font_fallback = fallback.skia_font();
+
+    // Optional OpenType features, e.g. `--frac` for nice fractions, for the
+    // whole text or (with --feature 'smcp[0:5]') just part of it.
+    let features = requested_features(config, primary_data);
+
+    // With --script, every run is shaped as that script.
+    let script = config.script.map(tag_from_bytes);
+
+    // -------------------------------------------------
+    // 2. Example string with emojis
+    // -------------------------------------------------
+    let text = config.text.as_str();
+
+    // With --measure-bench N, time measuring the text N times, with and
+    // without a shaped-width cache.
+    if let Some(iterations) = config.measure_bench {
+        measure_bench(&primary, text, iterations);
+    }
+
+    // With --kern-pair, report how the primary font kerns a pair like "AV".
+    if let Some(pair) = &config.kern_pair {
+        let adjustment = kerning_adjustment(hb_font_primary, pair);
+        eprintln!("Kerning for {pair:?} at {px_size}px: {adjustment:+.2}px");
+    }
+
+    // -------------------------------------------------
+    // 3. Shape with the primary font, falling back per cluster
+    // -------------------------------------------------
+    let shaped_runs = shape_with_fallback(&primary, &fallback, text, script, &features);
+
+    // Grapheme clusters, for counting and for the grid layout below (so we
+    // don't split a multi-codepoint emoji across cells).
+    let graphemes: Vec<(usize, &str)> = text.grapheme_indices(true).collect();
+
+    let glyph_count: usize = shaped_runs.iter().map(|run| run.infos.len()).sum();
+    eprintln!("Shaped {} clusters into {} glyphs", graphemes.len(), glyph_count);
+
+    // The unique glyph ids per font are exactly what a font subsetter needs
+    // (e.g. to embed only the used glyphs in a PDF); `used_glyphs` returns
+    // them.
+    let used = UsedGlyphs::of(&shaped_runs);
+    eprintln!("Primary glyphs used: {:?}", used.primary);
+    eprintln!("Fallback glyphs used: {:?}", used.fallback);
+
+    // -------------------------------------------------
+    // 4. Build a single Skia TextBlob from these runs
+    // -------------------------------------------------
+    // A blob run has a single font, which is exactly how we shaped them: one
+    // blob run per shaped run. `build_blob` lays the runs out one after
+    // another, so each run's glyph positions are relative to where the run
+    // starts, and its width is how far the next run starts after it.
+
+    // In grid mode every cluster snaps to a fixed-width cell, like a terminal.
+    // The cell is as wide as the primary font's "0"; wide clusters (most emoji)
+    // take two cells. Proportional mode just uses the shaped advances.
+    let cell_advance = {
+        let (_, zero_positions) = shape_range(hb_font_primary, "0", 0..1, None, &[]);
+        zero_positions.iter().map(|pos| pos.x_advance as f32 / 64.0).sum::<f32>()
+    };
+    // Where each cluster's first cell starts, keyed by the cluster's byte
+    // offset, and where the last one ends.
+    let mut cell_x = BTreeMap::new();
+    let mut column = 0.0;
+    for &(start, cluster) in &graphemes {
+        cell_x.insert(start, column);
+        column += cell_advance * cell_width(cluster) as f32;
+    }
+    cell_x.insert(text.len(), column);
+
+    // Glyphs the fallback font has SVG documents for (SVG-in-OpenType color
+    // fonts). Skia draws these as their plain outlines, so instead of putting
+    // them in the blob we render their SVG documents separately, on top.
+    // Their positions are from the start of the baseline, and each comes
+    // with its font's units per em, which the document is drawn in. Each
+    // font's SVG table is parsed once, for all of its glyphs.
+    let primary_svg = SvgGlyphs::new(primary_data);
+    let fallback_svg = SvgGlyphs::new(&fallback_data);
+    let mut svg_glyphs: Vec<(&[u8], Point, u16)> = Vec::new();
+
+    // Where the current run starts, from the start of the line.
+    let mut run_x = 0.0;
+    let mut font_runs = Vec::new();
+    for run in &shaped_runs {
+        // Pick the matching Skia font
+        let (skfont, svg_font) = match run.font {
+            FontChoice::Primary => (&skia_font_primary, &primary_svg),
+            FontChoice::Fallback => (&skia_font_fallback, &fallback_svg),
+        };
+
+        // The glyphs of this run that go into the blob.
+        let mut glyph_run = GlyphRun {
+            glyphs: Vec::new(),
+            positions: Vec::new(),
+            clusters: Vec::new(),
+            width: 0.0,
+        };
+
+        // The pen position, from the start of the run. In grid mode we also
+        // track how far we are into the current cluster, since a cluster can
+        // be made of several glyphs.
+        let mut pen_x = 0.0;
+        let mut current_cluster = None;
+        let mut cluster_x = 0.0;
+        for (info, pos) in run.infos.iter().zip(&run.positions) {
+            // HarfBuzz returns positions in 26.6 fixed -> /64.0. Its y axis
+            // points up, Skia's points down.
+            let x_offset = pos.x_offset as f32 / 64.0;
+            let y_offset = pos.y_offset as f32 / 64.0;
+            let x_advance = pos.x_advance as f32 / 64.0;
+
+            // HarfBuzz clusters are byte offsets into the run's text.
+            let cluster = run.start + info.cluster as usize;
+            if current_cluster != Some(cluster) {
+                current_cluster = Some(cluster);
+                cluster_x = 0.0;
+            }
+
+            let glyph_x = match config.layout {
+                LayoutMode::Proportional => pen_x,
+                LayoutMode::Grid => {
+                    let (_, &cell_start) = cell_x.range(..=cluster).next_back().unwrap();
+                    cell_start - run_x + cluster_x
+                }
+            };
+            let glyph_id = info.codepoint as u16;
+            let position = Point::new(glyph_x + x_offset, -y_offset);
+            match svg_font.document(glyph_id).zip(svg_font.units_per_em()) {
+                Some((document, upem)) => {
+                    svg_glyphs.push((document, position + Point::new(run_x, 0.0), upem))
+                }
+                None => {
+                    glyph_run.glyphs.push(glyph_id);
+                    glyph_run.positions.push(position);
+                    glyph_run.clusters.push(cluster as u32);
+                }
+            }
+
+            pen_x += x_advance;
+            cluster_x += x_advance;
+        }
+
+        // The next run starts after this one's advances, or in grid mode at
+        // the cell after this run's last cluster.
+        glyph_run.width = match config.layout {
+            LayoutMode::Proportional => pen_x,
+            LayoutMode::Grid => {
+                let (_, &end_x) = cell_x.range(..=run.end).next_back().unwrap();
+                end_x - run_x
+            }
+        };
+        run_x += glyph_run.width;
+        font_runs.push(FontRun {
+            font: skfont.clone(),
+            glyphs: glyph_run,
+        });
+    }
+
+    // The blob is empty if every glyph was an SVG glyph.
+    //
+    // With --grayscale-emoji, the fallback runs need their own paint, and a
+    // blob is drawn with a single paint. So we build two blobs instead, one
+    // with each font's glyphs. Glyphs left out of a blob still count for the
+    // width of their run, so the rest land where they would in the single
+    // one. `blob_where` keeps the glyphs whose font and cluster pass `keep`.
+    let blob_where = |keep: &dyn Fn(FontChoice, usize) -> bool| {
+        let runs: Vec<FontRun> = font_runs
+            .iter()
+            .zip(&shaped_runs)
+            .map(|(font_run, shaped)| {
+                let mut kept = font_run.clone();
+                kept.glyphs.glyphs.clear();
+                kept.glyphs.positions.clear();
+                kept.glyphs.clusters.clear();
+                let glyph_run = &font_run.glyphs;
+                for (i, &cluster) in glyph_run.clusters.iter().enumerate() {
+                    if keep(shaped.font, cluster as usize) {
+                        kept.glyphs.glyphs.push(glyph_run.glyphs[i]);
+                        kept.glyphs.positions.push(glyph_run.positions[i]);
+                        kept.glyphs.clusters.push(cluster);
+                    }
+                }
+                kept
+            })
+            .collect();
+        build_blob(&runs)
+    };
+    let blob_for = |font: FontChoice| blob_where(&|choice, _| choice == font);
+    let (text_blob, emoji_blob) = if config.grayscale_emoji {
+        (blob_for(FontChoice::Primary), blob_for(FontChoice::Fallback))
+    } else {
+        (build_blob(&font_runs), None)
+    };
+
+    // -------------------------------------------------
+    // 5. Draw to a Skia surface
+    // -------------------------------------------------
+    let width = 300;
+    let height = 120;
+
+    // The SVG glyphs are parsed up front, so drawing can't fail. Their
+    // documents are drawn in font units with the origin on the baseline (and
+    // y pointing down, like the rest of SVG), so we only have to move to the
+    // glyph's position and scale units to pixels.
+    let mut svg_doms = Vec::new();
+    for &(document, position, upem) in &svg_glyphs {
+        let upem = upem as f32;
+        let mut dom = svg::Dom::from_bytes(document, FontMgr::new())?;
+        dom.set_container_size((upem, upem));
+        svg_doms.push((dom, position, px_size / upem));
+    }
+
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+
+        // In grid mode, draw the cell boundaries so the alignment is easy to check.
+        if config.layout == LayoutMode::Grid {
+            let mut grid_paint = Paint::default();
+            grid_paint.set_color(Color::from_argb(0x40, 0x00, 0x00, 0xff));
+            let mut x = 50.0;
+            while x < width as f32 {
+                canvas.draw_line((x, 50.0), (x, 80.0), &grid_paint);
+                x += cell_advance;
+            }
+        }
+
+        // The emoji paint desaturates whatever it draws: a color filter applies
+        // to the color bitmap glyphs just like to a flat paint color.
+        let mut desaturate = ColorMatrix::default();
+        desaturate.set_saturation(0.0);
+        let mut emoji_paint = Paint::default();
+        if config.grayscale_emoji {
+            emoji_paint.set_color_filter(color_filters::matrix(&desaturate, None));
+        }
+
+        // Just draw the entire text_blob, with its baseline at y = 75.
+        let origin = Point::new(50.0, 75.0);
+        let mut paint = Paint::default();
+        if let Some(overlap) = config.overlap {
+            // With --overlap, every word is a layer of its own, pulled `overlap`
+            // pixels further left than the one before so that they pile up. The
+            // z-index makes each word cover the end of the previous one, rather
+            // than leaving it to the order the layers happen to be drawn in.
+            let word_starts = word_starts(text);
+            // Words count from 1; anything before the first word goes with it.
+            let word_of = |cluster| word_starts.partition_point(|&start| start <= cluster).max(1);
+            let mut layers = Vec::new();
+            for word in 1..=word_starts.len() {
+                let mut word_paint = Paint::default();
+                word_paint.set_anti_alias(true);
+                word_paint.set_color(WORD_COLORS[(word - 1) % WORD_COLORS.len()]);
+                let word_origin = origin - Point::new((word - 1) as f32 * overlap, 0.0);
+                // The emoji go in a layer of their own, with the emoji paint.
+                let word_layers = [
+                    (FontChoice::Primary, word_paint),
+                    (FontChoice::Fallback, emoji_paint.clone()),
+                ];
+                for (font, paint) in word_layers {
+                    let blob =
+                        blob_where(&|choice, cluster| choice == font && word_of(cluster) == word);
+                    if let Some(blob) = blob {
+                        layers.push(TextLayer {
+                            blob,
+                            origin: word_origin,
+                            paint,
+                            z_index: word as i32,
+                        });
+                    }
+                }
+            }
+            draw_layers(canvas, &layers);
+        } else if config.rainbow {
+            // With --rainbow, each cluster is drawn on its own, with a paint
+            // colored by where the cluster starts along the line. A blob can't
+            // do that: it is drawn with a single paint. Drawing by cluster
+            // rather than by glyph keeps a base letter and its marks (or the
+            // pieces of an emoji) the same color. `run_x` is now the width of
+            // the whole line.
+            let line_width = run_x.max(1.0);
+            let mut start_x = 0.0;
+            for (font_run, shaped) in font_runs.iter().zip(&shaped_runs) {
+                let glyph_run = &font_run.glyphs;
+                // With --grayscale-emoji, the emoji blob draws the fallback runs.
+                if !(config.grayscale_emoji && shaped.font == FontChoice::Fallback) {
+                    for range in cluster_ranges(&glyph_run.clusters) {
+                        let positions = &glyph_run.positions[range.clone()];
+                        let t = (start_x + positions[0].x) / line_width;
+                        paint.set_color(gradient_color(&RAINBOW, t));
+                        canvas.draw_glyphs_at(
+                            &glyph_run.glyphs[range],
+                            positions,
+                            origin + Point::new(start_x, 0.0),
+                            &font_run.font,
+                            &paint,
+                        );
+                    }
+                }
+                start_x += glyph_run.width;
+            }
+        } else if let Some(text_blob) = &text_blob {
+            canvas.draw_text_blob(text_blob, origin, &paint);
+        }
+
+        if let (Some(emoji_blob), None) = (&emoji_blob, config.overlap) {
+            canvas.draw_text_blob(emoji_blob, origin, &emoji_paint);
+        }
+
+        // Then the SVG glyphs.
+        if !svg_doms.is_empty() {
+            // An SVG document draws with its own paints, so to desaturate it we
+            // draw it into a layer and filter the layer instead.
+            if config.grayscale_emoji {
+                canvas.save_layer(&SaveLayerRec::default().paint(&emoji_paint));
+            }
+            for (dom, position, scale) in &svg_doms {
+                canvas.save();
+                canvas.translate(origin + *position);
+                canvas.scale((*scale, *scale));
+                dom.render(canvas);
+                canvas.restore();
+            }
+            if config.grayscale_emoji {
+                canvas.restore();
+            }
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((width, height))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    // Save result
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// The colors of `--rainbow`, from the start of the line to the end.
+const RAINBOW: [Color; 5] = [
+    Color::from_rgb(0xe0, 0x20, 0x20),
+    Color::from_rgb(0xf0, 0x90, 0x00),
+    Color::from_rgb(0x20, 0xa0, 0x30),
+    Color::from_rgb(0x20, 0x50, 0xe0),
+    Color::from_rgb(0x90, 0x30, 0xc0),
+];
+
+/// The color at `t` (0 to 1) along a linear gradient through `stops`, which
+/// are evenly spaced. Needs at least two stops.
+fn gradient_color(stops: &[Color], t: f32) -> Color {
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+    let (from, to) = (stops[index], stops[index + 1]);
+    let f = scaled - index as f32;
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f).round() as u8;
+    Color::from_rgb(mix(from.r(), to.r()), mix(from.g(), to.g()), mix(from.b(), to.b()))
+}
+
+/// The colors of the words with `--overlap`, in turn.
+const WORD_COLORS: [Color; 3] = [
+    Color::from_rgb(0x20, 0x50, 0xe0),
+    Color::from_rgb(0xe0, 0x40, 0x30),
+    Color::from_rgb(0x20, 0xa0, 0x60),
+];
+
+/// The byte offsets where the whitespace-separated words of `text` start.
+fn word_starts(text: &str) -> Vec<usize> {
+    let mut previous_is_space = true;
+    let mut starts = Vec::new();
+    for (i, c) in text.char_indices() {
+        if previous_is_space && !c.is_whitespace() {
+            starts.push(i);
+        }
+        previous_is_space = c.is_whitespace();
+    }
+    starts
+}
+
+/// Splits a run's glyphs into clusters: ranges of consecutive glyphs with the
+/// same cluster value.
+fn cluster_ranges(clusters: &[u32]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (i, cluster) in clusters.iter().enumerate() {
+        match ranges.last_mut() {
+            Some(range) if clusters[range.start] == *cluster => range.end = i + 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
+/// Shapes the text like `run` does, with its features, and returns the glyph
+/// ids in order.
+pub fn glyph_ids(config: &Config) -> Result<Vec<u32>, Box<dyn Error>> {
+    Ok(shape_config(config)?
+        .iter()
+        .flat_map(|run| run.infos.iter().map(|info| info.codepoint))
+        .collect())
+}
+
+/// Shapes the text like `run` does, with its features, and returns the sum
+/// of its advances in pixels (at 20px).
+pub fn advance_width(config: &Config) -> Result<f32, Box<dyn Error>> {
+    let advance: i32 = shape_config(config)?
+        .iter()
+        .flat_map(|run| run.positions.iter().map(|pos| pos.x_advance))
+        .sum();
+    Ok(advance as f32 / 64.0)
+}
+
+/// Shapes the text like `run` does, with its features, and returns the
+/// glyphs each font drew.
+pub fn used_glyphs(config: &Config) -> Result<UsedGlyphs, Box<dyn Error>> {
+    Ok(UsedGlyphs::of(&shape_config(config)?))
+}
+
+/// Shapes `config.text` at 20px with its features and font fallback.
+fn shape_config(config: &Config) -> Result<Vec<ShapedRun>, Box<dyn Error>> {
+    let primary_data: &[u8] = include_bytes!("../Roboto-LightItalic.ttf");
+    let primary = LoadedFont::from_bytes(primary_data, 20.0)?;
+    let fallback_data = fs::read(&config.fallback_font_path)?;
+    let fallback = LoadedFont::from_bytes(&fallback_data, 20.0)?;
+    let features = requested_features(config, primary_data);
+    let script = config.script.map(tag_from_bytes);
+    Ok(shape_with_fallback(&primary, &fallback, &config.text, script, &features))
+}
+
+/// Collects `config.features` (for the whole text) and
+/// `config.ranged_features` as `(tag, byte range)` pairs, leaving out (with
+/// a warning) the ones the primary font doesn't have. Only the primary font
+/// is checked; emoji fonts don't have typographic features.
+fn requested_features(config: &Config, primary_data: &[u8]) -> Vec<([u8; 4], Range<usize>)> {
+    let whole_text = config.features.iter().map(|&tag| (tag, 0..config.text.len()));
+    whole_text
+        .chain(config.ranged_features.iter().cloned())
+        .filter(|(tag, _)| {
+            let supported = supports_feature(primary_data, *tag);
+            if !supported {
+                eprintln!(
+                    "Skipping '{}': the primary font doesn't support it",
+                    String::from_utf8_lossy(tag)
+                );
+            }
+            supported
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of `text` that the primary font can't render and
+/// that get shaped with the fallback font instead.
+pub fn fallback_ranges(text: &str) -> Result<Vec<Range<usize>>, Box<dyn Error>> {
+    let primary = LoadedFont::from_bytes(include_bytes!("../Roboto-LightItalic.ttf"), 20.0)?;
+    let fallback_data = fs::read("NotoColorEmoji-Regular.ttf")?;
+    let fallback = LoadedFont::from_bytes(&fallback_data, 20.0)?;
+    let shaped_runs = shape_with_fallback(&primary, &fallback, text, None, &[]);
+    Ok(shaped_runs
+        .iter()
+        .filter(|run| run.font == FontChoice::Fallback)
+        .map(|run| run.start..run.end)
+        .collect())
+}
+
+/// Shapes `text` with the primary font, then reshapes just the clusters that
+/// came out as `.notdef` (glyph 0, i.e. missing from the font) with the
+/// fallback font.
+///
+/// Shaping the whole text with the primary font first keeps ligatures,
+/// kerning and features working across everything it can render. Only the
+/// clusters it can't are cut out (consecutive ones together, so e.g. a run of
+/// emoji is shaped as one) and replaced by the fallback font's glyphs, in the
+/// same place. That also handles fonts that only partly cover a script: a
+/// single missing letter in the middle of a word is the only thing that
+/// falls back.
+fn shape_with_fallback(
+    primary: &LoadedFont,
+    fallback: &LoadedFont,
+    text: &str,
+    script: Option<Tag>,
+    features: &[([u8; 4], Range<usize>)],
+) -> Vec<ShapedRun> {
+    let (infos, positions) = shape_range(&primary.hb_font, text, 0..text.len(), script, features);
+
+    // Where each cluster ends: at the next cluster's start (clusters are
+    // byte offsets), or at the end of the text for the last one.
+    let cluster_starts: BTreeSet<usize> = infos.iter().map(|info| info.cluster as usize).collect();
+    let cluster_end = |cluster: usize| {
+        cluster_starts.range(cluster + 1..).next().copied().unwrap_or(text.len())
+    };
+    // A cluster is missing if any of its glyphs is .notdef; a base letter the
+    // font has with a mark it doesn't can't be drawn properly either.
+    let missing: BTreeSet<usize> = infos
+        .iter()
+        .filter(|info| info.codepoint == 0)
+        .map(|info| info.cluster as usize)
+        .collect();
+
+    // Split the glyphs into stretches of found and missing clusters.
+    let mut runs: Vec<ShapedRun> = Vec::new();
+    for (info, position) in infos.into_iter().zip(positions) {
+        let cluster = info.cluster as usize;
+        let font = if missing.contains(&cluster) {
+            FontChoice::Fallback
+        } else {
+            FontChoice::Primary
+        };
+        match runs.last_mut() {
+            Some(run) if run.font == font => {
+                run.start = run.start.min(cluster);
+                run.end = run.end.max(cluster_end(cluster));
+                run.infos.push(info);
+                run.positions.push(position);
+            }
+            _ => runs.push(ShapedRun {
+                start: cluster,
+                end: cluster_end(cluster),
+                infos: vec![info],
+                positions: vec![position],
+                font,
+            }),
+        }
+    }
+
+    // The primary glyphs were shaped with the whole text, so their clusters
+    // are offsets from the start of it; make them relative to their run. The
+    // missing stretches get reshaped with the fallback font, which gives
+    // clusters relative to the stretch already.
+    for run in &mut runs {
+        match run.font {
+            FontChoice::Primary => {
+                for info in &mut run.infos {
+                    info.cluster -= run.start as u32;
+                }
+            }
+            FontChoice::Fallback => {
+                let range = run.start..run.end;
+                (run.infos, run.positions) =
+                    shape_range(&fallback.hb_font, text, range, script, features);
+            }
+        }
+    }
+    runs
+}
+
+/// A tiny helper to shape `text[range]` with a given HarfBuzz font.
+///
+/// The rest of `text` (before and after `range`) is passed along as pre- and
+/// post-context. It is not shaped itself, but HarfBuzz looks at it to pick
+/// contextual forms. Without it every run would be shaped as if it stood
+/// alone, which breaks cursive scripts: an Arabic letter at the edge of a run
+/// would get its isolated form instead of its initial/final (joining) form.
+/// With context, shaping a word piece-by-piece gives the same glyphs as
+/// shaping it in one go.
+///
+/// `script` overrides HarfBuzz's guess. The guess comes from the first
+/// character with a real script, so a run that starts with digits,
+/// punctuation or an emoji, or text in a script shared by several languages,
+/// can be shaped with the wrong script's rules (no joining, wrong mark
+/// positions). Passing the script explicitly fixes that.
+///
+/// `features` are turned on for byte ranges of the whole `text`. HarfBuzz
+/// matches a feature's start and end against the clusters, which are byte
+/// offsets into the buffer, i.e. into `text[range]`. So the ranges are moved
+/// to be relative to `range` (and clipped to it) before shaping.
+///
+/// HarfBuzz also has BOT/EOT (beginning/end of text) buffer flags, for
+/// fragments that are known to start or end the text. harfbuzz_rs doesn't
+/// expose them, so they're never set here. Joining and other contextual
+/// forms don't need them, as they come from the context above. What they do
+/// change is that with BOT set, a run that starts with a combining mark and has
+/// no pre-context gets a dotted circle (U+25CC) to sit on, if the font has
+/// one. Without it, the mark is drawn on its own.
+pub fn shape_range(
+    hb_font: &harfbuzz_rs::Font,
+    text: &str,
+    range: Range<usize>,
+    script: Option<Tag>,
+    features: &[([u8; 4], Range<usize>)],
+) -> (Vec<GlyphInfo>, Vec<GlyphPosition>) {
+    // Create a buffer and add our run text. Direction and language are left
+    // for HarfBuzz to guess from the script (RTL for Arabic, for example).
+    let mut buffer = UnicodeBuffer::new()
+        .set_pre_context(&text[..range.start])
+        .add_str(&text[range.clone()])
+        .set_post_context(&text[range.end..]);
+    if let Some(script) = script {
+        buffer = buffer.set_script(script);
+    }
+    let features: Vec<Feature> = features
+        .iter()
+        .filter_map(|(tag, feature_range)| {
+            let start = feature_range.start.max(range.start) - range.start;
+            let end = feature_range.end.min(range.end).saturating_sub(range.start);
+            (start < end).then(|| Feature::new(tag_from_bytes(*tag), 1, start..end))
+        })
+        .collect();
+    let shaped_buf = shape(hb_font, buffer, &features);
+
+    let infos = shaped_buf.get_glyph_infos().to_vec();
+    let positions = shaped_buf.get_glyph_positions().to_vec();
+
+    (infos, positions)
+}
+
+/// Measures `text` `iterations` times, first by shaping it every time and
+/// then through a `ShapeCache`, and prints how long each took. This is what
+/// a UI does when it lays out the same labels every frame.
+fn measure_bench(font: &LoadedFont, text: &str, iterations: u32) {
+    let start = Instant::now();
+    let mut width = 0.0;
+    for _ in 0..iterations {
+        width = shape_text(&font.hb_font, text, &[]).width;
+    }
+    let uncached = start.elapsed();
+
+    let mut cache = ShapeCache::new(256);
+    let start = Instant::now();
+    let mut cached_width = 0.0;
+    for _ in 0..iterations {
+        cached_width = cache.measure_shaped(font, text).width;
+    }
+    let cached = start.elapsed();
+
+    eprintln!("Measured {width}px (cached: {cached_width}px) {iterations} times");
+    eprintln!("  shaping every time: {uncached:?}");
+    eprintln!("  with ShapeCache:    {cached:?}");
+}
+
+/// The glyph ids a text was shaped into, per font, sorted and without
+/// duplicates: the list to hand a font subsetter, so that only the glyphs
+/// that are drawn get embedded (in a PDF, say).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsedGlyphs {
+    pub primary: BTreeSet<u16>,
+    pub fallback: BTreeSet<u16>,
+}
+
+impl UsedGlyphs {
+    fn of(shaped_runs: &[ShapedRun]) -> Self {
+        let used_by = |font| {
+            shaped_runs
+                .iter()
+                .filter(|run| run.font == font)
+                .flat_map(|run| run.infos.iter().map(|info| info.codepoint as u16))
+                .collect()
+        };
+        UsedGlyphs {
+            primary: used_by(FontChoice::Primary),
+            fallback: used_by(FontChoice::Fallback),
+        }
+    }
+}
+
+/// A run of consecutive clusters shaped with the same font.
+struct ShapedRun {
+    /// Byte offset of the run in the text; glyph clusters are relative to it.
+    start: usize,
+    /// Byte offset of the end of the run.
+    end: usize,
+    infos: Vec<GlyphInfo>,
+    positions: Vec<GlyphPosition>,
+    font: FontChoice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FontChoice {
+    Primary,
+    Fallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutMode {
+    /// Advance by the shaped glyph advances.
+    Proportional,
+    /// Advance by whole monospace cells (see `common::monospace::cell_width`).
+    Grid,
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub layout: LayoutMode,
+    pub text: String,
+    /// The font for the clusters the primary font can't draw. Color fonts
+    /// work in any of their formats, including SVG-in-OpenType.
+    pub fallback_font_path: String,
+    /// OpenType features to turn on, as 4-byte tags.
+    pub features: Vec<[u8; 4]>,
+    /// OpenType features to turn on for just a byte range of `text` each,
+    /// e.g. `(*b"smcp", 0..5)` for small caps in the first five bytes.
+    pub ranged_features: Vec<([u8; 4], Range<usize>)>,
+    /// Number of measurements to time, if any.
+    pub measure_bench: Option<u32>,
+    /// Two characters to report the kerning of, e.g. "AV".
+    pub kern_pair: Option<String>,
+    /// ISO 15924 script tag to shape with (e.g. `*b"Arab"`), instead of
+    /// letting HarfBuzz guess it.
+    pub script: Option<[u8; 4]>,
+    /// Draw the fallback (emoji) runs in grayscale, leaving the text's color.
+    pub grayscale_emoji: bool,
+    /// Color each cluster by its position along a rainbow gradient.
+    pub rainbow: bool,
+    /// Draw each word as its own layer, this many pixels further left than
+    /// it would be, each on top of the one before.
+    pub overlap: Option<f32>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            layout: LayoutMode::Proportional,
+            text: "Hello, world 🌎".to_string(),
+            fallback_font_path: "NotoColorEmoji-Regular.ttf".to_string(),
+            features: Vec::new(),
+            ranged_features: Vec::new(),
+            measure_bench: None,
+            kern_pair: None,
+            script: None,
+            grayscale_emoji: false,
+            rainbow: false,
+            overlap: None,
+            dump_picture: None,
+        }
+    }
+}
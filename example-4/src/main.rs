@@ -1,200 +1,138 @@
-use harfbuzz_rs::{
-    Face, Font as HbFont, UnicodeBuffer, GlyphInfo, GlyphPosition,
-    shape
-};
-use skia_safe::{
-    Color, Data, EncodedImageFormat, Font, FontMgr, Paint, Surface, TextBlobBuilder,
-};
-use unicode_segmentation::UnicodeSegmentation;
+use common::features::features_by_script;
+use common::output::write_output;
+use example_4::{run, Config, LayoutMode};
+use std::env;
 use std::error::Error;
 use std::fs;
+use std::ops::Range;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // -------------------------------------------------
-    // 1. Load and create HarfBuzz + Skia fonts
-    // -------------------------------------------------
-
-    let primary_font_path = "Roboto-LightItalic.ttf";      // or any Latin-capable font
-    let fallback_font_path = "NotoColorEmoji-Regular.ttf"; // or any emoji-capable font
-
-    let primary_data = fs::read(primary_font_path)?;
-    let fallback_data = fs::read(fallback_font_path)?;
-
-    // Create HarfBuzz face/font for primary
-    let hb_face_primary = Face::from_bytes(&primary_data, 0);
-    let mut hb_font_primary = HbFont::new(hb_face_primary);
-    // We'll shape at 20px => 20 * 64 = 1280 in 26.6 fixed point
-    let px_size = 20.0;
-    let hb_scale = (px_size * 64.) as i32;
-    hb_font_primary.set_scale(hb_scale, hb_scale);
-    // Optional: set_ppem if you want hinting
-    // hb_font_primary.set_ppem(px_size as u32, px_size as u32);
-
-    // Create HarfBuzz face/font for fallback
-    let hb_face_fallback = Face::from_bytes(&fallback_data, 0);
-    let mut hb_font_fallback = HbFont::new(hb_face_fallback);
-    hb_font_fallback.set_scale(hb_scale, hb_scale);
-    // hb_font_fallback.set_ppem(px_size as u32, px_size as u32);
-
-    // Create parallel Skia Font objects (so we can actually draw):
-    let skia_data_primary = Data::new_copy(&primary_data);
-    let skia_data_fallback = Data::new_copy(&fallback_data);
-
-    let font_mgr = FontMgr::new();
-    let typeface_primary = font_mgr
-        .new_from_data(&skia_data_primary, None)
-        .ok_or("Failed to load primary typeface")?;
-    let typeface_fallback = font_mgr
-        .new_from_data(&skia_data_fallback, None)
-        .ok_or("Failed to load fallback typeface")?;
-
-    let mut skia_font_primary = Font::default();
-    skia_font_primary.set_size(px_size);
-    skia_font_primary.set_typeface(typeface_primary);
-
-    let mut skia_font_fallback = Font::default();
-    skia_font_fallback.set_size(px_size);
-    skia_font_fallback.set_typeface(typeface_fallback);
-
-    // -------------------------------------------------
-    // 2. Example string with emojis
-    // -------------------------------------------------
-    let text = "Hello, world 🌎";
-
-    // -------------------------------------------------
-    // 3. Segment text by grapheme clusters
-    //    (so we don't split a multi-codepoint emoji)
-    // -------------------------------------------------
-    let graphemes = text.graphemes(true);
-
-    // We'll accumulate everything into a single set of glyphs+positions
-    // We'll also track "which HarfBuzz font" was used for each cluster
-    // so we know which Skia font to use for drawing that cluster's glyphs.
-    // We'll store these as "runs" for building a Skia TextBlob.
-    // In a more sophisticated approach, you'd do fewer runs by merging adjacent
-    // clusters that used the same font, but let's keep it simple.
-    let mut shaped_runs = Vec::new();
-
-    for cluster in graphemes {
-        // shape with primary
-        let (infos, positions) = shape_cluster(&hb_font_primary, cluster);
-        // Check if we got only missing glyphs (codepoint=0). If so, fallback.
-        let has_valid_glyph = infos.iter().any(|info| info.codepoint != 0);
-        if has_valid_glyph {
-            shaped_runs.push((infos, positions, FontChoice::Primary));
-        } else {
-            // shape with fallback
-            let (infos_fb, positions_fb) = shape_cluster(&hb_font_fallback, cluster);
-            shaped_runs.push((infos_fb, positions_fb, FontChoice::Fallback));
+    let (config, out_path, list_features) = config_from_args()?;
+
+    // With --list-features, print what each font can do instead of drawing.
+    if list_features {
+        let fallback_path = config.fallback_font_path.as_str();
+        let fonts = [
+            ("Roboto-LightItalic.ttf", include_bytes!("../Roboto-LightItalic.ttf").to_vec()),
+            (fallback_path, fs::read(fallback_path)?),
+        ];
+        for (name, data) in fonts {
+            println!("{name}");
+            for (script, features) in features_by_script(&data) {
+                let features: Vec<String> = features.into_iter().collect();
+                println!("  {script:<4} {}", features.join(" "));
+            }
         }
+        return Ok(());
     }
 
-    // -------------------------------------------------
-    // 4. Build a single Skia TextBlob from these runs
-    // -------------------------------------------------
-    // We'll do this by:
-    //   - flattening the shaped data into (glyphId, xOffset, yOffset) arrays
-    //   - but we have to break them up into separate runs if the font changes
-
-    // For naive simplicity, let's do "one run per cluster".
-    // (A more advanced approach would gather consecutive clusters that share the same font.)
-    let mut blob_builder = TextBlobBuilder::new();
-
-    // We'll place runs one after another horizontally. We'll track a global "x" offset.
-    let mut x_cursor = 0.0;
-
-    // We'll store "clusters" in runs. Each run is just one cluster in this simplified approach.
-    for (infos, positions, which_font) in shaped_runs {
-        // Pick the matching Skia font
-        let skfont = match which_font {
-            FontChoice::Primary => &skia_font_primary,
-            FontChoice::Fallback => &skia_font_fallback,
-        };
-
-        let count = infos.len();
-        if count == 0 {
-            continue;
-        }
-
-        // Start a run
-        let (glyphs, point_positions) = blob_builder.alloc_run_pos(skfont, count, None);
-
-        // We'll keep track of local x as we place glyphs from this cluster
-        let mut local_x = 0.0;
-        for i in 0..count {
-            let info = &infos[i];
-            let pos = &positions[i];
-
-            glyphs[i] = info.codepoint as u16;
-
-            // HarfBuzz returns positions in 26.6 fixed -> /64.0
-            let x_offset = pos.x_offset as f32 / 64.0;
-            let y_offset = pos.y_offset as f32 / 64.0;
-            let x_advance = pos.x_advance as f32 / 64.0;
-            // typically y_advance is zero in horizontal text, but let's read it anyway:
-            // let y_advance = pos.y_advance as f32 / 64.0;
-
-            point_positions[i] = skia_safe::Point::new(x_cursor + local_x + x_offset, 50.0 + y_offset);
-
-            // Move local_x by the horizontal advance
-            local_x += x_advance;
+    let png_bytes = run(&config)?;
+    let path = out_path.as_deref().unwrap_or("fallback_hb.png");
+    write_output(path, &png_bytes)?;
+    eprintln!("Wrote {path}");
+    Ok(())
+}
 
-            // If you had vertical text, you'd also add y_advance, etc.
+/// Reads the `Config`, the output path (`--out`) and whether to list the
+/// fonts' features (`--list-features`) from the command line, e.g.
+/// `cargo run -- --grid --text "1/2 🍕"` or
+/// `cargo run -- --text "hello world" --feature 'smcp[0:5]'`.
+fn config_from_args() -> Result<(Config, Option<String>, bool), Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut out_path = None;
+    let mut list_features = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--grid" => config.layout = LayoutMode::Grid,
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            "--fallback-font" => {
+                config.fallback_font_path = args.next().ok_or("--fallback-font expects a path")?
+            }
+            // Fractions: "1/2" becomes a proper fraction (numerator, fraction
+            // slash, denominator, or a single precomposed glyph).
+            "--frac" => config.features.push(*b"frac"),
+            // Ordinals: the "o" in "1o" (or the "a" in "2a") as a superior letter.
+            "--ordn" => config.features.push(*b"ordn"),
+            // Capital spacing: a little extra space between capitals, for
+            // text set in all caps. Only fonts that define it change.
+            "--cpsp" => config.features.push(*b"cpsp"),
+            "--feature" => {
+                let value = args.next().ok_or("--feature expects e.g. smcp or 'smcp[0:5]'")?;
+                match parse_feature(&value)? {
+                    (tag, Some(range)) => config.ranged_features.push((tag, range)),
+                    (tag, None) => config.features.push(tag),
+                }
+            }
+            "--measure-bench" => {
+                let value = args.next().ok_or("--measure-bench expects a count, e.g. 10000")?;
+                config.measure_bench = Some(value.parse()?);
+            }
+            "--kern-pair" => {
+                let value = args.next().ok_or("--kern-pair expects two characters, e.g. AV")?;
+                config.kern_pair = Some(value);
+            }
+            "--script" => {
+                let value = args.next().ok_or("--script expects a tag, e.g. Arab")?;
+                config.script = Some(parse_script(&value)?);
+            }
+            "--dump-picture" => {
+                let path = args.next().ok_or("--dump-picture expects a file path")?;
+                config.dump_picture = Some(path.into());
+            }
+            // Where to write the image; `-` is stdout, for piping.
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            "--list-features" => list_features = true,
+            "--grayscale-emoji" => config.grayscale_emoji = true,
+            "--rainbow" => config.rainbow = true,
+            "--overlap" => {
+                let value = args.next().ok_or("--overlap expects a distance in pixels, e.g. 8")?;
+                config.overlap = Some(value.parse()?);
+            }
+            other => return Err(format!("Unknown argument: {other}").into()),
         }
-        // After finishing the run (cluster), we shift x_cursor by the total "local_x"
-        x_cursor += local_x;
     }
-
-    let text_blob = blob_builder.make().ok_or("Failed to build text blob")?;
-
-    // -------------------------------------------------
-    // 5. Draw to a Skia surface
-    // -------------------------------------------------
-    let width = 300;
-    let height = 120;
-    let mut surface = Surface::new_raster_n32_premul((width, height))
-        .ok_or("Could not create a surface")?;
-    let canvas = surface.canvas();
-    canvas.clear(Color::WHITE);
-
-    // Just draw the entire text_blob
-    let paint = Paint::default();
-    canvas.draw_text_blob(&text_blob, (50, 25), &paint);
-
-    // Save result
-    let image = surface.image_snapshot();
-    let png_data = image
-        .encode_to_data(EncodedImageFormat::PNG)
-        .ok_or("Failed to encode image")?;
-    fs::write("fallback_hb.png", png_data.as_bytes())?;
-    println!("Wrote fallback_hb.png");
-    Ok(())
+    Ok((config, out_path, list_features))
 }
 
-/// A tiny helper to shape a single cluster (grapheme) with a given HarfBuzz font.
-fn shape_cluster(hb_font: &harfbuzz_rs::Font, text: &str) -> (Vec<GlyphInfo>, Vec<GlyphPosition>) {
-    // Create a buffer, add our cluster text, shape it horizontally (LTR) just for the example.
-    // If your text might be RTL, you can set_direction(Direction::Rtl).
-    //
-    let shaped_buf = shape(
-        hb_font,
-        UnicodeBuffer::new()
-            .add_str(text),
-            // You could set script, language, direction as needed:
-            //   .set_script(Tag::new('Z','y','y','y'))  // "Zyyy" = Common script, for example
-            //   .set_language(Language::from_str("en").unwrap())
-            //   .set_direction(Direction::Ltr)
-        &[],  // optional features
-    );
-
-    let infos = shaped_buf.get_glyph_infos().to_vec();
-    let positions = shaped_buf.get_glyph_positions().to_vec();
-
-    (infos, positions)
+/// Parses a feature in HarfBuzz's `hb-shape --features` syntax: a tag for
+/// the whole text, or a tag and a byte range with the end left out, like
+/// `smcp[0:5]` for bytes 0 to 4. Either side of the range can be empty, as in
+/// `smcp[6:]`, to run to the start or end of the text.
+fn parse_feature(value: &str) -> Result<([u8; 4], Option<Range<usize>>), Box<dyn Error>> {
+    let (tag, range) = match value.split_once('[') {
+        Some((tag, range)) => (tag, Some(range)),
+        None => (value, None),
+    };
+    let tag: [u8; 4] = tag
+        .as_bytes()
+        .try_into()
+        .map_err(|_| format!("Feature tags are 4 characters, got `{tag}`"))?;
+    let Some(range) = range else {
+        return Ok((tag, None));
+    };
+    let (start, end) = range
+        .strip_suffix(']')
+        .and_then(|range| range.split_once(':'))
+        .ok_or_else(|| format!("Expected a range like [0:5], got `[{range}`"))?;
+    let start = if start.is_empty() { 0 } else { start.parse()? };
+    let end = if end.is_empty() { usize::MAX } else { end.parse()? };
+    Ok((tag, Some(start..end)))
 }
 
-#[derive(Debug)]
-enum FontChoice {
-    Primary,
-    Fallback,
+/// Parses an ISO 15924 script tag like "Latn" or "arab".
+///
+/// HarfBuzz expects the tag in its canonical title case ("Arab"), and takes
+/// anything else as an unknown script, so we normalize it here.
+fn parse_script(value: &str) -> Result<[u8; 4], Box<dyn Error>> {
+    let bytes: [u8; 4] = value
+        .as_bytes()
+        .try_into()
+        .map_err(|_| format!("Script tags are 4 letters, got `{value}`"))?;
+    if !bytes.iter().all(u8::is_ascii_alphabetic) {
+        return Err(format!("Script tags are 4 letters, got `{value}`").into());
+    }
+    let mut tag = bytes.map(|b| b.to_ascii_lowercase());
+    tag[0] = tag[0].to_ascii_uppercase();
+    Ok(tag)
 }
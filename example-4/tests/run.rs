@@ -0,0 +1,352 @@
+use common::features::supports_feature;
+use common::raster::read_rgba;
+use common::font::LoadedFont;
+use common::shaping::kerning_adjustment;
+use example_4::{
+    advance_width, fallback_ranges, glyph_ids, run, shape_range, used_glyphs, Config, LayoutMode,
+};
+use skia_safe::{AlphaType, Color, Data, Image};
+use std::collections::BTreeSet;
+use std::{env, fs};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn grid_mode_spreads_narrow_letters_to_whole_cells() {
+    // The rightmost column with dark pixels in it.
+    let ink_right = |layout| {
+        let png = run(&Config {
+            layout,
+            text: "iiii".to_string(),
+            ..Config::default()
+        })
+        .unwrap();
+        let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+        let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+        let width = image.width() as usize;
+        pixels.chunks(4).enumerate().filter(|(_, p)| p[0] < 128).map(|(i, _)| i % width).max()
+    };
+    // An "i" is less than half as wide as the "0" that sets the cell width,
+    // so the last of four starts well over ten pixels further right.
+    let proportional = ink_right(LayoutMode::Proportional).unwrap();
+    let grid = ink_right(LayoutMode::Grid).unwrap();
+    assert!(grid > proportional + 10, "{grid} vs {proportional}");
+}
+
+#[test]
+fn forcing_the_detected_script_changes_nothing() {
+    let text = "Hello, world!".to_string();
+    let guessed = Config {
+        text: text.clone(),
+        ..Config::default()
+    };
+    let forced = Config {
+        text,
+        script: Some(*b"Latn"),
+        ..Config::default()
+    };
+    assert_eq!(run(&guessed).unwrap(), run(&forced).unwrap());
+}
+
+#[test]
+fn only_the_missing_character_falls_back() {
+    // Roboto has every letter here but not the emoji in the middle.
+    let text = "abc🌎def";
+    assert_eq!(fallback_ranges(text).unwrap(), [3..7]);
+}
+
+#[test]
+fn ranged_feature_only_changes_its_range() {
+    let text = "hello world".to_string();
+    let plain = glyph_ids(&Config {
+        text: text.clone(),
+        ..Config::default()
+    })
+    .unwrap();
+    // Small caps for "hello" only.
+    let small_caps = glyph_ids(&Config {
+        text,
+        ranged_features: vec![(*b"smcp", 0..5)],
+        ..Config::default()
+    })
+    .unwrap();
+
+    // One glyph per letter either way, so they line up.
+    assert_eq!(plain.len(), small_caps.len());
+    for (i, (plain, small_cap)) in plain.iter().zip(&small_caps).take(5).enumerate() {
+        assert_ne!(plain, small_cap, "glyph {i} should be a small cap");
+    }
+    assert_eq!(plain[5..], small_caps[5..]);
+}
+
+#[test]
+fn capital_spacing_widens_all_caps_text() {
+    // Only fonts that define `cpsp` respace anything.
+    if !supports_feature(include_bytes!("../Roboto-LightItalic.ttf"), *b"cpsp") {
+        eprintln!("Skipping: the primary font has no cpsp feature");
+        return;
+    }
+    let plain = Config {
+        text: "HELLO WORLD".to_string(),
+        ..Config::default()
+    };
+    let spaced = Config {
+        features: vec![*b"cpsp"],
+        ..plain.clone()
+    };
+    assert!(advance_width(&spaced).unwrap() > advance_width(&plain).unwrap());
+}
+
+#[test]
+fn av_is_kerned_closer_together() {
+    let font = LoadedFont::from_bytes(include_bytes!("../Roboto-LightItalic.ttf"), 20.0).unwrap();
+    let adjustment = kerning_adjustment(&font.hb_font, "AV");
+    assert!(adjustment < 0.0, "{adjustment}");
+    // "Hi" isn't a kerning pair.
+    assert_eq!(kerning_adjustment(&font.hb_font, "Hi"), 0.0);
+}
+
+#[test]
+fn frac_swaps_in_fraction_glyphs() {
+    let plain = Config {
+        text: "1/2".to_string(),
+        ..Config::default()
+    };
+    let fraction = Config {
+        features: vec![*b"frac"],
+        ..plain.clone()
+    };
+    // Roboto has no ½ glyph, so `frac` doesn't merge the three into one.
+    // Instead every glyph is replaced: a numerator 1, a fraction slash and
+    // a denominator 2.
+    let (before, after) = (glyph_ids(&plain).unwrap(), glyph_ids(&fraction).unwrap());
+    assert_eq!(before.len(), 3);
+    assert_eq!(after.len(), 3);
+    assert!(before.iter().zip(&after).all(|(a, b)| a != b), "{before:?} -> {after:?}");
+}
+
+/// The most saturated pixel in the PNG (the largest spread between its
+/// channels), and the darkest one.
+fn saturation_and_darkness(png: &[u8]) -> (u8, u8) {
+    let image = Image::from_encoded(Data::new_copy(png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let spread = |p: &[u8]| p[..3].iter().max().unwrap() - p[..3].iter().min().unwrap();
+    let saturation = pixels.chunks(4).map(spread).max().unwrap();
+    let darkness = pixels.chunks(4).map(|p| *p[..3].iter().max().unwrap()).min().unwrap();
+    (saturation, darkness)
+}
+
+#[test]
+fn grayscale_emoji_leaves_the_text_alone() {
+    let color = Config::default();
+    let gray = Config {
+        grayscale_emoji: true,
+        ..Config::default()
+    };
+    // The globe emoji is blue and green...
+    let (color_saturation, _) = saturation_and_darkness(&run(&color).unwrap());
+    assert!(color_saturation > 100, "saturation {color_saturation}");
+    // ...and gray with the option, while the text is still solid black.
+    let (gray_saturation, darkness) = saturation_and_darkness(&run(&gray).unwrap());
+    assert!(gray_saturation <= 2, "saturation {gray_saturation}");
+    assert!(darkness < 30, "darkest pixel {darkness}");
+}
+
+#[test]
+fn rainbow_colors_the_first_and_last_letters_differently() {
+    let config = Config {
+        text: "Rainbow".to_string(),
+        rainbow: true,
+        ..Config::default()
+    };
+    let png = run(&config).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let width = image.width() as usize;
+
+    // The hue of the most saturated pixel in each column that has ink.
+    let spread = |p: &[u8]| p[..3].iter().max().unwrap() - p[..3].iter().min().unwrap();
+    let hues: Vec<f32> = (0..width)
+        .filter_map(|x| {
+            let p = pixels.chunks(4).skip(x).step_by(width).max_by_key(|p| spread(p))?;
+            (spread(p) > 80).then(|| Color::from_rgb(p[0], p[1], p[2]).to_hsv().h)
+        })
+        .collect();
+    let (first, last) = (hues[0], hues[hues.len() - 1]);
+    let difference = (first - last).abs().min(360.0 - (first - last).abs());
+    assert!(difference > 90.0, "first letter hue {first}, last letter hue {last}");
+}
+
+/// The last column of `png` with any ink in it.
+fn ink_right(png: &[u8]) -> usize {
+    let image = Image::from_encoded(Data::new_copy(png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let width = image.width() as usize;
+    (0..width)
+        .rev()
+        .find(|&x| pixels.chunks(4).skip(x).step_by(width).any(|p| p[..3] != [255; 3]))
+        .unwrap()
+}
+
+#[test]
+fn overlapping_words_pile_up() {
+    let plain = Config {
+        text: "one two three".to_string(),
+        ..Config::default()
+    };
+    let overlapping = Config {
+        overlap: Some(10.0),
+        ..plain.clone()
+    };
+    // The third word is pulled 20px left, and is still drawn (on top).
+    let shift = ink_right(&run(&plain).unwrap()) - ink_right(&run(&overlapping).unwrap());
+    assert!((19..=21).contains(&shift), "shifted by {shift}px");
+}
+
+#[test]
+fn arabic_shaped_piece_by_piece_matches_the_whole_word() {
+    // Roboto has no Arabic, so borrow example-3's font.
+    let font_data = fs::read("../example-3/Rubik-VariableFont_wght.ttf").unwrap();
+    let font = LoadedFont::from_bytes(&font_data, 20.0).unwrap();
+    let word = "مرحبا";
+    // Glyph ids in logical order: HarfBuzz returns RTL glyphs in visual
+    // order, so sort them back by cluster.
+    let logical = |range: std::ops::Range<usize>, text: &str| {
+        let (mut infos, _) = shape_range(&font.hb_font, text, range, None, &[]);
+        infos.sort_by_key(|info| info.cluster);
+        infos.iter().map(|info| info.codepoint).collect::<Vec<u32>>()
+    };
+    let whole = logical(0..word.len(), word);
+
+    // One letter at a time, with the rest of the word as context, every
+    // letter keeps its joining form.
+    let pieces: Vec<u32> =
+        word.char_indices().flat_map(|(i, c)| logical(i..i + c.len_utf8(), word)).collect();
+    assert_eq!(pieces, whole);
+
+    // Each letter shaped on its own gets its isolated form instead.
+    let alone: Vec<u32> = word
+        .char_indices()
+        .flat_map(|(i, c)| logical(0..c.len_utf8(), &word[i..i + c.len_utf8()]))
+        .collect();
+    assert_ne!(alone, whole);
+}
+
+#[test]
+fn used_glyphs_are_the_shaped_glyphs_of_each_font() {
+    let config = Config {
+        text: "Hello, hello 😀😀".to_string(),
+        ..Config::default()
+    };
+    let used = used_glyphs(&config).unwrap();
+
+    // Every glyph that was shaped, from either font, is in one of the sets...
+    let shaped: BTreeSet<u32> = glyph_ids(&config).unwrap().into_iter().collect();
+    let listed: BTreeSet<u32> =
+        used.primary.iter().chain(&used.fallback).map(|&glyph| glyph as u32).collect();
+    assert_eq!(listed, shaped);
+    // ...once: "Hello, hello " only needs "H", "e", "l", "o", ",", " " and
+    // "h", and both emoji are the same glyph.
+    assert_eq!(used.primary.len(), 7, "{used:?}");
+    assert_eq!(used.fallback.len(), 1, "{used:?}");
+}
+
+/// `data` with its `SVG ` table swapped for one with `document` as the only
+/// document, for `glyph` alone. Checksums are left at zero; neither Skia nor
+/// ttf-parser checks them.
+fn with_svg_glyph(data: &[u8], glyph: u16, document: &[u8]) -> Vec<u8> {
+    let mut svg = Vec::new();
+    // The table header: version, offset of the document list, reserved.
+    svg.extend(0u16.to_be_bytes());
+    svg.extend(10u32.to_be_bytes());
+    svg.extend(0u32.to_be_bytes());
+    // The list, of one record: first and last glyph, and the document's
+    // offset from the start of the list and its length.
+    svg.extend(1u16.to_be_bytes());
+    svg.extend(glyph.to_be_bytes());
+    svg.extend(glyph.to_be_bytes());
+    svg.extend(14u32.to_be_bytes());
+    svg.extend((document.len() as u32).to_be_bytes());
+    svg.extend(document);
+
+    let u16_at = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+    let u32_at = |offset: usize| u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = (0..u16_at(4))
+        .map(|i| 12 + 16 * i)
+        .filter(|&record| &data[record..record + 4] != b"SVG ")
+        .map(|record| {
+            let (offset, length) = (u32_at(record + 8) as usize, u32_at(record + 12) as usize);
+            (data[record..record + 4].try_into().unwrap(), data[offset..offset + length].to_vec())
+        })
+        .chain([(*b"SVG ", svg)])
+        .collect();
+    // The table directory is sorted by tag, for binary search.
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = 16 << entry_selector;
+    let mut font = data[..4].to_vec();
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend(value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    let mut bodies = Vec::new();
+    for (tag, body) in &tables {
+        font.extend(tag);
+        font.extend(0u32.to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((body.len() as u32).to_be_bytes());
+        // Tables start on 4-byte boundaries.
+        let padded = body.len().next_multiple_of(4);
+        bodies.extend(body);
+        bodies.resize(bodies.len() + padded - body.len(), 0);
+        offset += padded;
+    }
+    font.extend(bodies);
+    font
+}
+
+#[test]
+fn svg_glyphs_are_drawn_from_their_documents() {
+    let text = "🍕".to_string();
+    let pizza = glyph_ids(&Config {
+        text: text.clone(),
+        ..Config::default()
+    })
+    .unwrap()[0] as u16;
+    // A blue square one em wide, sitting on the baseline. Nothing in the
+    // bundled emoji is this blue.
+    let document = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\">\
+         <rect id=\"glyph{pizza}\" fill=\"#0000ff\" \
+         x=\"0\" y=\"-1024\" width=\"1024\" height=\"1024\"/></svg>"
+    );
+    let emoji = fs::read("NotoColorEmoji-Regular.ttf").unwrap();
+    let path = env::temp_dir().join("example-4-svg-glyph.ttf");
+    fs::write(&path, with_svg_glyph(&emoji, pizza, document.as_bytes())).unwrap();
+
+    let blue_pixels = |fallback_font_path: &str| {
+        let png = run(&Config {
+            text: text.clone(),
+            fallback_font_path: fallback_font_path.to_string(),
+            ..Config::default()
+        })
+        .unwrap();
+        let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+        let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+        pixels.chunks(4).filter(|p| p[0] < 30 && p[1] < 30 && p[2] > 200).count()
+    };
+    // The bundled pizza is drawn from its own document, in reds and
+    // oranges...
+    assert_eq!(blue_pixels("NotoColorEmoji-Regular.ttf"), 0);
+    // ...so the blue comes from ours: a 20px square, less its blended edges.
+    let blue = blue_pixels(path.to_str().unwrap());
+    assert!(blue > 300, "{blue} blue pixels");
+}
@@ -0,0 +1,507 @@
+use common::features::supports_feature;
+use common::font::{hb_variations, match_axes};
+use common::picture::record_picture;
+use common::png::encode_apng;
+use common::raster::{downsample, read_rgba};
+use freetype as ft;
+use freetype::freetype_sys as ffi;
+use harfbuzz_rs::{
+    Face, Feature, Font as HbFont, Owned, UnicodeBuffer, shape, Direction, Language, Tag,
+};
+use skia_safe::{
+    utils::parse_path, AlphaType, Canvas, Color, EncodedImageFormat, Image, Paint, PaintStyle,
+    Path, Rect, Surface,
+};
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Shapes the text (Arabic by default), draws FreeType outlines of it and returns them as
+/// PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    let image = render(config)?.0;
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok(png_data.as_bytes().to_vec())
+}
+
+/// Draws the text at a range of weights, from the lightest the font has to
+/// the boldest and back, and returns the frames as one looping animated PNG.
+///
+/// Any `wght` in `config.variations` is replaced; other axes are kept.
+pub fn run_weight_sweep(config: &Config, frame_delay_ms: u16) -> Result<Vec<u8>, Box<dyn Error>> {
+    // Rubik's weight axis goes from 300 to 900. Going up and back down (without
+    // repeating the ends) makes the loop seamless.
+    let up = (300..=900).step_by(100);
+    let down = (400..900).step_by(100).rev();
+    let mut frames = Vec::new();
+    let mut size = None;
+    for weight in up.chain(down) {
+        let mut frame_config = config.clone();
+        frame_config.variations.retain(|(tag, _)| tag != b"wght");
+        frame_config.variations.push((*b"wght", weight as f32));
+        let image = render(&frame_config)?.0;
+        size = Some(image.dimensions());
+        frames.push(read_rgba(&image, AlphaType::Unpremul).ok_or("Failed to read pixels")?);
+    }
+    let size = size.ok_or("No frames")?;
+    encode_apng(&frames, size, frame_delay_ms).ok_or_else(|| "Failed to encode the APNG".into())
+}
+
+/// Like `run`, but returns the outlines as an SVG document.
+///
+/// Every glyph gets its own `<g>`, even one without an outline (like a
+/// space), so animation tools can target single letters. Groups are in
+/// HarfBuzz's glyph order (visual order, so right to left reads backwards
+/// for Arabic) and carry the glyph id and the cluster (byte offset into the
+/// text the glyph came from):
+///
+/// ```text
+/// <g id="glyph-0" data-glyph-id="245" data-cluster="24"><path d="..."/></g>
+/// ```
+pub fn run_svg(config: &Config) -> Result<String, Box<dyn Error>> {
+    Ok(render(config)?.1)
+}
+
+/// Shapes and draws the text like `run`, but only returns how wide it came
+/// out: HarfBuzz's total advance and the width of the FreeType outlines.
+pub fn measure(config: &Config) -> Result<Widths, Box<dyn Error>> {
+    Ok(render(config)?.2)
+}
+
+/// The advance boxes `run` draws with `show_advances`, one per glyph in
+/// visual order, in image pixels.
+pub fn advance_boxes(config: &Config) -> Result<Vec<Rect>, Box<dyn Error>> {
+    let config = Config {
+        show_advances: true,
+        ..config.clone()
+    };
+    Ok(render(&config)?.3)
+}
+
+/// Draws the text, returning it both as an image and as an SVG document,
+/// along with its widths and its advance boxes (empty without
+/// `show_advances`).
+fn render(config: &Config) -> Result<(Image, String, Widths, Vec<Rect>), Box<dyn Error>> {
+    let font_path = config.font_path.as_str();
+    let font_data = fs::read(font_path)?;
+    
+    let library = ft::Library::init()?;
+    let mut ft_face = library.new_face(font_path, 0)?;
+    
+    // Set the desired font size (in pixels). FreeType and HarfBuzz both take
+    // it in 26.6 fixed point (1/64ths of a pixel), so a size like 13.5px is
+    // kept exactly. `set_pixel_sizes` only takes whole pixels and would turn
+    // 13.5 into 13, drawing outlines a bit smaller than HarfBuzz spaces them.
+    // `set_char_size` takes points, which are pixels at 72 dpi.
+    let desired_font_size = config.font_size;
+    let size_26_6 = (desired_font_size * 64.0).round();
+    ft_face.set_char_size(0, size_26_6 as isize, 72, 72)?;
+    
+    let hb_font = hb_font(&font_data, config);
+
+    // With --variations (e.g. "wght=700"), pick a different instance of the
+    // variable font. HarfBuzz needs them for shaping, since advances change
+    // with the axes, and FreeType for the outlines we draw; setting only one
+    // of the two gives bold glyphs at regular spacing, or the other way round.
+    if !config.variations.is_empty() {
+        set_ft_variations(&library, &mut ft_face, &config.variations)?;
+    }
+    
+    // With --ft-kerning, a font whose kerning is only in a legacy `kern`
+    // table (no `kern` feature in GPOS) is kerned by FreeType instead: the
+    // pair adjustment between each glyph and the one before it goes on the
+    // pen advance, and HarfBuzz's own `kern` is turned off so the pairs
+    // aren't kerned twice. HarfBuzz does apply a legacy `kern` table when
+    // it shapes the pairs together, so this is for code that places glyphs
+    // without a shaper pass over the whole run (glyphs shaped or cached one
+    // at a time), and for comparing the two. FreeType can't read GPOS at
+    // all, so for every other font HarfBuzz's kerning is kept.
+    let text = config.text.as_str();
+    let legacy_kerning =
+        config.ft_kerning && ft_face.has_kerning() && !supports_feature(&font_data, *b"kern");
+    if config.ft_kerning && !legacy_kerning {
+        eprintln!("--ft-kerning: the font has no legacy-only kerning, keeping HarfBuzz's");
+    }
+    let features = if legacy_kerning {
+        vec![Feature::new(Tag::new('k', 'e', 'r', 'n'), 0, ..)]
+    } else {
+        Vec::new()
+    };
+    let shaped_result = shape(&hb_font, text_buffer(text), &features);
+    let glyph_infos = shaped_result.get_glyph_infos();
+    let glyph_positions = shaped_result.get_glyph_positions();
+
+    // The kerning before each glyph, in pixels. Unfitted, so it stays as
+    // fractional as HarfBuzz's advances instead of being rounded.
+    let mut kerning = vec![0.0; glyph_infos.len()];
+    if legacy_kerning {
+        for (i, pair) in glyph_infos.windows(2).enumerate() {
+            let adjustment = ft_face.get_kerning(
+                pair[0].codepoint,
+                pair[1].codepoint,
+                ft::face::KerningMode::KerningUnfitted,
+            )?;
+            kerning[i + 1] = adjustment.x as f32 / 64.0;
+        }
+    }
+    let shaped_width: i32 = glyph_positions.iter().map(|pos| pos.x_advance).sum();
+    let shaped_width = shaped_width as f32 / 64.0 + kerning.iter().sum::<f32>();
+    eprintln!("Shaped width: {shaped_width:.2}px");
+    
+    let width = 500;
+    let height = 200;
+
+    // The load flags decide how FreeType prepares the outline. We only use the
+    // outline (never FreeType's own bitmap), so NO_BITMAP is always set. The
+    // load target picks a hinting style; since hinting is what moves outline
+    // points onto the pixel grid, the target only changes the outlines while
+    // hinting is on (the default). With --no-hinting you get the unmodified
+    // design outline whatever the target.
+    let mut load_flags = ft::face::LoadFlag::NO_BITMAP | config.load_target;
+    if !config.hinting {
+        load_flags |= ft::face::LoadFlag::NO_HINTING;
+    }
+    
+    let origin_x = 50.0;
+    let origin_y = 100.0;
+    
+    // Running horizontal offset (in pixels) for glyph placement.
+    let mut x_accum = 0.0;
+
+    // With --show-advances, each glyph gets a box as wide as its advance and
+    // one em tall, split around the baseline in the ascender:descender ratio.
+    // The boxes are drawn first so the glyphs sit on top; where a glyph pokes
+    // out of its box, that's overhang into the neighbouring glyph's space.
+    let em = desired_font_size;
+    let ascender = ft_face.ascender() as f32;
+    let descender = ft_face.descender() as f32;
+    let em_top = origin_y - em * ascender / (ascender - descender);
+    let mut advance_boxes = Vec::new();
+
+    // The glyphs' outlines, in image pixels, ready to draw.
+    let mut glyph_paths = Vec::new();
+
+    // The bounds of everything drawn, for `measure`.
+    let mut ink_bounds = Rect::new_empty();
+
+    // The same outlines, one group per glyph, for the SVG version.
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\">\n"
+    );
+    
+    // Process each glyph from the HarfBuzz shaping result.
+    for (glyph_index, (info, pos)) in glyph_infos.iter().zip(glyph_positions.iter()).enumerate() {
+        let glyph_id = info.codepoint;
+        // HarfBuzz positions are in 26.6 fixed point.
+        let x_offset = pos.x_offset as f32 / 64.0;
+        let y_offset = pos.y_offset as f32 / 64.0;
+        let x_advance = pos.x_advance as f32 / 64.0;
+        x_accum += kerning[glyph_index];
+
+        if config.show_advances {
+            // The box starts at the pen position, ignoring the glyph's offset:
+            // offsets move the ink, not the space the glyph takes up. Adjacent
+            // boxes alternate colors so their shared edges are easy to spot.
+            let pen_x = origin_x + x_accum;
+            let advance_box = Rect::from_xywh(pen_x, em_top, x_advance, em);
+            let (fill, outline) = if glyph_index % 2 == 0 {
+                (Color::from_argb(40, 0, 120, 255), Color::from_argb(160, 0, 120, 255))
+            } else {
+                (Color::from_argb(40, 255, 140, 0), Color::from_argb(160, 255, 140, 0))
+            };
+            advance_boxes.push((advance_box, fill, outline));
+        }
+        
+        // Compute the glyph’s drawing origin. HarfBuzz's y axis points up
+        // and Skia's down, so a mark that GPOS raises has to move up here.
+        let glyph_origin_x = origin_x + x_accum + x_offset;
+        let glyph_origin_y = origin_y - y_offset;
+        
+        // Load the glyph into the FreeType face.
+        // (The glyph index from HarfBuzz should match FreeType’s index.)
+        ft_face.load_glyph(glyph_id, load_flags)?;
+        let glyph_slot = ft_face.glyph();
+
+        write!(
+            svg,
+            "  <g id=\"glyph-{glyph_index}\" data-glyph-id=\"{glyph_id}\" data-cluster=\"{}\">",
+            info.cluster
+        )?;
+        
+        // If the glyph has an outline, convert it into a Skia Path.
+        if let Some(outline) = glyph_slot.outline() {
+            let mut path = Path::new();
+            // Iterate over each contour in the outline.
+            for contour in outline.contours_iter() {
+                // Get the starting point of the contour.
+                let start_pt = contour.start();
+                // Convert from 26.6 fixed point to float (divide by 64)
+                // and flip the y-axis (FreeType’s y goes up; Skia’s goes down).
+                let start_x = start_pt.x as f32 / 64.0;
+                let start_y = -start_pt.y as f32 / 64.0;
+                path.move_to((start_x, start_y));
+                
+                // Process each curve segment in the contour.
+                for curve in contour {
+                    match curve {
+                        ft::outline::Curve::Line(pt) => {
+                            let x = pt.x as f32 / 64.0;
+                            let y = -pt.y as f32 / 64.0;
+                            path.line_to((x, y));
+                        }
+                        ft::outline::Curve::Bezier2(pt1, pt2) => {
+                            let x1 = pt1.x as f32 / 64.0;
+                            let y1 = -pt1.y as f32 / 64.0;
+                            let x2 = pt2.x as f32 / 64.0;
+                            let y2 = -pt2.y as f32 / 64.0;
+                            path.quad_to((x1, y1), (x2, y2));
+                        }
+                        ft::outline::Curve::Bezier3(pt1, pt2, pt3) => {
+                            let x1 = pt1.x as f32 / 64.0;
+                            let y1 = -pt1.y as f32 / 64.0;
+                            let x2 = pt2.x as f32 / 64.0;
+                            let y2 = -pt2.y as f32 / 64.0;
+                            let x3 = pt3.x as f32 / 64.0;
+                            let y3 = -pt3.y as f32 / 64.0;
+                            path.cubic_to((x1, y1), (x2, y2), (x3, y3));
+                        }
+                    }
+                }
+                path.close();
+            }
+            // Offset the path so that it is drawn at the correct glyph position.
+            path.offset((glyph_origin_x, glyph_origin_y));
+            ink_bounds.join(path.bounds());
+            write!(svg, "<path d=\"{}\"/>", parse_path::to_svg(&path))?;
+            glyph_paths.push(path);
+        }
+        svg.push_str("</g>\n");
+        
+        // Advance the horizontal position by the glyph’s advance width.
+        x_accum += x_advance;
+    }
+    
+    svg.push_str("</svg>\n");
+    
+    let widths = Widths {
+        advance: shaped_width,
+        outline: ink_bounds.width(),
+    };
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+        let mut box_paint = Paint::default();
+        box_paint.set_anti_alias(true);
+        for &(advance_box, fill, outline) in &advance_boxes {
+            box_paint.set_style(PaintStyle::Fill).set_color(fill);
+            canvas.draw_rect(advance_box, &box_paint);
+            box_paint.set_style(PaintStyle::Stroke).set_stroke_width(1.0).set_color(outline);
+            canvas.draw_rect(advance_box, &box_paint);
+        }
+
+        let mut paint = Paint::default();
+        // A monochrome target is meant for 1-bit rendering, so draw it that way.
+        paint.set_anti_alias(config.load_target != ft::face::LoadFlag::TARGET_MONO);
+        for path in &glyph_paths {
+            canvas.draw_path(path, &paint);
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp, at the image's
+    // own size however much it's supersampled.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+
+    // With --supersample N, draw N times bigger (in image coordinates, the
+    // canvas is scaled) and shrink the result at the end.
+    let samples = config.supersample.max(1);
+    let mut surface =
+        Surface::new_raster_n32_premul((width * samples as i32, height * samples as i32))
+            .ok_or("Could not create surface")?;
+    let canvas = surface.canvas();
+    canvas.scale((samples as f32, samples as f32));
+    draw(canvas);
+
+    let mut image = surface.image_snapshot();
+    if samples > 1 {
+        image = downsample(&image, (width, height), Color::WHITE)
+            .ok_or("Could not downsample the image")?;
+    }
+    let boxes = advance_boxes.iter().map(|&(advance_box, _, _)| advance_box).collect();
+    Ok((image, svg, widths, boxes))
+}
+
+/// One glyph as HarfBuzz placed it. Positions are in 26.6 fixed point
+/// pixels, with y pointing up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedGlyph {
+    pub glyph_id: u32,
+    /// Byte offset into the text the glyph came from.
+    pub cluster: u32,
+    pub x_advance: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+/// Shapes `text` the way `run` shapes its Arabic line (same font, size,
+/// variations and buffer setup) and returns the glyphs in visual order.
+pub fn shape_arabic(config: &Config, text: &str) -> Result<Vec<PlacedGlyph>, Box<dyn Error>> {
+    let font_data = fs::read(&config.font_path)?;
+    let hb_font = hb_font(&font_data, config);
+    let shaped = shape(&hb_font, arabic_buffer(text), &[]);
+    Ok(shaped
+        .get_glyph_infos()
+        .iter()
+        .zip(shaped.get_glyph_positions())
+        .map(|(info, pos)| PlacedGlyph {
+            glyph_id: info.codepoint,
+            cluster: info.cluster,
+            x_advance: pos.x_advance,
+            x_offset: pos.x_offset,
+            y_offset: pos.y_offset,
+        })
+        .collect())
+}
+
+/// A HarfBuzz font at the configured size and variations.
+fn hb_font<'a>(font_data: &'a [u8], config: &Config) -> Owned<HbFont<'a>> {
+    let mut hb_font = HbFont::new(Face::from_bytes(font_data, 0));
+
+    // The HarfBuzz scale is the same 26.6 size as FreeType's, so advances
+    // match the outlines.
+    let size_26_6 = (config.font_size * 64.0).round() as i32;
+    hb_font.set_scale(size_26_6, size_26_6);
+
+    if !config.variations.is_empty() {
+        hb_font.set_variations(&hb_variations(&config.variations));
+    }
+    hb_font
+}
+
+/// A buffer for Arabic text, with direction, script and language set.
+///
+/// Guessing would usually get the same result, but setting the script
+/// explicitly is what selects the font's Arabic lookups: joining forms from
+/// GSUB (`init`, `medi`, `fina`) and mark attachment from GPOS (`mark`,
+/// `mkmk`). Shaped as Latin, the letters come out in their isolated forms
+/// and the marks are placed by HarfBuzz's generic fallback instead of the
+/// font's anchors.
+fn arabic_buffer(text: &str) -> UnicodeBuffer {
+    UnicodeBuffer::new()
+        .add_str(text)
+        .set_direction(Direction::Rtl)
+        .set_language(Language::from_str("ar").unwrap())
+        .set_script(Tag::new('a', 'r', 'a', 'b'))
+}
+
+/// A buffer for `text`: set up for Arabic (see `arabic_buffer`) if it has
+/// any Arabic letters, otherwise with its direction and script guessed.
+fn text_buffer(text: &str) -> UnicodeBuffer {
+    if text.chars().any(|c| ('\u{0600}'..='\u{06ff}').contains(&c)) {
+        arabic_buffer(text)
+    } else {
+        UnicodeBuffer::new().add_str(text).guess_segment_properties()
+    }
+}
+
+/// How wide the text came out, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Widths {
+    /// The sum of HarfBuzz's advances, plus FreeType's kerning with
+    /// `ft_kerning`.
+    pub advance: f32,
+    /// The width of the FreeType outlines' bounding box.
+    pub outline: f32,
+}
+
+/// Sets the FreeType face's variable font axes to match `variations`.
+///
+/// freetype-rs has no safe wrapper for variable fonts, so this goes through
+/// the raw FFI. FreeType wants a design coordinate for every axis of the font,
+/// in the font's own axis order, so axes that aren't mentioned keep their
+/// default value.
+fn set_ft_variations(
+    library: &ft::Library,
+    face: &mut ft::Face,
+    variations: &[([u8; 4], f32)],
+) -> Result<(), Box<dyn Error>> {
+    let face_ptr: ffi::FT_Face = face.raw_mut();
+    let mut mm_var: *mut ffi::FT_MM_Var = std::ptr::null_mut();
+    if unsafe { ffi::FT_Get_MM_Var(face_ptr, &mut mm_var) } != 0 {
+        return Err("The font has no variation axes".into());
+    }
+
+    // Axis values are 16.16 fixed point.
+    let axes: Vec<([u8; 4], f32)> =
+        unsafe { std::slice::from_raw_parts((*mm_var).axis, (*mm_var).num_axis as usize) }
+            .iter()
+            .map(|axis| ((axis.tag as u32).to_be_bytes(), axis.def as f32 / 65536.0))
+            .collect();
+    unsafe { ffi::FT_Done_MM_Var(library.raw(), mm_var) };
+    let (values, unknown) = match_axes(&axes, variations);
+    for tag in unknown {
+        eprintln!("Skipping '{}': the font has no such axis", String::from_utf8_lossy(&tag));
+    }
+    let coords: Vec<ffi::FT_Fixed> =
+        values.iter().map(|value| (value * 65536.0).round() as ffi::FT_Fixed).collect();
+
+    let error = unsafe {
+        ffi::FT_Set_Var_Design_Coordinates(face_ptr, coords.len() as ffi::FT_UInt, coords.as_ptr())
+    };
+    if error != 0 {
+        return Err(format!("FreeType could not set the variations (error {error})").into());
+    }
+    Ok(())
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    /// The font to draw with; it has to cover the text.
+    pub font_path: String,
+    pub font_size: f32,
+    /// FreeType's hinting target:
+    /// - `normal`: full hinting, snapping both axes to the pixel grid;
+    /// - `light`: only snaps vertically, keeping the glyph shapes closer to the design;
+    /// - `lcd`: like normal, tuned for horizontal sub-pixel (LCD) rendering;
+    /// - `mono`: strong hinting for 1-bit rendering. Blocky, but very crisp at
+    ///   small sizes; try it at `--size 12`.
+    pub load_target: ft::face::LoadFlag,
+    pub hinting: bool,
+    /// Variable font axis values, e.g. `[(*b"wght", 700.0)]`.
+    pub variations: Vec<([u8; 4], f32)>,
+    /// Draw each glyph's advance box (advance wide, one em tall) behind it.
+    pub show_advances: bool,
+    /// Draw at this many times the size in each direction and shrink the
+    /// result, for smoother outline edges than the paint's anti-aliasing
+    /// alone (see example-6's `Config::supersample`). 1 draws directly.
+    pub supersample: u32,
+    /// Kern with FreeType's `get_kerning` for fonts that only have a legacy
+    /// `kern` table (see `run`).
+    pub ft_kerning: bool,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            text: "مرحبا بالعالم".to_string(),
+            font_path: "Rubik-VariableFont_wght.ttf".to_string(),
+            font_size: 40.0,
+            load_target: ft::face::LoadFlag::TARGET_NORMAL,
+            hinting: true,
+            variations: Vec::new(),
+            show_advances: false,
+            supersample: 1,
+            ft_kerning: false,
+            dump_picture: None,
+        }
+    }
+}
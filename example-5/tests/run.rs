@@ -0,0 +1,250 @@
+use example_5::{advance_boxes, measure, run, run_svg, run_weight_sweep, shape_arabic, Config};
+use freetype::face::LoadFlag;
+use harfbuzz_rs::{shape, Face, Font, UnicodeBuffer};
+use std::env;
+use std::fs;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn bolder_weight_is_wider() {
+    // Rubik's default instance is its lightest, 300. Its only axis is wght,
+    // so there's no wdth to try.
+    let regular = measure(&Config::default()).unwrap();
+    let bold = measure(&Config {
+        variations: vec![(*b"wght", 700.0)],
+        ..Config::default()
+    })
+    .unwrap();
+    // HarfBuzz's advances and FreeType's outlines both follow the axis.
+    assert!(bold.advance > regular.advance, "{bold:?} vs {regular:?}");
+    assert!(bold.outline > regular.outline, "{bold:?} vs {regular:?}");
+}
+
+#[test]
+fn advance_boxes_are_side_by_side() {
+    let boxes = advance_boxes(&Config::default()).unwrap();
+    assert_eq!(boxes.len(), shape_arabic(&Config::default(), "مرحبا بالعالم").unwrap().len());
+    // Each box starts where the one before it ends, whatever the glyphs'
+    // offsets, and all of them are one em tall.
+    for pair in boxes.windows(2) {
+        assert!((pair[1].left - pair[0].right).abs() < 0.001, "{pair:?}");
+        assert!((pair[1].height() - 40.0).abs() < 0.001, "{pair:?}");
+    }
+}
+
+#[test]
+fn svg_has_one_group_per_glyph() {
+    let svg = run_svg(&Config::default()).unwrap();
+    let groups: Vec<&str> = svg
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| line.starts_with("<g "))
+        .collect();
+    assert!(!groups.is_empty());
+
+    // Groups are numbered in glyph order, and each names a real glyph (not
+    // .notdef, which would mean the font is missing a character).
+    for (index, group) in groups.iter().enumerate() {
+        assert!(group.contains(&format!("id=\"glyph-{index}\"")), "{group}");
+        let glyph_id = group.split("data-glyph-id=\"").nth(1).unwrap();
+        let glyph_id: u32 = glyph_id[..glyph_id.find('"').unwrap()].parse().unwrap();
+        assert_ne!(glyph_id, 0);
+        assert!(group.contains("data-cluster=\""));
+        assert!(group.ends_with("</g>"));
+    }
+}
+
+#[test]
+fn fractional_sizes_are_not_rounded_down() {
+    // Without hinting, so the outlines aren't snapped to whole pixels either.
+    let at_size = |font_size| {
+        let config = Config {
+            font_size,
+            hinting: false,
+            ..Config::default()
+        };
+        measure(&config).unwrap()
+    };
+    let (small, fractional, large) = (at_size(13.0), at_size(13.5), at_size(14.0));
+
+    assert!(small.advance < fractional.advance && fractional.advance < large.advance);
+    assert!(small.outline < fractional.outline && fractional.outline < large.outline);
+}
+
+/// The `(type, data)` of every chunk in a PNG.
+fn png_chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut chunks = Vec::new();
+    let mut rest = &png[PNG_SIGNATURE.len()..];
+    while !rest.is_empty() {
+        let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        chunks.push((rest[4..8].try_into().unwrap(), &rest[8..8 + length]));
+        rest = &rest[12 + length..];
+    }
+    chunks
+}
+
+#[test]
+fn weight_sweep_is_a_looping_apng() {
+    let apng = run_weight_sweep(&Config::default(), 80).unwrap();
+    assert!(apng.starts_with(PNG_SIGNATURE));
+    let chunks = png_chunks(&apng);
+
+    // 300 to 900 and back down to 400, in steps of 100.
+    let (_, actl) = chunks.iter().find(|(chunk_type, _)| chunk_type == b"acTL").unwrap();
+    assert_eq!(&actl[..4], 11u32.to_be_bytes(), "frame count");
+    assert_eq!(&actl[4..], 0u32.to_be_bytes(), "plays (0 loops forever)");
+
+    let frame_controls: Vec<&[u8]> = chunks
+        .iter()
+        .filter(|(chunk_type, _)| chunk_type == b"fcTL")
+        .map(|(_, data)| *data)
+        .collect();
+    assert_eq!(frame_controls.len(), 11);
+    for fctl in frame_controls {
+        assert_eq!(&fctl[20..24], [0, 80, 0x03, 0xe8], "80/1000 s delay");
+    }
+    assert_eq!(chunks.last().unwrap().0, *b"IEND");
+}
+
+#[test]
+fn gpos_attaches_arabic_marks() {
+    // "marḥaban" with its vowel marks: fatha, sukun, fatha and tanwin fath.
+    // With the bundled Rubik (version 2.300), Arabic shaping gives nine
+    // glyphs: five letters, each in its joining form, and four zero-width
+    // marks that GPOS `mark` moves onto their letter's anchor. At 40px, every
+    // mark is shifted sideways, and three of them are also lowered slightly
+    // (3 font units, a few 1/64ths of a pixel). Without GPOS, all the marks
+    // would sit at offset (0, 0), at the pen position after their letter.
+    let glyphs = shape_arabic(&Config::default(), "مَرْحَبًا").unwrap();
+    assert_eq!(glyphs.len(), 9);
+    let marks: Vec<_> = glyphs.iter().filter(|glyph| glyph.x_advance == 0).collect();
+    assert_eq!(marks.len(), 4);
+    assert!(marks.iter().all(|mark| mark.x_offset != 0), "{marks:?}");
+    assert!(marks.iter().any(|mark| mark.y_offset != 0), "{marks:?}");
+    assert!(glyphs.iter().filter(|glyph| glyph.x_advance != 0).all(|glyph| glyph.y_offset == 0));
+}
+
+/// `data` rebuilt with the tables `keep` accepts, plus `extra`. Checksums are
+/// left at zero; neither FreeType nor HarfBuzz checks them.
+fn rebuild_font(
+    data: &[u8],
+    keep: impl Fn(&[u8]) -> bool,
+    extra: Vec<([u8; 4], Vec<u8>)>,
+) -> Vec<u8> {
+    let u16_at = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+    let u32_at = |offset: usize| u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = (0..u16_at(4))
+        .map(|i| 12 + 16 * i)
+        .filter(|&record| keep(&data[record..record + 4]))
+        .map(|record| {
+            let (offset, length) = (u32_at(record + 8) as usize, u32_at(record + 12) as usize);
+            (data[record..record + 4].try_into().unwrap(), data[offset..offset + length].to_vec())
+        })
+        .chain(extra)
+        .collect();
+    // The table directory is sorted by tag, for binary search.
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = 16 << entry_selector;
+    let mut font = data[..4].to_vec();
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend(value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    let mut bodies = Vec::new();
+    for (tag, body) in &tables {
+        font.extend(tag);
+        font.extend(0u32.to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((body.len() as u32).to_be_bytes());
+        // Tables start on 4-byte boundaries.
+        let padded = body.len().next_multiple_of(4);
+        bodies.extend(body);
+        bodies.resize(bodies.len() + padded - body.len(), 0);
+        offset += padded;
+    }
+    font.extend(bodies);
+    font
+}
+
+/// A version 0 `kern` table with a single format 0 subtable for one pair.
+fn kern_table(left: u16, right: u16, value: i16) -> Vec<u8> {
+    let header = [0, 1]; // version, number of subtables
+    // Subtable version, length and coverage (horizontal kerning, format 0).
+    let subtable = [0, 6 + 8 + 6, 0x0001];
+    // Number of pairs, search range, entry selector and range shift.
+    let pairs = [1, 6, 0, 0];
+    let pair = [left, right, value as u16];
+    [&header[..], &subtable, &pairs, &pair]
+        .concat()
+        .iter()
+        .flat_map(|field: &u16| field.to_be_bytes())
+        .collect()
+}
+
+#[test]
+fn ft_kerning_kerns_a_legacy_kern_table_font() {
+    // Rubik kerns with GPOS. Make two copies without it: one with a legacy
+    // `kern` table for "AV", like an old font, and one without any kerning.
+    let rubik = fs::read("Rubik-VariableFont_wght.ttf").unwrap();
+    let face = Face::from_bytes(&rubik, 0);
+    let shaped = shape(&Font::new(face), UnicodeBuffer::new().add_str("AV"), &[]);
+    let [a, v] = [0, 1].map(|i| shaped.get_glyph_infos()[i].codepoint as u16);
+
+    let no_gpos = |tag: &[u8]| tag != b"GPOS";
+    let legacy = rebuild_font(&rubik, no_gpos, vec![(*b"kern", kern_table(a, v, -150))]);
+    let unkerned = rebuild_font(&rubik, no_gpos, Vec::new());
+    let write = |name: &str, data: &[u8]| {
+        let path = env::temp_dir().join(name);
+        fs::write(&path, data).unwrap();
+        path.to_str().unwrap().to_string()
+    };
+    let advance = |font_path: &str, ft_kerning| {
+        let config = Config {
+            text: "AV".to_string(),
+            font_path: font_path.to_string(),
+            hinting: false,
+            ft_kerning,
+            ..Config::default()
+        };
+        measure(&config).unwrap().advance
+    };
+    let legacy_path = write("example-5-legacy-kern.ttf", &legacy);
+    let unkerned_path = write("example-5-unkerned.ttf", &unkerned);
+
+    // -150 units at 40px is -6px.
+    let plain = advance(&unkerned_path, true);
+    let kerned = advance(&legacy_path, true);
+    assert!((plain - kerned - 6.0).abs() < 0.1, "{plain} vs {kerned}");
+    // HarfBuzz kerns the pair the same way when it's left to it, so either
+    // way the pair is kerned exactly once.
+    let by_harfbuzz = advance(&legacy_path, false);
+    assert!((by_harfbuzz - kerned).abs() < 0.1, "{by_harfbuzz} vs {kerned}");
+}
+
+#[test]
+fn load_target_moves_the_outline_points_only_when_hinting() {
+    // The SVG's path data is the outline coordinates as FreeType loaded them.
+    let outlines = |load_target, hinting| {
+        run_svg(&Config {
+            font_size: 12.0,
+            load_target,
+            hinting,
+            ..Config::default()
+        })
+        .unwrap()
+    };
+    // Light hinting only snaps vertically, mono snaps both ways.
+    assert_ne!(outlines(LoadFlag::TARGET_LIGHT, true), outlines(LoadFlag::TARGET_MONO, true));
+    // Without hinting the target has nothing to change.
+    assert_eq!(outlines(LoadFlag::TARGET_LIGHT, false), outlines(LoadFlag::TARGET_MONO, false));
+}
@@ -0,0 +1,541 @@
+use common::mesh::triangulate;
+use common::picture::record_picture;
+use common::raster::downsample;
+use freetype as ft;
+use rustybuzz::ttf_parser::{gdef::GlyphClass, GlyphId, Tag};
+use rustybuzz::{
+    script, shape, Direction, Face, Feature, Language, Script, UnicodeBuffer,
+};
+use skia_safe::{
+    Canvas, Color, ContourMeasureIter, EncodedImageFormat, Paint, Path, PathFillType, Point, Rect,
+    Surface,
+};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Shapes the text (a Devanagari conjunct by default), draws its FreeType outlines and
+/// returns them as PNG bytes.
+///
+/// Prints a warning for every pair of glyphs that `find_overlaps` flags.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (png_bytes, overlaps) = render(config)?;
+    for overlap in overlaps {
+        eprintln!(
+            "Warning: glyphs {} and {} overlap by {:.0}% of the narrower one; \
+             are the positions and the outlines scaled the same?",
+            overlap.first,
+            overlap.second,
+            overlap.ratio * 100.0,
+        );
+    }
+    Ok(png_bytes)
+}
+
+/// Like `run`, but returns the overlapping glyphs instead of printing them
+/// (and no image).
+pub fn glyph_overlaps(config: &Config) -> Result<Vec<GlyphOverlap>, Box<dyn Error>> {
+    Ok(render(config)?.1)
+}
+
+/// Draws the text, and checks the glyphs' ink boxes for overlaps.
+fn render(config: &Config) -> Result<(Vec<u8>, Vec<GlyphOverlap>), Box<dyn Error>> {
+    // Load font data and create a FreeType face.
+    let font_path = "NotoSans-VariableFont.ttf";
+    let font_data = fs::read(font_path)?;
+
+    let library = ft::Library::init()?;
+    let ft_face = library.new_face(font_path, 0)?;
+    
+    // Set the desired pixel size for FreeType.
+    let desired_font_size = 40.0;
+    ft_face.set_pixel_sizes(0, desired_font_size as u32)?;
+    
+    // Create a rustybuzz face from the font data.
+    let face = Face::from_slice(&font_data, 0).unwrap();
+    
+    // Get the font’s units per em (upem) and compute a scaling factor.
+    let upem = face.units_per_em() as f32;
+    let scale = config.position_scale.unwrap_or(desired_font_size / upem);
+    
+    // Build the UnicodeBuffer. The conjunct gets its script set up front
+    // (see `script_buffer`); other text is left for the shaper to guess.
+    let text = config.text.as_str();
+    let buffer = if text == CONJUNCT {
+        script_buffer(text, script::DEVANAGARI)
+    } else {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+        buffer
+    };
+    
+    // Shape the text.
+    // Note: The arguments are (face, features, buffer). We use an empty features slice.
+    let glyph_buffer = shape(&face, &[], buffer);
+    let glyph_infos = glyph_buffer.glyph_infos();
+    let glyph_positions = glyph_buffer.glyph_positions();
+
+    // With --verbose, log what the shaper produced. Glyph ids alone are hard to
+    // read, so we also look up each glyph's name (for the bundled font the
+    // conjunct above comes out as a single glyph, "uni0921094D0921").
+    if config.verbose {
+        for (info, pos) in glyph_infos.iter().zip(glyph_positions.iter()) {
+            eprintln!(
+                "glyph {:>5} {:<20} cluster {:>2} advance {:>5} offset ({}, {})",
+                info.glyph_id,
+                glyph_name(&face, info.glyph_id),
+                info.cluster,
+                pos.x_advance,
+                pos.x_offset,
+                pos.y_offset,
+            );
+        }
+    }
+    
+    // The glyphs' outlines, in image pixels, ready to draw.
+    let mut glyph_paths = Vec::new();
+    let origin_x = 50.0;
+    let origin_y = 100.0;
+    let mut x_accum = 0.0;
+    // Outline point counts before and after --simplify.
+    let (mut points_before, mut points_after) = (0, 0);
+    // With --mesh, the first glyph with an outline is also triangulated.
+    let mut mesh_path = config.dump_mesh.as_ref();
+    // Where each glyph's ink ended up, for the overlap check. Marks are left
+    // out: sitting on top of the glyph before them is their job.
+    let gdef = face.tables().gdef;
+    let mut ink_boxes = Vec::new();
+    
+    // Process each glyph from the shaping result.
+    for (index, (info, pos)) in glyph_infos.iter().zip(glyph_positions.iter()).enumerate() {
+        let glyph_id = info.glyph_id;
+        // The shaping positions are in font units; scale them to pixels.
+        let x_offset = pos.x_offset as f32 * scale;
+        let y_offset = pos.y_offset as f32 * scale;
+        let x_advance = pos.x_advance as f32 * scale;
+        
+        // The shaper's y axis points up and Skia's down, so a mark that GPOS
+        // raises has to move up here.
+        let glyph_origin_x = origin_x + x_accum + x_offset;
+        let glyph_origin_y = origin_y - y_offset;
+        
+        // Load the glyph into FreeType (the glyph index should match).
+        ft_face.load_glyph(glyph_id, ft::face::LoadFlag::NO_BITMAP)?;
+        let glyph_slot = ft_face.glyph();
+        
+        // If the glyph has an outline, convert it into a Skia Path.
+        if let Some(outline) = glyph_slot.outline() {
+            let mut path = Path::new();
+            path.set_fill_type(config.fill_type);
+            for contour in outline.contours_iter() {
+                let start_pt = contour.start();
+                let start_x = start_pt.x as f32 / 64.0;
+                let start_y = -start_pt.y as f32 / 64.0;
+                path.move_to((start_x, start_y));
+                
+                for curve in contour {
+                    match curve {
+                        ft::outline::Curve::Line(pt) => {
+                            let x = pt.x as f32 / 64.0;
+                            let y = -pt.y as f32 / 64.0;
+                            path.line_to((x, y));
+                        }
+                        ft::outline::Curve::Bezier2(pt1, pt2) => {
+                            let x1 = pt1.x as f32 / 64.0;
+                            let y1 = -pt1.y as f32 / 64.0;
+                            let x2 = pt2.x as f32 / 64.0;
+                            let y2 = -pt2.y as f32 / 64.0;
+                            path.quad_to((x1, y1), (x2, y2));
+                        }
+                        ft::outline::Curve::Bezier3(pt1, pt2, pt3) => {
+                            let x1 = pt1.x as f32 / 64.0;
+                            let y1 = -pt1.y as f32 / 64.0;
+                            let x2 = pt2.x as f32 / 64.0;
+                            let y2 = -pt2.y as f32 / 64.0;
+                            let x3 = pt3.x as f32 / 64.0;
+                            let y3 = -pt3.y as f32 / 64.0;
+                            path.cubic_to((x1, y1), (x2, y2), (x3, y3));
+                        }
+                    }
+                }
+                path.close();
+            }
+            // With --simplify, trade accuracy for fewer points.
+            points_before += path.count_points();
+            if let Some(tolerance) = config.simplify {
+                path = simplify_path(&path, tolerance);
+            }
+            points_after += path.count_points();
+
+            // Offset the path to the correct glyph position.
+            path.offset((glyph_origin_x, glyph_origin_y));
+            let class = gdef.map(|gdef| gdef.glyph_class(GlyphId(glyph_id as u16)));
+            if class != Some(Some(GlyphClass::Mark)) {
+                ink_boxes.push((index, path.compute_tight_bounds()));
+            }
+
+            // The mesh is in the same pixel coordinates as the image, with
+            // curves flattened to within a tenth of a pixel.
+            if let Some(mesh_path) = mesh_path.take() {
+                let mesh = triangulate(&path, 0.1).ok_or("Could not triangulate the glyph")?;
+                fs::write(mesh_path, mesh.to_obj())?;
+                eprintln!(
+                    "Glyph mesh ({} vertices, {} triangles) written to {}",
+                    mesh.vertices.len(),
+                    mesh.indices.len() / 3,
+                    mesh_path.display()
+                );
+            }
+            glyph_paths.push(path);
+        }
+        
+        // Advance the current horizontal position.
+        x_accum += x_advance;
+    }
+    
+    if let Some(tolerance) = config.simplify {
+        eprintln!(
+            "Simplified outlines with tolerance {tolerance}px: \
+             {points_before} -> {points_after} points"
+        );
+    }
+
+    let width = 500;
+    let height = 200;
+    let mut paint = Paint::default();
+    paint.set_anti_alias(config.anti_alias);
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+        for path in &glyph_paths {
+            canvas.draw_path(path, &paint);
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp, at the image's
+    // own size however much it's supersampled.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+
+    // Create a drawing surface. With --supersample N it is N times bigger
+    // in each direction, and scaled so we can still draw in image pixels.
+    let samples = config.supersample.max(1);
+    let mut surface =
+        Surface::new_raster_n32_premul((width * samples as i32, height * samples as i32))
+            .ok_or("Could not create surface")?;
+    let canvas = surface.canvas();
+    canvas.scale((samples as f32, samples as f32));
+    draw(canvas);
+
+    let mut image = surface.image_snapshot();
+    if samples > 1 {
+        image = downsample(&image, (width, height), Color::WHITE)
+            .ok_or("Could not downsample the image")?;
+    }
+    let png_data = image.encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+    Ok((png_data.as_bytes().to_vec(), find_overlaps(&ink_boxes, OVERLAP_THRESHOLD)))
+}
+
+/// The text `run` draws by default.
+const CONJUNCT: &str = "ड्ड";
+
+/// How much two neighbouring glyphs may overlap before `run` warns, as a
+/// fraction of the narrower one's width.
+const OVERLAP_THRESHOLD: f32 = 0.5;
+
+/// Two neighbouring glyphs whose ink overlaps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphOverlap {
+    /// Indices of the two glyphs in the shaped output.
+    pub first: usize,
+    pub second: usize,
+    /// How much their ink boxes overlap horizontally, as a fraction of the
+    /// narrower box's width.
+    pub ratio: f32,
+}
+
+/// Finds the neighbours among `ink_boxes` (glyph index and ink box, in
+/// visual order) whose boxes overlap by more than `threshold` of the narrower
+/// one's width.
+///
+/// Glyphs touch and overlap a little all the time (kerned pairs, italic
+/// overhangs, Devanagari's headline joining letters), but one glyph half on
+/// top of the next usually means a positioning bug. The classic one is a
+/// scale mismatch: FreeType scales the outlines to the pixel size itself,
+/// while the shaper's advances are in font units and have to be scaled by
+/// size / units per em by hand. Get that factor wrong (say, assume 2048 units
+/// per em for a 1000-unit font) and the outlines are the right size but
+/// crammed together. It's a heuristic, though: some scripts overlap on
+/// purpose, like the Devanagari vowel sign I, which reaches over the
+/// consonant after it.
+pub fn find_overlaps(ink_boxes: &[(usize, Rect)], threshold: f32) -> Vec<GlyphOverlap> {
+    ink_boxes
+        .windows(2)
+        .filter_map(|pair| {
+            let ((first, a), (second, b)) = (pair[0], pair[1]);
+            // Boxes side by side but at different heights (a superscript,
+            // say) don't collide.
+            if a.bottom <= b.top || b.bottom <= a.top {
+                return None;
+            }
+            let overlap = a.right.min(b.right) - a.left.max(b.left);
+            let narrower = a.width().min(b.width());
+            let ratio = overlap / narrower;
+            (narrower > 0.0 && ratio > threshold).then_some(GlyphOverlap {
+                first,
+                second,
+                ratio,
+            })
+        })
+        .collect()
+}
+
+/// Shapes `text` as a single run in `script` and returns the glyph ids in
+/// visual order, which is where Indic reordering shows: Tamil "கொ" is KA
+/// followed by the two-part vowel sign O, but comes out as the E part, then
+/// KA, then the AA part.
+pub fn shape_script(
+    font_data: &[u8],
+    text: &str,
+    script: Script,
+) -> Result<Vec<u32>, Box<dyn Error>> {
+    let face = Face::from_slice(font_data, 0).ok_or("Could not parse the font")?;
+    let glyph_buffer = shape(&face, &[], script_buffer(text, script));
+    Ok(glyph_buffer.glyph_infos().iter().map(|info| info.glyph_id).collect())
+}
+
+/// A buffer for `text` with its script, language and direction set up front,
+/// rather than left for the shaper to guess.
+///
+/// The script picks the shaper: only an Indic script tag gets the Indic one,
+/// which splits two-part vowel signs, moves pre-base matras and reph, and
+/// applies the font's `akhn`/`blwf`/`pstf` features in syllable order. The
+/// language picks the font's language system where it has one (Tamil and
+/// Bengali fonts sometimes do), and otherwise changes nothing.
+fn script_buffer(text: &str, script: Script) -> UnicodeBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_script(script);
+    buffer.set_direction(Direction::LeftToRight);
+    if let Some(language) = script_language(script) {
+        buffer.set_language(language);
+    }
+    buffer
+}
+
+/// The main language written in each of the Indic scripts we test, if any.
+fn script_language(script: Script) -> Option<Language> {
+    let tag = match script {
+        script::DEVANAGARI => "hi",
+        script::BENGALI => "bn",
+        script::TAMIL => "ta",
+        script::TELUGU => "te",
+        _ => return None,
+    };
+    tag.parse().ok()
+}
+
+/// Returns the glyph's name from the font's `post` (or CFF) table.
+///
+/// Not every font carries glyph names (the `post` table can be version 3.0,
+/// which has none), so we fall back to a `gidN` placeholder.
+fn glyph_name(face: &Face, glyph_id: u32) -> String {
+    face.glyph_name(GlyphId(glyph_id as u16))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("gid{glyph_id}"))
+}
+
+/// A stretch of text that an OpenType feature shaped differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureChange {
+    /// Byte range of the text.
+    pub range: Range<usize>,
+    /// Glyph ids for the text with the feature turned off...
+    pub without: Vec<u32>,
+    /// ...and with it turned on.
+    pub with: Vec<u32>,
+}
+
+/// Shapes `text` with the font twice, with `feature` off and on, and returns
+/// where the glyphs differ. That is exactly where the feature fired.
+///
+/// Meant for contextual alternates (`calt`), which handwriting and
+/// connecting-script fonts use to pick a glyph based on its neighbours: a
+/// font can list the feature and still not apply it to a given piece of text.
+/// It works for any substitution feature, though, e.g. `liga`.
+///
+/// A substitution can change how many glyphs there are, and merges the
+/// clusters of the characters it joins (an "ffi" ligature has one cluster for
+/// three characters). So the two results are compared per stretch of text
+/// between cluster boundaries that both of them have, rather than glyph by
+/// glyph.
+pub fn feature_changes(
+    font_data: &[u8],
+    text: &str,
+    feature: &[u8; 4],
+) -> Result<Vec<FeatureChange>, Box<dyn Error>> {
+    let face = Face::from_slice(font_data, 0).ok_or("Could not parse the font")?;
+    let shaped = |value| {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+        let features = [Feature::new(Tag::from_bytes(feature), value, ..)];
+        let glyph_buffer = shape(&face, &features, buffer);
+        glyph_buffer
+            .glyph_infos()
+            .iter()
+            .map(|info| (info.cluster as usize, info.glyph_id))
+            .collect::<Vec<_>>()
+    };
+    let (without, with) = (shaped(0), shaped(1));
+
+    let cluster_starts = |glyphs: &[(usize, u32)]| {
+        glyphs.iter().map(|&(cluster, _)| cluster).collect::<BTreeSet<_>>()
+    };
+    let mut boundaries: Vec<usize> =
+        cluster_starts(&without).intersection(&cluster_starts(&with)).copied().collect();
+    boundaries.push(text.len());
+
+    let glyphs_in = |glyphs: &[(usize, u32)], range: &Range<usize>| {
+        glyphs
+            .iter()
+            .filter(|(cluster, _)| range.contains(cluster))
+            .map(|&(_, glyph_id)| glyph_id)
+            .collect::<Vec<_>>()
+    };
+    Ok(boundaries
+        .windows(2)
+        .map(|pair| pair[0]..pair[1])
+        .filter_map(|range| {
+            let (without, with) = (glyphs_in(&without, &range), glyphs_in(&with, &range));
+            (without != with).then_some(FeatureChange { range, without, with })
+        })
+        .collect())
+}
+
+/// Simplifies an outline by flattening its curves into short line segments
+/// and then dropping every point that lies within `tolerance` pixels of the
+/// line through its neighbours (Ramer–Douglas–Peucker).
+///
+/// Small tolerances (around 0.1px) remove redundant points without visible
+/// change; large ones (a few pixels) turn the curves into obvious facets.
+/// Note that Skia's own `Path::simplify` (from pathops) is something else: it
+/// resolves overlapping contours and doesn't reduce the point count.
+fn simplify_path(path: &Path, tolerance: f32) -> Path {
+    // Sample finely enough that flattening itself doesn't lose any detail.
+    let step = 0.25;
+    let mut simplified = Path::new();
+    simplified.set_fill_type(path.fill_type());
+    for contour in ContourMeasureIter::new(path, true, None) {
+        let samples = (contour.length() / step).ceil().max(2.0) as usize;
+        let points: Vec<Point> = (0..=samples)
+            .filter_map(|i| contour.pos_tan(contour.length() * i as f32 / samples as f32))
+            .map(|(point, _)| point)
+            .collect();
+        let kept = douglas_peucker(&points, tolerance);
+        if let Some((first, rest)) = kept.split_first() {
+            simplified.move_to(*first);
+            for point in rest {
+                simplified.line_to(*point);
+            }
+            simplified.close();
+        }
+    }
+    simplified
+}
+
+/// Returns the points of the polyline `points` that are needed to stay within
+/// `tolerance` of it. The first and last points are always kept.
+fn douglas_peucker(points: &[Point], tolerance: f32) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let chord = last - first;
+    let distance = |point: Point| {
+        let length = chord.length();
+        if length == 0.0 {
+            (point - first).length()
+        } else {
+            chord.cross(point - first).abs() / length
+        }
+    };
+
+    // Find the point furthest from the chord between the two ends.
+    let (index, max_distance) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, &point)| (i + 1, distance(point)))
+        .fold((0, 0.0), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+    if max_distance <= tolerance {
+        return vec![first, last];
+    }
+    // It's too far off, so keep it and simplify both halves around it.
+    let mut kept = douglas_peucker(&points[..=index], tolerance);
+    kept.pop();
+    kept.extend(douglas_peucker(&points[index..], tolerance));
+    kept
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// How overlapping contours are filled. TrueType and CFF outlines are
+    /// defined with the nonzero winding rule, which is Skia's `Winding`; that
+    /// is what keeps overlapping strokes (common in CJK and variable fonts)
+    /// solid while still punching out holes drawn in the opposite direction,
+    /// like the inside of an "o". `EvenOdd` is only here for comparison: it
+    /// turns every overlap into a hole.
+    pub fill_type: PathFillType,
+    /// Print every shaped glyph (id, name, cluster, position).
+    pub verbose: bool,
+    /// Outline simplification tolerance in pixels, if any.
+    pub simplify: Option<f32>,
+    /// Also save the first glyph as a triangle mesh (.obj) at this path.
+    pub dump_mesh: Option<PathBuf>,
+    /// Anti-alias the outlines. Note that `Paint::default()` doesn't: a path
+    /// drawn with a default paint gets hard, stair-stepped edges.
+    pub anti_alias: bool,
+    /// Draw at this many times the size in each direction and shrink the
+    /// result, averaging `supersample²` samples per pixel. 1 draws directly.
+    ///
+    /// Text drawn as a blob (or with `draw_str`) goes through Skia's glyph
+    /// cache instead: glyph masks are rendered once per size with hinting,
+    /// subpixel positioning and a contrast boost for thin stems, and are
+    /// always anti-aliased. A path gets none of that, just the paint's edge
+    /// coverage, which is why path-drawn text can look lighter and rougher,
+    /// most visibly on diagonal stems. Anti-aliasing gets most of the way
+    /// there; supersampling smooths edges further, and with anti-aliasing
+    /// off shows how the sample count alone reduces the jaggies.
+    pub supersample: u32,
+    /// The text to draw.
+    pub text: String,
+    /// Pixels per font unit for the shaper's positions and advances. `None`
+    /// is the right value, the font size over the font's units per em; this
+    /// is here to try out what a wrong one does (see `find_overlaps`).
+    pub position_scale: Option<f32>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            fill_type: PathFillType::Winding,
+            verbose: false,
+            simplify: None,
+            dump_mesh: None,
+            anti_alias: true,
+            supersample: 1,
+            text: CONJUNCT.to_string(),
+            position_scale: None,
+            dump_picture: None,
+        }
+    }
+}
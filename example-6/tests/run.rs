@@ -0,0 +1,338 @@
+use common::raster::read_rgba;
+use example_6::{feature_changes, glyph_overlaps, run, shape_script, Config};
+use rustybuzz::{script, Face, Script};
+use skia_safe::{AlphaType, Data, Image, PathFillType};
+use std::fs;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn simplified_outlines_change_the_edges_but_keep_the_shape() {
+    let plain = render_gray(&Config::default());
+    let simplified = render_gray(&Config {
+        simplify: Some(2.0),
+        ..Config::default()
+    });
+    assert_ne!(simplified, plain);
+    // Dropping points within 2px of the outline moves edges, not strokes:
+    // about as much of the image is still dark.
+    let dark = |pixels: &[u8]| pixels.iter().filter(|&&red| red < 128).count() as f32;
+    let ratio = dark(&simplified) / dark(&plain);
+    assert!((0.5..1.5).contains(&ratio), "{ratio}");
+}
+
+#[test]
+fn dumps_the_glyph_mesh() {
+    let path = std::env::temp_dir().join("example-6-glyph.obj");
+    let config = Config {
+        dump_mesh: Some(path.clone()),
+        ..Config::default()
+    };
+    run(&config).unwrap();
+
+    let obj = fs::read_to_string(&path).unwrap();
+    assert!(obj.lines().any(|line| line.starts_with("v ")));
+    assert!(obj.lines().any(|line| line.starts_with("f ")));
+}
+
+#[test]
+fn feature_changes_finds_where_a_feature_fired() {
+    let font_data = fs::read("NotoSans-VariableFont.ttf").unwrap();
+    let text = "An office coffee";
+
+    // None of the bundled fonts has contextual alternates, so `calt` can't
+    // change anything here...
+    assert!(feature_changes(&font_data, text, b"calt").unwrap().is_empty());
+
+    // ...but ligatures show the comparison at work: "ffi" and "ff" each
+    // become one glyph, and nothing else changes.
+    let changes = feature_changes(&font_data, text, b"liga").unwrap();
+    let ranges: Vec<&str> = changes.iter().map(|change| &text[change.range.clone()]).collect();
+    assert_eq!(ranges, ["ffi", "ff"]);
+    assert_eq!((changes[0].without.len(), changes[0].with.len()), (3, 1));
+    assert_eq!((changes[1].without.len(), changes[1].with.len()), (2, 1));
+}
+
+#[test]
+fn calt_changes_only_where_its_context_matches() {
+    // The bundled font with its GSUB swapped for one whose calt turns "o"
+    // into "O" right after "f".
+    let noto = fs::read("NotoSans-VariableFont.ttf").unwrap();
+    let face = Face::from_slice(&noto, 0).unwrap();
+    let glyph = |c| face.glyph_index(c).unwrap().0;
+    let gsub = calt_gsub(glyph('f'), glyph('o'), glyph('O'));
+    let font_data = rebuild_font(&noto, |tag| tag != b"GSUB", vec![(*b"GSUB", gsub)]);
+
+    // The first "o" of "foo" and the one in "fox" follow an "f"; the second
+    // "o" of "foo" and the one in "of" don't.
+    let text = "foo of fox";
+    let changes = feature_changes(&font_data, text, b"calt").unwrap();
+    let ranges: Vec<_> = changes.iter().map(|change| change.range.clone()).collect();
+    assert_eq!(ranges, [1..2, 8..9]);
+    for change in &changes {
+        assert_eq!(change.without, [u32::from(glyph('o'))]);
+        assert_eq!(change.with, [u32::from(glyph('O'))]);
+    }
+}
+
+/// `data` rebuilt with the tables `keep` accepts, plus `extra`. Checksums are
+/// left at zero; neither FreeType nor HarfBuzz checks them.
+fn rebuild_font(
+    data: &[u8],
+    keep: impl Fn(&[u8]) -> bool,
+    extra: Vec<([u8; 4], Vec<u8>)>,
+) -> Vec<u8> {
+    let u16_at = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+    let u32_at = |offset: usize| u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = (0..u16_at(4))
+        .map(|i| 12 + 16 * i)
+        .filter(|&record| keep(&data[record..record + 4]))
+        .map(|record| {
+            let (offset, length) = (u32_at(record + 8) as usize, u32_at(record + 12) as usize);
+            (data[record..record + 4].try_into().unwrap(), data[offset..offset + length].to_vec())
+        })
+        .chain(extra)
+        .collect();
+    // The table directory is sorted by tag, for binary search.
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = 16 << entry_selector;
+    let mut font = data[..4].to_vec();
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend(value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    let mut bodies = Vec::new();
+    for (tag, body) in &tables {
+        font.extend(tag);
+        font.extend(0u32.to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((body.len() as u32).to_be_bytes());
+        // Tables start on 4-byte boundaries.
+        let padded = body.len().next_multiple_of(4);
+        bodies.extend(body);
+        bodies.resize(bodies.len() + padded - body.len(), 0);
+        offset += padded;
+    }
+    font.extend(bodies);
+    font
+}
+
+/// A `GSUB` table whose only feature is `calt`, replacing `input` with
+/// `output` where it follows `after`. All scripts use it, through `DFLT`.
+///
+/// The feature's lookup is a chaining context (type 6, format 3): `after`
+/// as the backtrack, `input` as the input, and no lookahead. Where it
+/// matches, it applies a second lookup, a single substitution (type 1,
+/// format 2), to the input glyph. A font with real contextual alternates
+/// does the same with classes of glyphs rather than single ones.
+fn calt_gsub(after: u16, input: u16, output: u16) -> Vec<u8> {
+    let coverage = |glyph: u16| [1, 1, glyph];
+    // Version 1.0, then the script, feature and lookup lists' offsets.
+    let header = [1, 0, 10, 30, 44];
+    // One script, DFLT, whose default language system has feature 0: no
+    // reordering table, no required feature, one feature index.
+    let scripts = [1, u16::from_be_bytes(*b"DF"), u16::from_be_bytes(*b"LT"), 8, 4, 0];
+    let language_system = [0, 0xffff, 1, 0];
+    // One feature, calt, with lookup 0 and no parameters.
+    let features = [1, u16::from_be_bytes(*b"ca"), u16::from_be_bytes(*b"lt"), 8, 0, 1, 0];
+    // Two lookups: type 6 at offset 6, 38 bytes long, then type 1.
+    let lookups = [2, 6, 44];
+    let chaining = [6, 0, 1, 8];
+    // Format 3: one backtrack coverage (at 18), one input coverage (at 24),
+    // no lookahead, and one record applying lookup 1 to input glyph 0.
+    let context = [3, 1, 18, 1, 24, 0, 1, 0, 1];
+    let single = [1, 0, 1, 8];
+    // Format 2: the coverage at 8, and one substitute glyph.
+    let substitution = [2, 8, 1, output];
+    [
+        &header[..],
+        &scripts,
+        &language_system,
+        &features,
+        &lookups,
+        &chaining,
+        &context,
+        &coverage(after),
+        &coverage(input),
+        &single,
+        &substitution,
+        &coverage(input),
+    ]
+    .concat()
+    .iter()
+    .flat_map(|field: &u16| field.to_be_bytes())
+    .collect()
+}
+
+#[test]
+fn devanagari_conjunct_needs_the_devanagari_script() {
+    let font_data = fs::read("NotoSans-VariableFont.ttf").unwrap();
+    // DDA + VIRAMA + DDA becomes the stacked conjunct glyph
+    // "uni0921094D0921", with no mark left over to position.
+    assert_eq!(shape_script(&font_data, "ड्ड", script::DEVANAGARI).unwrap(), [4357]);
+    // Misconfigured as Latin: DDA, a bare virama mark, DDA again. This is
+    // how a shaper set up with the wrong script breaks Devanagari silently.
+    assert_eq!(shape_script(&font_data, "ड्ड", script::LATIN).unwrap().len(), 3);
+}
+
+/// Shapes a syllable with the script's own Noto font and checks the glyphs
+/// come out in the expected visual order. `expected` lists characters whose
+/// cmap glyph should appear at that position, or `None` for a glyph that only
+/// shaping produces (like a below-base form), which no character in the text
+/// maps to directly.
+///
+/// Only Devanagari is covered by the bundled font, so these need the
+/// script's font next to the example (e.g. NotoSansTamil-Regular.ttf from
+/// fonts.google.com) and are ignored by default. Put the fonts there and run
+/// them with `cargo test -- --ignored`.
+fn check_reordering(font_path: &str, text: &str, script: Script, expected: &[Option<char>]) {
+    let font_data = fs::read(font_path).unwrap_or_else(|err| panic!("{font_path}: {err}"));
+    let face = Face::from_slice(&font_data, 0).unwrap();
+    let cmap_glyph = |c: char| u32::from(face.glyph_index(c).unwrap().0);
+    let cmap_glyphs: Vec<u32> = text.chars().map(cmap_glyph).collect();
+
+    let glyphs = shape_script(&font_data, text, script).unwrap();
+    assert_eq!(glyphs.len(), expected.len(), "{text}: {glyphs:?}");
+    for (&glyph, expected) in glyphs.iter().zip(expected) {
+        match expected {
+            Some(c) => assert_eq!(glyph, cmap_glyph(*c), "{text}: {glyphs:?}"),
+            None => assert!(glyph != 0 && !cmap_glyphs.contains(&glyph), "{text}: {glyphs:?}"),
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs NotoSansTamil-Regular.ttf"]
+fn tamil_two_part_vowel_sign_is_split_around_the_consonant() {
+    // KA + O: the E part of the vowel sign is drawn before KA, the AA part after.
+    let expected = [Some('\u{0BC6}'), Some('\u{0B95}'), Some('\u{0BBE}')];
+    check_reordering("NotoSansTamil-Regular.ttf", "கொ", script::TAMIL, &expected);
+}
+
+#[test]
+#[ignore = "needs NotoSansBengali-Regular.ttf"]
+fn bengali_two_part_vowel_sign_is_split_around_the_consonant() {
+    // KA + O, split the same way as in Tamil.
+    let expected = [Some('\u{09C7}'), Some('\u{0995}'), Some('\u{09BE}')];
+    check_reordering("NotoSansBengali-Regular.ttf", "কো", script::BENGALI, &expected);
+}
+
+#[test]
+#[ignore = "needs NotoSansTelugu-Regular.ttf"]
+fn telugu_conjunct_uses_a_below_base_form() {
+    // SA + VIRAMA + TA: SA stays the base and TA becomes a below-base form;
+    // the virama itself is gone.
+    let expected = [Some('\u{0C38}'), None];
+    check_reordering("NotoSansTelugu-Regular.ttf", "స్త", script::TELUGU, &expected);
+}
+
+/// The red channel of a rendering of the conjunct, one byte per pixel.
+fn render_gray(config: &Config) -> Vec<u8> {
+    let png = run(config).unwrap();
+    let image = Image::from_encoded(Data::new_copy(&png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    pixels.chunks(4).map(|p| p[0]).collect()
+}
+
+#[test]
+fn the_hole_in_an_o_stays_open_with_either_fill_rule() {
+    for fill_type in [PathFillType::Winding, PathFillType::EvenOdd] {
+        let pixels = render_gray(&Config {
+            text: "o".to_string(),
+            fill_type,
+            ..Config::default()
+        });
+        // The image is 500 pixels wide; find the box around the ink.
+        let inked: Vec<(usize, usize)> = pixels
+            .iter()
+            .enumerate()
+            .filter(|&(_, &red)| red < 128)
+            .map(|(i, _)| (i % 500, i / 500))
+            .collect();
+        assert!(!inked.is_empty(), "{fill_type:?}: nothing drawn");
+        let (xs, ys): (Vec<usize>, Vec<usize>) = inked.into_iter().unzip();
+        let center_x = (xs.iter().min().unwrap() + xs.iter().max().unwrap()) / 2;
+        let center_y = (ys.iter().min().unwrap() + ys.iter().max().unwrap()) / 2;
+        // The middle of the box is inside the counter, which is background.
+        assert_eq!(pixels[center_y * 500 + center_x], 255, "{fill_type:?}");
+    }
+}
+
+/// The rows of a rendering of `text` that have any ink in them.
+fn inked_rows(text: &str) -> Vec<usize> {
+    let pixels = render_gray(&Config {
+        text: text.to_string(),
+        ..Config::default()
+    });
+    // The image is 500 pixels wide.
+    (0..pixels.len() / 500)
+        .filter(|&y| pixels[y * 500..(y + 1) * 500].iter().any(|&red| red < 128))
+        .collect()
+}
+
+#[test]
+fn marks_that_gpos_raises_are_drawn_raised() {
+    // The combining acute is drawn for lowercase letters; on a capital H the
+    // font's mark anchors raise it by 178 units (7px at 40px), clear of the
+    // top of the H.
+    let capital = inked_rows("H");
+    let accented = inked_rows("H\u{301}");
+    assert!(accented[0] + 5 < capital[0], "{accented:?} {capital:?}");
+    // There's a gap of clean rows between the accent and the H.
+    assert!(accented.windows(2).any(|pair| pair[1] > pair[0] + 1), "{accented:?}");
+}
+
+#[test]
+fn more_samples_mean_smoother_edges() {
+    // A well-sampled rendering to compare against.
+    let reference = render_gray(&Config {
+        supersample: 8,
+        ..Config::default()
+    });
+    let error = |supersample| {
+        let pixels = render_gray(&Config {
+            anti_alias: false,
+            supersample,
+            ..Config::default()
+        });
+        let total: u32 = pixels.iter().zip(&reference).map(|(&a, &b)| a.abs_diff(b) as u32).sum();
+        total as f32 / pixels.len() as f32
+    };
+
+    // Without anti-aliasing every edge pixel is either in or out, so the
+    // diagonal stems are stair-stepped. Each step up in samples gets closer
+    // to the smooth edge.
+    let (one, two, four) = (error(1), error(2), error(4));
+    assert!(two < one, "error with 2x2 samples {two}, with 1 sample {one}");
+    assert!(four < two, "error with 4x4 samples {four}, with 2x2 samples {two}");
+}
+
+#[test]
+fn overlap_check_catches_a_wrong_position_scale() {
+    let config = Config {
+        text: "Hello, world".to_string(),
+        ..Config::default()
+    };
+    assert_eq!(glyph_overlaps(&config).unwrap(), []);
+    assert_eq!(glyph_overlaps(&Config::default()).unwrap(), []);
+
+    // Scaled as if the font had 2048 units per em rather than 1000, the
+    // advances are half what they should be, while FreeType still draws the
+    // outlines at full size.
+    let wrong = Config {
+        position_scale: Some(40.0 / 2048.0),
+        ..config
+    };
+    let overlaps = glyph_overlaps(&wrong).unwrap();
+    assert!(overlaps.len() >= 3, "{overlaps:?}");
+    assert!(overlaps.iter().all(|overlap| overlap.second == overlap.first + 1));
+}
@@ -0,0 +1,469 @@
+use common::font::LoadedFont;
+use common::metrics::line_metrics;
+use common::picture::record_picture;
+use common::shaping::shape_text;
+use common::truncate::{truncate_with_ellipsis, ELLIPSIS};
+use common::wrap::{wrap_hyphenated, WrappedLine};
+use harfbuzz_rs::{shape, Direction, Font as HbFont, UnicodeBuffer};
+use skia_safe::{
+    Canvas, Color, EncodedImageFormat, Font, Paint, Point, Surface, TextBlob, TextBlobBuilder,
+};
+use hyphenation::{Hyphenator, Language, Load, Standard};
+use unicode_bidi::{BidiInfo, Level, ParagraphInfo};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Wraps, reorders and draws the paragraph and returns it as PNG bytes.
+pub fn run(config: &Config) -> Result<Vec<u8>, Box<dyn Error>> {
+    render(config, &mut LineCache::default())
+}
+
+/// Like `run`, but reuses the lines in `cache` that were shaped by an earlier
+/// call, and leaves this call's lines in it for the next one.
+pub fn render(config: &Config, cache: &mut LineCache) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(render_lines(config, cache)?.0)
+}
+
+/// The y of each line's baseline, from the top of the image `run` draws,
+/// first line first. Consecutive lines are one line height apart.
+pub fn line_baselines(config: &Config) -> Result<Vec<f32>, Box<dyn Error>> {
+    Ok(render_lines(config, &mut LineCache::default())?.1)
+}
+
+/// Draws the paragraph and returns the PNG bytes along with each line's
+/// baseline.
+fn render_lines(
+    config: &Config,
+    cache: &mut LineCache,
+) -> Result<(Vec<u8>, Vec<f32>), Box<dyn Error>> {
+    // 1. Load the font. Rubik (the default) covers both Latin and Arabic, so
+    //    we don't need font fallback here and can focus on line breaking and
+    //    direction.
+    let font_data = fs::read(&config.font_path)?;
+    let font_size = 20.0;
+    let font = LoadedFont::from_bytes(&font_data, font_size)?;
+    let skia_font = font.skia_font();
+
+    //    Lines are spaced the way the font asks for: its ascent, descent and
+    //    line gap (`leading`) added up, the spacing browsers use for
+    //    `line-height: normal`. With --line-height it's a multiple of the
+    //    font size instead. (This example used to space lines 1.5em apart,
+    //    which for Rubik at 20px is 30px against its own 23.7px: Rubik has no
+    //    line gap, and ascent plus descent are only 1.185em.)
+    let (_, metrics) = skia_font.metrics();
+    let line_height = match config.line_height {
+        Some(multiple) => font_size * multiple,
+        None => line_metrics([metrics]).height(),
+    };
+
+    // 2. Run the bidi algorithm over the whole paragraph *before* wrapping.
+    //    Embedding levels depend on the surrounding text (a space between two
+    //    Arabic words is RTL, the same space between two English words is LTR),
+    //    so they have to be resolved with the full paragraph as context.
+    //    The paragraph direction comes from --direction, or from the first
+    //    strong character when it isn't given. A `\n` starts a new paragraph,
+    //    which detects its own direction.
+    let text = config.text.as_str();
+    let bidi_info = BidiInfo::new(text, config.direction);
+
+    // 3. Wrap the paragraph in logical order. The width of a line doesn't
+    //    depend on its visual order, so we can measure before reordering.
+    let lines = wrap(config, &font)?;
+
+    // 4. Reorder and shape each line. Reordering (rules L1 and L2 in UAX #9)
+    //    has to happen per line: which words end up on a line changes the
+    //    visual order of the runs on it, so the same paragraph wrapped at a
+    //    different width can put an Arabic phrase at the other end of a line.
+    //    Lines are aligned to their paragraph's starting edge: right for RTL
+    //    paragraphs, left for LTR ones.
+    //
+    //    Each line becomes its own blob, drawn at the line's position, so a
+    //    line that is exactly the same as in the previous render (same font
+    //    and size, text and bidi levels) can reuse its blob from the cache instead of being
+    //    shaped again. Cached lines that don't show up this time are dropped.
+    let margin = 20.0;
+    let mut placed_lines = Vec::new();
+    let mut used = HashMap::new();
+    for WrappedLine { range: line, hyphenated } in &lines {
+        let paragraph = paragraph_at(&bidi_info, line.start).ok_or("The text is empty")?;
+        let key = LineKey {
+            font_path: config.font_path.clone(),
+            font_size: font_size.to_bits(),
+            text: text[line.clone()].to_string(),
+            paragraph_level: paragraph.level.number(),
+            levels: bidi_info.levels[line.clone()].iter().map(|level| level.number()).collect(),
+            hyphenated: *hyphenated,
+        };
+        let shaped = match cache.lines.remove(&key).or_else(|| used.get(&key).cloned()) {
+            Some(shaped) => shaped,
+            None => {
+                cache.reshapes += 1;
+                let mut runs = shape_line(&font.hb_font, &bidi_info, paragraph, line.clone());
+                if *hyphenated {
+                    // The hyphen goes at the end of the line, which is on
+                    // the left in an RTL paragraph.
+                    let hyphen = shape_hyphen(&font.hb_font);
+                    let index = if paragraph.level.is_rtl() { 0 } else { runs.len() };
+                    runs.insert(index, hyphen);
+                }
+                let mut builder = TextBlobBuilder::new();
+                add_runs(&mut builder, &skia_font, &runs, Point::new(0.0, 0.0));
+                ShapedLine {
+                    blob: builder.make(),
+                    width: runs.iter().map(|run| run.width).sum(),
+                }
+            }
+        };
+
+        placed_lines.push((shaped.blob.clone(), shaped.width, paragraph.level.is_rtl()));
+        used.insert(key, shaped);
+    }
+    cache.lines = used;
+
+    // 5. Size the surface to fit the lines exactly, plus the margin. The
+    //    text is as wide as the wrapping width, or with --fit-width as wide
+    //    as its widest line (either way, never narrower than a line that had
+    //    to overflow, like a single long word). Vertically, the first line's
+    //    ascent and the last line's descent are what touch the margins; the
+    //    lines in between are a line height apart.
+    let widest_line = placed_lines.iter().map(|&(_, width, _)| width).fold(0.0, f32::max);
+    let text_width = if config.fit_width {
+        widest_line
+    } else {
+        config.width.max(widest_line)
+    };
+    let first_baseline = margin - metrics.ascent;
+    let baselines: Vec<f32> = (0..placed_lines.len())
+        .map(|line_index| first_baseline + line_index as f32 * line_height)
+        .collect();
+    let last_baseline = baselines.last().copied().unwrap_or(first_baseline);
+    let width = (text_width + 2.0 * margin).ceil() as i32;
+    let height = (last_baseline + metrics.descent + margin).ceil() as i32;
+    let draw = |canvas: &Canvas| {
+        canvas.clear(Color::WHITE);
+
+        // Mark the wrapping width, so the alignment is easy to check.
+        let mut edge_paint = Paint::default();
+        edge_paint.set_color(Color::from_argb(0x40, 0x00, 0x00, 0xff));
+        canvas.draw_line((margin, 0.0), (margin, height as f32), &edge_paint);
+        let right_edge = margin + text_width;
+        canvas.draw_line((right_edge, 0.0), (right_edge, height as f32), &edge_paint);
+
+        for ((blob, line_width, rtl), &baseline) in placed_lines.iter().zip(&baselines) {
+            let x = if *rtl {
+                right_edge - line_width
+            } else {
+                margin
+            };
+            if let Some(blob) = blob {
+                canvas.draw_text_blob(blob, (x, baseline), &Paint::default());
+            }
+        }
+    };
+
+    // With --dump-picture, also save the drawing as an .skp.
+    if let Some(path) = &config.dump_picture {
+        let picture = record_picture((width, height), draw).ok_or("Could not record a picture")?;
+        fs::write(path, picture.serialize().as_bytes())?;
+    }
+    let mut surface = Surface::new_raster_n32_premul((width, height))
+        .ok_or("Could not create a surface")?;
+    draw(surface.canvas());
+
+    // 6. Encode the result as a PNG.
+    let image = surface.image_snapshot();
+    let png_data = image
+        .encode_to_data(EncodedImageFormat::PNG)
+        .ok_or("Failed to encode image")?;
+
+    eprintln!("Laid out {} lines", lines.len());
+    Ok((png_data.as_bytes().to_vec(), baselines))
+}
+
+/// The paragraph's lines, as drawn: with a hyphen added after each line that
+/// ends in the middle of a word.
+pub fn wrapped_lines(config: &Config) -> Result<Vec<String>, Box<dyn Error>> {
+    let font_data = fs::read(&config.font_path)?;
+    let font = LoadedFont::from_bytes(&font_data, 20.0)?;
+    let lines = wrap(config, &font)?;
+    Ok(lines.iter().map(|line| line_text(config, line)).collect())
+}
+
+/// A line as `run` draws it, split into directional runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisualLine {
+    /// The line's paragraph is right-to-left, so the line is aligned right.
+    pub rtl: bool,
+    /// The runs in visual (left-to-right) order, each with its text (in
+    /// logical order) and whether it's right-to-left.
+    pub runs: Vec<(String, bool)>,
+}
+
+/// The paragraph's lines, each split into directional runs and reordered
+/// for display like `run` does. Hyphens added by wrapping are left out.
+pub fn visual_lines(config: &Config) -> Result<Vec<VisualLine>, Box<dyn Error>> {
+    let font_data = fs::read(&config.font_path)?;
+    let font = LoadedFont::from_bytes(&font_data, 20.0)?;
+    let text = config.text.as_str();
+    let bidi_info = BidiInfo::new(text, config.direction);
+    let mut lines = Vec::new();
+    for WrappedLine { range: line, .. } in wrap(config, &font)? {
+        let paragraph = paragraph_at(&bidi_info, line.start).ok_or("The text is empty")?;
+        let runs = directional_runs(&bidi_info, paragraph, line)
+            .into_iter()
+            .map(|(run, rtl)| (text[run].to_string(), rtl))
+            .collect();
+        lines.push(VisualLine {
+            rtl: paragraph.level.is_rtl(),
+            runs,
+        });
+    }
+    Ok(lines)
+}
+
+/// The result of `clamp_lines`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClampedText {
+    /// The lines to show: all of them if the text fits, otherwise the first
+    /// `max_lines`, the last of them ending in an ellipsis.
+    pub lines: Vec<String>,
+    /// The text needed more than `max_lines` lines.
+    pub overflowed: bool,
+}
+
+/// Wraps the text like `run` and keeps at most `max_lines` lines, like a
+/// list item that shows two lines of a description (CSS `line-clamp`).
+///
+/// When the text doesn't fit, the last line shown ends in "…", cut shorter
+/// (between grapheme clusters) if the ellipsis doesn't fit after it. A word
+/// too long for a line is already broken up by the wrapping, so a single
+/// long word comes out as `max_lines` pieces, the last one ellipsized.
+pub fn clamp_lines(config: &Config, max_lines: usize) -> Result<ClampedText, Box<dyn Error>> {
+    let font_data = fs::read(&config.font_path)?;
+    let font = LoadedFont::from_bytes(&font_data, 20.0)?;
+    let wrapped = wrap(config, &font)?;
+    let overflowed = wrapped.len() > max_lines;
+    let mut lines: Vec<String> =
+        wrapped.iter().take(max_lines).map(|line| line_text(config, line)).collect();
+
+    if let (true, Some(last)) = (overflowed, max_lines.checked_sub(1)) {
+        // The ellipsis replaces any hyphen: the text goes on either way.
+        let text = config.text[wrapped[last].range.clone()].trim_end();
+        let measure = |line: &str| shape_text(&font.hb_font, line, &[]).width;
+        // If the line is too wide with the ellipsis added, it's cut shorter
+        // until it fits. Either way it ends in the ellipsis, unless not even
+        // that fits on its own.
+        lines[last] = truncate_with_ellipsis(&format!("{text}{ELLIPSIS}"), config.width, measure);
+    }
+    Ok(ClampedText { lines, overflowed })
+}
+
+/// A wrapped line's text, as drawn: with a hyphen if it ends mid-word.
+fn line_text(config: &Config, line: &WrappedLine) -> String {
+    let hyphen = if line.hyphenated { "-" } else { "" };
+    format!("{}{hyphen}", &config.text[line.range.clone()])
+}
+
+/// Wraps the text to `config.width`, hyphenating long words if
+/// `config.hyphenation` asks for it.
+fn wrap(config: &Config, font: &LoadedFont) -> Result<Vec<WrappedLine>, Box<dyn Error>> {
+    let dictionary = match config.hyphenation {
+        Some(language) => Some(Standard::from_embedded(language)?),
+        None => None,
+    };
+    let measure = |line: &str| shape_text(&font.hb_font, line, &[]).width;
+    Ok(wrap_hyphenated(&config.text, config.width, measure, |word| {
+        // The patterns are for lowercase words. Lowercasing can change the
+        // length of the word (e.g. "İ"), and then the break points wouldn't
+        // line up with the original, so such words aren't hyphenated.
+        let lowercase = word.to_lowercase();
+        match &dictionary {
+            Some(dictionary) if lowercase.len() == word.len() => {
+                dictionary.hyphenate(&lowercase).breaks
+            }
+            _ => Vec::new(),
+        }
+    }))
+}
+
+/// Lines shaped by earlier renders, for an editor that re-renders on every
+/// keystroke: only lines whose text (or bidi levels) changed get reshaped.
+///
+/// Lines shaped with another font or size aren't reused, so one cache can
+/// be kept across those changing too. Wrapping still measures the whole
+/// text, since an edit can move words between lines.
+#[derive(Default)]
+pub struct LineCache {
+    lines: HashMap<LineKey, ShapedLine>,
+    reshapes: usize,
+}
+
+impl LineCache {
+    /// How many lines have been shaped (cache misses) so far.
+    pub fn reshapes(&self) -> usize {
+        self.reshapes
+    }
+}
+
+/// What a shaped line depends on: the font and size, its text, and the
+/// bidi levels from the paragraph around it (the same words can be ordered
+/// differently in an RTL paragraph).
+#[derive(PartialEq, Eq, Hash)]
+struct LineKey {
+    font_path: PathBuf,
+    /// The font size's bits, as `f32` isn't `Hash`.
+    font_size: u32,
+    text: String,
+    paragraph_level: u8,
+    levels: Vec<u8>,
+    hyphenated: bool,
+}
+
+/// A line ready to draw: its blob has the line's start on the baseline at
+/// (0, 0).
+#[derive(Clone)]
+struct ShapedLine {
+    blob: Option<TextBlob>,
+    width: f32,
+}
+
+/// The bidi paragraph that the text at `offset` is part of.
+fn paragraph_at<'a>(bidi_info: &'a BidiInfo, offset: usize) -> Option<&'a ParagraphInfo> {
+    // An empty last line (after a trailing `\n`) starts past the last
+    // paragraph's range, so search from the end for the last one that starts
+    // at or before `offset`.
+    bidi_info.paragraphs.iter().rev().find(|paragraph| paragraph.range.start <= offset)
+}
+
+/// One directional run of a line, shaped and in visual order.
+struct LineRun {
+    glyphs: Vec<u16>,
+    /// Glyph positions relative to the start of the run.
+    positions: Vec<Point>,
+    width: f32,
+}
+
+/// Splits the line `line` (a byte range into the paragraph) into directional
+/// runs in visual (left-to-right) order, each with whether it's
+/// right-to-left.
+fn directional_runs(
+    bidi_info: &BidiInfo,
+    paragraph: &ParagraphInfo,
+    line: Range<usize>,
+) -> Vec<(Range<usize>, bool)> {
+    if line.is_empty() {
+        return Vec::new();
+    }
+    let (levels, runs) = bidi_info.visual_runs(paragraph, line);
+    runs.into_iter()
+        .map(|run| {
+            let rtl = levels[run.start].is_rtl();
+            (run, rtl)
+        })
+        .collect()
+}
+
+/// Splits the line `line` into directional runs in visual order (see
+/// `directional_runs`) and shapes each in its direction.
+fn shape_line(
+    hb_font: &HbFont,
+    bidi_info: &BidiInfo,
+    paragraph: &ParagraphInfo,
+    line: Range<usize>,
+) -> Vec<LineRun> {
+    let text = bidi_info.text;
+    directional_runs(bidi_info, paragraph, line)
+        .into_iter()
+        .map(|(run, rtl)| {
+            let direction = if rtl { Direction::Rtl } else { Direction::Ltr };
+            // Script and language are guessed from the run's text.
+            let buffer = UnicodeBuffer::new()
+                .add_str(&text[run])
+                .set_direction(direction)
+                .guess_segment_properties();
+            let shaped = shape(hb_font, buffer, &[]);
+
+            // HarfBuzz already returns RTL glyphs in visual order, so each run
+            // is laid out left-to-right like an LTR one.
+            let mut line_run = LineRun {
+                glyphs: Vec::new(),
+                positions: Vec::new(),
+                width: 0.0,
+            };
+            for (info, pos) in shaped.get_glyph_infos().iter().zip(shaped.get_glyph_positions()) {
+                // 26.6 fixed point => divide by 64. HarfBuzz's y axis points up.
+                let x_offset = pos.x_offset as f32 / 64.0;
+                let y_offset = pos.y_offset as f32 / 64.0;
+                line_run.glyphs.push(info.codepoint as u16);
+                line_run.positions.push(Point::new(line_run.width + x_offset, -y_offset));
+                line_run.width += pos.x_advance as f32 / 64.0;
+            }
+            line_run
+        })
+        .collect()
+}
+
+/// The hyphen drawn after a hyphenated line, as a run of its own.
+fn shape_hyphen(hb_font: &HbFont) -> LineRun {
+    let shaped = shape_text(hb_font, "-", &[]);
+    LineRun {
+        glyphs: shaped.glyphs,
+        positions: shaped.positions,
+        width: shaped.width,
+    }
+}
+
+/// Appends the runs of one line to `builder`, starting at `origin`.
+fn add_runs(builder: &mut TextBlobBuilder, skia_font: &Font, runs: &[LineRun], origin: Point) {
+    let mut x = origin.x;
+    for run in runs.iter().filter(|run| !run.glyphs.is_empty()) {
+        let (glyphs, positions) = builder.alloc_run_pos(skia_font, run.glyphs.len(), None);
+        glyphs.copy_from_slice(&run.glyphs);
+        for (position, run_position) in positions.iter_mut().zip(&run.positions) {
+            *position = Point::new(x, origin.y) + *run_position;
+        }
+        x += run.width;
+    }
+}
+
+/// What to draw and how; `Config::default()` gives the plain example.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub text: String,
+    /// Wrapping width in pixels.
+    pub width: f32,
+    /// The paragraph direction, or `None` to detect it from the text.
+    pub direction: Option<Level>,
+    /// Make the image only as wide as the widest line, instead of `width`.
+    pub fit_width: bool,
+    /// Break long words with a hyphen, using this language's hyphenation
+    /// patterns, or `None` to only break between words.
+    pub hyphenation: Option<Language>,
+    /// Also save what's drawn as a serialized picture (.skp) at this path.
+    pub dump_picture: Option<PathBuf>,
+    /// The font to draw with. It needs to cover every script in the text;
+    /// there is no font fallback here.
+    pub font_path: PathBuf,
+    /// The distance between baselines as a multiple of the font size, or
+    /// `None` for the font's own line spacing (ascent + descent + line gap).
+    pub line_height: Option<f32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            text: "Arabic for \"hello, world\" is مرحبا بالعالم, and it reads from right to left."
+                .to_string(),
+            width: 300.0,
+            direction: None,
+            fit_width: false,
+            hyphenation: None,
+            dump_picture: None,
+            font_path: "Rubik-VariableFont_wght.ttf".into(),
+            line_height: None,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use common::output::write_output;
+use example_7::{render, Config, LineCache};
+use std::env;
+use std::error::Error;
+use hyphenation::Language;
+use unicode_bidi::Level;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (config, insertion, out_path) = config_from_args()?;
+    let mut cache = LineCache::default();
+    let mut png_bytes = render(&config, &mut cache)?;
+
+    // With --insert, edit the text like a keystroke in an editor would and
+    // render again with the same cache: only the edited line gets reshaped.
+    if let Some((char_index, inserted)) = insertion {
+        let mut edited = config.clone();
+        let byte_index = edited
+            .text
+            .char_indices()
+            .nth(char_index)
+            .map_or(edited.text.len(), |(i, _)| i);
+        edited.text.insert_str(byte_index, &inserted);
+        let before = cache.reshapes();
+        png_bytes = render(&edited, &mut cache)?;
+        eprintln!("Reshaped {} lines after the edit", cache.reshapes() - before);
+    }
+    let path = out_path.as_deref().unwrap_or("output_lines.png");
+    write_output(path, &png_bytes)?;
+
+    eprintln!("Image written to {path}");
+    Ok(())
+}
+
+/// The `Config`, an edit to make (character index, inserted text) and where to
+/// write the image.
+type Args = (Config, Option<(usize, String)>, Option<String>);
+
+/// Reads the `Config` from the command line, e.g. `cargo run -- --width 250 --direction rtl`
+/// or `--hyphenate en-us`,
+/// along with an optional edit (`--insert 12 X` inserts "X" before the 12th character) and
+/// output path (`--out`, where `-` is stdout).
+fn config_from_args() -> Result<Args, Box<dyn Error>> {
+    let mut config = Config::default();
+    let mut insertion = None;
+    let mut out_path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--text" => config.text = args.next().ok_or("--text expects a string")?,
+            "--width" => {
+                let value = args.next().ok_or("--width expects a width in pixels")?;
+                config.width = value.parse()?;
+            }
+            "--direction" => {
+                config.direction = match args.next().as_deref() {
+                    Some("ltr") => Some(Level::ltr()),
+                    Some("rtl") => Some(Level::rtl()),
+                    _ => return Err("--direction expects `ltr` or `rtl`".into()),
+                }
+            }
+            "--fit-width" => config.fit_width = true,
+            "--dump-picture" => {
+                let path = args.next().ok_or("--dump-picture expects a file path")?;
+                config.dump_picture = Some(path.into());
+            }
+            "--hyphenate" => {
+                config.hyphenation = Some(match args.next().as_deref() {
+                    Some("en-us") => Language::EnglishUS,
+                    Some("en-gb") => Language::EnglishGB,
+                    Some("de") => Language::German1996,
+                    Some("fr") => Language::French,
+                    Some("es") => Language::Spanish,
+                    Some("nl") => Language::Dutch,
+                    _ => return Err("--hyphenate expects en-us, en-gb, de, fr, es or nl".into()),
+                })
+            }
+            "--insert" => {
+                let index = args.next().ok_or("--insert expects a character index and text")?;
+                let text = args.next().ok_or("--insert expects a character index and text")?;
+                insertion = Some((index.parse()?, text));
+            }
+            "--font" => config.font_path = args.next().ok_or("--font expects a path")?.into(),
+            "--line-height" => {
+                let value = args.next().ok_or("--line-height expects a multiple, e.g. 1.5")?;
+                config.line_height = Some(value.parse()?);
+            }
+            "--out" => out_path = Some(args.next().ok_or("--out expects a path, or -")?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    Ok((config, insertion, out_path))
+}
@@ -0,0 +1,270 @@
+use example_7::{
+    clamp_lines, line_baselines, render, run, visual_lines, wrapped_lines, Config, LineCache,
+    VisualLine,
+};
+use hyphenation::Language;
+use common::raster::read_rgba;
+use skia_safe::{AlphaType, Data, Image};
+use std::env;
+use std::fs;
+use std::path::Path;
+use unicode_bidi::Level;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[test]
+fn renders_a_png() {
+    let png = run(&Config::default()).unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn editing_one_line_reshapes_only_that_line() {
+    let mut config = Config {
+        text: "First line\nSecond line\nThird line".to_string(),
+        ..Config::default()
+    };
+    let mut cache = LineCache::default();
+    render(&config, &mut cache).unwrap();
+    assert_eq!(cache.reshapes(), 3);
+
+    // Type a character into the middle line.
+    config.text = "First line\nSecond, line\nThird line".to_string();
+    let png = render(&config, &mut cache).unwrap();
+    assert_eq!(cache.reshapes(), 4);
+
+    // The reused lines must look exactly like freshly shaped ones.
+    assert_eq!(png, run(&config).unwrap());
+}
+
+#[test]
+fn lines_shaped_with_another_font_are_not_reused() {
+    let config = Config {
+        text: "First line\nSecond line".to_string(),
+        ..Config::default()
+    };
+    let mut cache = LineCache::default();
+    render(&config, &mut cache).unwrap();
+    assert_eq!(cache.reshapes(), 2);
+
+    // The same text in another font has other glyphs and advances.
+    let other_font = Config {
+        font_path: "../example-1/Roboto-LightItalic.ttf".into(),
+        ..config
+    };
+    let png = render(&other_font, &mut cache).unwrap();
+    assert_eq!(cache.reshapes(), 4);
+    assert_eq!(png, run(&other_font).unwrap());
+}
+
+/// The height of `png`, and the rows that have dark (text) pixels in them.
+/// The light blue guides at the wrapping edges don't count.
+fn text_rows(png: &[u8]) -> (i32, Vec<i32>) {
+    let image = Image::from_encoded(Data::new_copy(png)).unwrap();
+    let pixels = read_rgba(&image, AlphaType::Premul).unwrap();
+    let row_bytes = image.width() as usize * 4;
+    let rows = (0..image.height())
+        .filter(|&y| {
+            let row = &pixels[y as usize * row_bytes..][..row_bytes];
+            row.chunks(4).any(|pixel| pixel[0] < 128)
+        })
+        .collect();
+    (image.height(), rows)
+}
+
+#[test]
+fn long_paragraph_fits_vertically() {
+    let config = Config {
+        text: "The surface is sized from the wrapped lines, so however long the \
+               paragraph is, and however narrow the wrapping width, the first \
+               and last lines keep their margins and nothing is clipped."
+            .to_string(),
+        width: 150.0,
+        ..Config::default()
+    };
+    let (height, rows) = text_rows(&run(&config).unwrap());
+
+    // The margin is 20px; glyphs don't quite reach the font's ascent and
+    // descent, so there's at least that much blank space above and below.
+    assert!(rows.len() > 5 * 20, "expected several lines of text");
+    assert!(rows[0] >= 20, "first line clipped: starts at row {}", rows[0]);
+    assert!(*rows.last().unwrap() < height - 20, "last line clipped");
+}
+
+#[test]
+fn long_word_is_hyphenated() {
+    // "hyphenation" is about 114px wide, so it needs two lines at 90px.
+    let mut config = Config {
+        text: "hyphenation".to_string(),
+        width: 90.0,
+        ..Config::default()
+    };
+    let lines = wrapped_lines(&config).unwrap();
+    assert!(lines.iter().all(|line| !line.ends_with('-')), "{lines:?}");
+
+    config.hyphenation = Some(Language::EnglishUS);
+    let lines = wrapped_lines(&config).unwrap();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    assert!(lines[0].ends_with('-'), "{lines:?}");
+    assert_eq!(lines.concat().replace('-', ""), "hyphenation");
+    assert!(run(&config).unwrap().starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn long_paragraph_is_clamped_to_two_lines() {
+    let config = Config {
+        text: "The quick brown fox jumps over the lazy dog, and then it does it again, \
+               because once is never enough for a fox."
+            .to_string(),
+        width: 150.0,
+        ..Config::default()
+    };
+    let all_lines = wrapped_lines(&config).unwrap();
+    assert!(all_lines.len() > 2, "{all_lines:?}");
+
+    let clamped = clamp_lines(&config, 2).unwrap();
+    assert!(clamped.overflowed);
+    assert_eq!(clamped.lines.len(), 2);
+    assert_eq!(clamped.lines[0], all_lines[0]);
+    // The second line is cut short (or not at all) to make room for "…".
+    let shown = clamped.lines[1].strip_suffix('…').expect("no ellipsis");
+    assert!(all_lines[1].starts_with(shown), "{:?}", clamped.lines);
+
+    // Text that fits comes back as it was.
+    let short = clamp_lines(&Config { text: "A fox.".to_string(), ..config }, 2).unwrap();
+    assert!(!short.overflowed);
+    assert_eq!(short.lines, ["A fox."]);
+}
+
+#[test]
+fn single_long_word_is_clamped_too() {
+    let config = Config {
+        text: "Supercalifragilisticexpialidocious".to_string(),
+        width: 90.0,
+        ..Config::default()
+    };
+    let clamped = clamp_lines(&config, 2).unwrap();
+    assert!(clamped.overflowed);
+    assert_eq!(clamped.lines.len(), 2);
+    assert!(clamped.lines[1].ends_with('…'), "{:?}", clamped.lines);
+    assert!(config.text.starts_with(&clamped.lines.concat().replace('…', "")));
+}
+
+/// Where `tag`'s table starts in the font `data`, from the table directory.
+fn table_offset(data: &[u8], tag: &[u8; 4]) -> usize {
+    let num_tables = u16::from_be_bytes([data[4], data[5]]) as usize;
+    let record = (0..num_tables)
+        .map(|i| &data[12 + 16 * i..][..16])
+        .find(|record| &record[..4] == tag)
+        .unwrap();
+    u32::from_be_bytes(record[8..12].try_into().unwrap()) as usize
+}
+
+fn read_i16(data: &[u8], offset: usize) -> i16 {
+    i16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+#[test]
+fn lines_are_spaced_by_the_fonts_line_gap() {
+    // Rubik has no line gap, so give a copy of it one. It goes in `hhea`,
+    // and in `OS/2` too (sTypoLineGap): Rubik sets USE_TYPO_METRICS, which
+    // makes Skia read that table's metrics instead.
+    let gap: i16 = 400;
+    let mut data = fs::read("Rubik-VariableFont_wght.ttf").unwrap();
+    let hhea = table_offset(&data, b"hhea");
+    let os2 = table_offset(&data, b"OS/2");
+    for offset in [hhea + 8, os2 + 72] {
+        data[offset..offset + 2].copy_from_slice(&gap.to_be_bytes());
+    }
+    let font_path = env::temp_dir().join("example-7-line-gap.ttf");
+    fs::write(&font_path, &data).unwrap();
+
+    // The reference: ascender, descender and line gap in font units, scaled
+    // to 20px.
+    let (ascender, descender) = (read_i16(&data, hhea + 4), read_i16(&data, hhea + 6));
+    let head = table_offset(&data, b"head");
+    let units_per_em = u16::from_be_bytes([data[head + 18], data[head + 19]]);
+    let line_height =
+        |gap: i16| (ascender - descender + gap) as f32 * 20.0 / units_per_em as f32;
+
+    // The distance between the tops of three identical lines.
+    let line_pitch = |font_path: &Path| {
+        let config = Config {
+            text: "H\nH\nH".to_string(),
+            font_path: font_path.into(),
+            ..Config::default()
+        };
+        let (_, rows) = text_rows(&run(&config).unwrap());
+        let tops: Vec<i32> =
+            rows.iter().copied().filter(|row| !rows.contains(&(row - 1))).collect();
+        assert_eq!(tops.len(), 3, "rows {rows:?}");
+        (tops[2] - tops[0]) as f32 / 2.0
+    };
+
+    let with_gap = line_pitch(&font_path);
+    assert!((with_gap - line_height(gap)).abs() <= 1.0, "{with_gap} vs {}", line_height(gap));
+    let without = line_pitch(Path::new("Rubik-VariableFont_wght.ttf"));
+    assert!((without - line_height(0)).abs() <= 1.0, "{without} vs {}", line_height(0));
+}
+
+#[test]
+fn baselines_are_one_line_height_apart() {
+    let mut config = Config {
+        text: "One line\nand another,\nand a third.".to_string(),
+        line_height: Some(1.5),
+        ..Config::default()
+    };
+    let baselines = line_baselines(&config).unwrap();
+    assert_eq!(baselines.len(), 3);
+    // 1.5 times the 20px font size.
+    for pair in baselines.windows(2) {
+        assert!((pair[1] - pair[0] - 30.0).abs() < 1e-3, "{baselines:?}");
+    }
+
+    // Without --line-height, the lines are still evenly spaced, by the
+    // font's own (smaller) line spacing.
+    config.line_height = None;
+    let baselines = line_baselines(&config).unwrap();
+    let line_height = baselines[1] - baselines[0];
+    assert!(line_height > 20.0 && line_height < 30.0, "{baselines:?}");
+    for pair in baselines.windows(2) {
+        assert!((pair[1] - pair[0] - line_height).abs() < 1e-3, "{baselines:?}");
+    }
+}
+
+#[test]
+fn mixed_paragraph_is_reordered_line_by_line() {
+    // Two lines: "Hello مرحبا " and "بالعالم world".
+    let mut config = Config {
+        text: "Hello مرحبا بالعالم world".to_string(),
+        width: 150.0,
+        ..Config::default()
+    };
+    let line = |rtl, runs: &[(&str, bool)]| VisualLine {
+        rtl,
+        runs: runs.iter().map(|&(text, rtl)| (text.to_string(), rtl)).collect(),
+    };
+
+    // The paragraph starts with English, so it's left-to-right: each line
+    // reads left to right, with the Arabic words as right-to-left runs in
+    // between. The space at the end of a line goes back to the paragraph's
+    // direction.
+    assert_eq!(
+        visual_lines(&config).unwrap(),
+        [
+            line(false, &[("Hello ", false), ("مرحبا", true), (" ", false)]),
+            line(false, &[("بالعالم", true), (" world", false)]),
+        ],
+    );
+
+    // Forced right-to-left, the same lines start on the right: the English
+    // words end up on the left of the Arabic ones.
+    config.direction = Some(Level::rtl());
+    assert_eq!(
+        visual_lines(&config).unwrap(),
+        [
+            line(true, &[(" مرحبا ", true), ("Hello", false)]),
+            line(true, &[("world", false), ("بالعالم ", true)]),
+        ],
+    );
+}